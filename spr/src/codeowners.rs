@@ -0,0 +1,174 @@
+//! CODEOWNERS files, to find reviewers for the files a revision touches.
+//! Patterns follow GitHub's rules: gitignore style globs, and the last matching line wins.
+
+use git2::Oid;
+use lazy_regex::Regex;
+
+use crate::error::Result;
+
+/// Where GitHub looks for the file, in order.
+const LOCATIONS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug)]
+struct Rule {
+    pattern: Regex,
+    owners: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+pub(crate) fn pattern_regex(pattern: &str) -> Option<Regex> {
+    let pattern = pattern.strip_prefix('\\').unwrap_or(pattern);
+    let (anchored, pattern) = match pattern.strip_prefix('/') {
+        Some(pattern) => (true, pattern),
+        None => (false, pattern),
+    };
+    let (directory, pattern) = match pattern.strip_suffix('/') {
+        Some(pattern) => (true, pattern),
+        None => (false, pattern),
+    };
+    // A slash anywhere but at the end ties the pattern to the root.
+    let anchored = anchored || pattern.contains('/');
+    // Unlike in .gitignore, `docs/*` doesn't match files in subdirectories of `docs`.
+    let shallow = pattern.ends_with("/*") && !pattern.ends_with("**/*");
+
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut rest = pattern;
+    while let Some(c) = rest.chars().next() {
+        if let Some(r) = rest.strip_prefix("**/") {
+            regex.push_str("(?:.*/)?");
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("**") {
+            regex.push_str(".*");
+            rest = r;
+        } else {
+            match c {
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                c => regex.push_str(&lazy_regex::regex::escape(&c.to_string())),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    regex.push_str(if directory {
+        "/.*$"
+    } else if shallow {
+        "$"
+    } else {
+        "(?:/.*)?$"
+    });
+
+    Regex::new(&regex).ok()
+}
+
+/// The name to request a review from. `@user` is a user and `@org/team` a team. Owners given
+/// by email can't be requested.
+fn reviewer(owner: &str) -> Option<String> {
+    owner.strip_prefix('@').map(String::from)
+}
+
+impl CodeOwners {
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .filter_map(|line| {
+                let line = match line.find(" #") {
+                    Some(index) => &line[..index],
+                    None => line,
+                };
+                let mut words = line.split_whitespace();
+                let pattern = words.next().filter(|p| !p.starts_with('#'))?;
+                Some(Rule {
+                    // GitHub skips lines it can't parse as well.
+                    pattern: pattern_regex(pattern)?,
+                    owners: words.filter_map(reviewer).collect(),
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Read the CODEOWNERS file from the tree of `commit`, if it has one.
+    pub fn from_commit(repo: &git2::Repository, commit: Oid) -> Result<Option<Self>> {
+        let tree = repo.find_commit(commit)?.tree()?;
+        for location in LOCATIONS {
+            let Ok(entry) = tree.get_path(std::path::Path::new(location)) else {
+                continue;
+            };
+            let blob = repo.find_blob(entry.id())?;
+            return Ok(Some(Self::parse(&String::from_utf8_lossy(blob.content()))));
+        }
+        Ok(None)
+    }
+
+    /// Owners of a single file. The last matching rule wins, even if it has no owners.
+    pub fn owners(&self, path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.pattern.is_match(path))
+            .map_or(&[], |rule| rule.owners.as_slice())
+    }
+
+    /// Owners of all `paths`, without duplicates, in the order they first show up.
+    pub fn owners_of<I, S>(&self, paths: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut ret: Vec<String> = Vec::new();
+        for path in paths {
+            for owner in self.owners(path.as_ref()) {
+                if !ret.contains(owner) {
+                    ret.push(owner.clone());
+                }
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CodeOwners;
+
+    #[test]
+    fn matches_like_github() {
+        let owners = CodeOwners::parse(
+            r#"
+# Comment
+*       @global
+*.js    @js-owner #This is an inline comment.
+**/logs @logs
+/build/logs/ @doctocat
+docs/*  docs@example.com @docs
+apps/   @octocat
+/scripts/ @myorg/scripts
+/scripts/generated
+"#,
+        );
+
+        assert_eq!(owners.owners("README.md"), ["global"]);
+        assert_eq!(owners.owners("src/index.js"), ["js-owner"]);
+        assert_eq!(owners.owners("build/logs/out.txt"), ["doctocat"]);
+        assert_eq!(owners.owners("docs/getting-started.md"), ["docs"]);
+        assert_eq!(owners.owners("docs/build-app/trouble.md"), ["global"]);
+        assert_eq!(owners.owners("nested/apps/main.rs"), ["octocat"]);
+        assert_eq!(owners.owners("deep/down/logs/today"), ["logs"]);
+        assert_eq!(owners.owners("scripts/run.sh"), ["myorg/scripts"]);
+        // A later rule without owners takes the files away again.
+        assert!(owners.owners("scripts/generated/run.sh").is_empty());
+    }
+
+    #[test]
+    fn collects_owners_once() {
+        let owners = CodeOwners::parse("*.rs @alice @myorg/rust\n*.md @bob @alice\n");
+        assert_eq!(
+            owners.owners_of(["src/main.rs", "README.md", "src/lib.rs", "Cargo.lock"]),
+            vec!["alice", "myorg/rust", "bob"]
+        );
+    }
+}
@@ -5,8 +5,10 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::VecDeque;
+
 use crate::{
-    error::Result,
+    error::{Error, Result},
     jj::RevSet,
     message::{MessageSection, MessageSectionsMap, build_commit_message},
 };
@@ -19,6 +21,18 @@ pub struct AdoptOptions {
     /// If given, create new branch but do not check out
     #[clap(long)]
     no_checkout: bool,
+
+    /// Also adopt all open Pull Requests stacked on top of it, recursively
+    #[clap(long)]
+    stack: bool,
+}
+
+/// A PR that ended up in the local tree, for the summary at the end.
+struct Adopted {
+    number: u64,
+    parent: Option<u64>,
+    title: String,
+    reused: bool,
 }
 
 fn find_commit_for_pr(
@@ -28,9 +42,23 @@ fn find_commit_for_pr(
 ) -> Result<crate::jj::Revision> {
     let url = config.pull_request_url(nr);
 
-    let id =
-        jj.revset_to_change_id(&RevSet::description(format!("substring:\"{}\"", url)).unique())?;
-    jj.read_revision(id)
+    // The URL of #1 is also the start of the one of #10, so only revisions whose `Pull Request`
+    // section names exactly this PR count.
+    let mut revisions: Vec<_> = jj
+        .read_revision_range(
+            config,
+            &RevSet::description(format!("substring:\"{}\"", url)),
+        )?
+        .into_iter()
+        .filter(|rev| rev.pull_request_number == Some(nr))
+        .collect();
+    match (revisions.pop(), revisions.is_empty()) {
+        (Some(revision), true) => Ok(revision),
+        _ => Err(Error::new(format!(
+            "Could not find unique revision for {}",
+            url
+        ))),
+    }
 }
 
 fn do_adopt(
@@ -40,11 +68,12 @@ fn do_adopt(
     branch_name: &str,
     parent: Option<u64>,
 ) -> Result<()> {
-    jj.run_git_fetch()?;
+    jj.run_git_fetch_for(config)?;
+    // The head is in the fork for PRs pushed there.
+    let remote = config.branch_remote(&jj.git_repo, branch_name).to_string();
 
-    let resolved = jj.resolve_reference(
-        format!("refs/remotes/{}/{}", config.remote_name, branch_name).as_str(),
-    )?;
+    let resolved =
+        jj.resolve_reference(format!("refs/remotes/{}/{}", remote, branch_name).as_str())?;
 
     let mut message = message.clone();
     message.insert(MessageSection::LastCommit, resolved.to_string());
@@ -52,15 +81,14 @@ fn do_adopt(
 
     let head_revset = {
         let head_branch = jj.git_repo.find_branch(
-            format!("{}/{}", config.remote_name, branch_name).as_ref(),
+            format!("{}/{}", remote, branch_name).as_ref(),
             git2::BranchType::Remote,
         )?;
-        RevSet::from_remote_branch(&head_branch, config.remote_name.clone())?.unique()
+        RevSet::from_remote_branch(&head_branch, remote.clone())?.unique()
     };
 
     let base_revset = if let Some(parent) = parent {
-        let url = config.pull_request_url(parent);
-        RevSet::description(format!("substring:\"{}\"", url)).unique()
+        RevSet::from(&find_commit_for_pr(jj, config, parent)?.id).unique()
     } else {
         let base_branch = jj.git_repo.find_branch(
             format!("{}/{}", config.remote_name, config.master_ref).as_str(),
@@ -78,7 +106,7 @@ fn do_adopt(
         None as Option<&str>,
         Some(format!(
             "exactly(remote_bookmarks({}, {}), 1)",
-            branch_name, config.remote_name
+            branch_name, remote
         )),
         Some("@"),
     )?;
@@ -116,21 +144,91 @@ where
         pr_chain.push((next, None));
     }
 
+    let mut adopted = Vec::new();
     for (pr, parent) in pr_chain.into_iter().rev() {
-        let sections = MessageSectionsMap::from([
-            (MessageSection::Title, pr.title().into()),
-            (MessageSection::Summary, pr.body().into()),
-            (
-                MessageSection::PullRequest,
-                config.pull_request_url(pr.pr_number()),
-            ),
-        ]);
-        do_adopt(jj, config, &sections, pr.head_branch_name(), parent)?;
+        // When taking over a whole stack, the PR itself may have been adopted before.
+        let reused = opts.stack && find_commit_for_pr(jj, config, pr.pr_number()).is_ok();
+        if !reused {
+            adopt_pr(jj, config, &pr, parent)?;
+        }
+        adopted.push(Adopted {
+            number: pr.pr_number(),
+            parent,
+            title: pr.title().to_string(),
+            reused,
+        });
+    }
+
+    if opts.stack {
+        // Breadth first, so every PR is adopted after the one it is based on.
+        let root = gh.pull_request(opts.pull_request).await?;
+        let mut queue = VecDeque::from([(root.head_branch_name().to_string(), root.pr_number())]);
+        while let Some((head, number)) = queue.pop_front() {
+            for child in gh.open_pull_requests_by_base(head).await? {
+                if adopted.iter().any(|a| a.number == child.pr_number()) {
+                    continue;
+                }
+
+                let reused = find_commit_for_pr(jj, config, child.pr_number()).is_ok();
+                if !reused {
+                    adopt_pr(jj, config, &child, Some(number))?;
+                }
+                adopted.push(Adopted {
+                    number: child.pr_number(),
+                    parent: Some(number),
+                    title: child.title().to_string(),
+                    reused,
+                });
+                queue.push_back((child.head_branch_name().to_string(), child.pr_number()));
+            }
+        }
+    }
+
+    let mut forest = crate::tree::Forest::new();
+    for pr in adopted {
+        let parent = pr.parent;
+        forest.insert_below(&|a: &Adopted| Some(a.number) == parent, pr);
+    }
+    crate::output::output(&config.icons.ok, "Adopted:")?;
+    let term = console::Term::stdout();
+    for tree in forest.trees() {
+        for line in tree.draw(&config.drawing, &|a: &Adopted| {
+            format!(
+                "#{} {}{}",
+                a.number,
+                a.title,
+                if a.reused { " (already local)" } else { "" }
+            )
+        }) {
+            term.write_line(&line)?;
+        }
     }
 
     Ok(())
 }
 
+fn adopt_pr<PR>(
+    jj: &mut crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    pr: &PR,
+    parent: Option<u64>,
+) -> Result<()>
+where
+    PR: crate::github::GHPullRequest,
+{
+    let (title, summary) = crate::snapshot::remote(pr);
+    let mut sections = MessageSectionsMap::from([
+        (MessageSection::Title, title.clone()),
+        (MessageSection::Summary, summary.clone()),
+        (
+            MessageSection::PullRequest,
+            config.pull_request_url(pr.pr_number()),
+        ),
+    ]);
+    crate::snapshot::update(&mut sections, &title, &summary)?;
+    do_adopt(jj, config, &sections, pr.head_branch_name(), parent)
+}
+
 #[cfg(test)]
 mod tests {
     use super::AdoptOptions;
@@ -151,6 +249,7 @@ mod tests {
             AdoptOptions {
                 pull_request: pr_nr,
                 no_checkout: true,
+                stack: false,
             },
             &mut jj,
             &mut crate::github::fakes::GitHub {
@@ -164,6 +263,7 @@ mod tests {
                         "",
                     ),
                 )]),
+                ..Default::default()
             },
             &testing::config::basic(),
         )
@@ -174,7 +274,7 @@ mod tests {
             .revset_to_change_id(&RevSet::current())
             .expect("Failed to resolve change of current");
         let rev = jj
-            .read_revision(change)
+            .read_revision(&testing::config::basic(), change)
             .expect("Failed to read revision after patch");
 
         let new_tree = jj
@@ -227,6 +327,7 @@ mod tests {
             AdoptOptions {
                 pull_request: other_nr,
                 no_checkout: true,
+                stack: false,
             },
             &mut jj,
             &mut crate::github::fakes::GitHub {
@@ -252,6 +353,7 @@ mod tests {
                         ),
                     ),
                 ]),
+                ..Default::default()
             },
             &testing::config::basic(),
         )
@@ -333,11 +435,13 @@ mod tests {
                     ),
                 ),
             ]),
+            ..Default::default()
         };
         super::adopt(
             AdoptOptions {
                 pull_request: pr_nr,
                 no_checkout: true,
+                stack: false,
             },
             &mut jj,
             &mut gh.clone(),
@@ -350,6 +454,7 @@ mod tests {
             AdoptOptions {
                 pull_request: other_nr,
                 no_checkout: true,
+                stack: false,
             },
             &mut jj,
             &mut gh.clone(),
@@ -397,4 +502,157 @@ mod tests {
             "Adopted revision should have the same tree as upstream branch"
         );
     }
+
+    #[tokio::test]
+    async fn tells_apart_prs_sharing_a_prefix() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/one");
+        let _ = testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/ten");
+        let gh = crate::github::fakes::GitHub {
+            pull_requests: std::collections::BTreeMap::from([
+                (
+                    1,
+                    crate::github::fakes::PullRequest::new("main", "spr/test/one", 1, "One", ""),
+                ),
+                (
+                    10,
+                    crate::github::fakes::PullRequest::new("main", "spr/test/ten", 10, "Ten", ""),
+                ),
+            ]),
+            ..Default::default()
+        };
+
+        for (nr, stack) in [(10, false), (1, true)] {
+            super::adopt(
+                AdoptOptions {
+                    pull_request: nr,
+                    no_checkout: true,
+                    stack,
+                },
+                &mut jj,
+                &mut gh.clone(),
+                &config,
+            )
+            .await
+            .expect("adopt() should not fail");
+        }
+
+        let one = super::find_commit_for_pr(&jj, &config, 1).expect("#1 should be adopted");
+        let ten = super::find_commit_for_pr(&jj, &config, 10).expect("#10 should be adopted");
+        assert_eq!(one.pull_request_number, Some(1));
+        assert_eq!(ten.pull_request_number, Some(10));
+        assert_ne!(one.id, ten.id, "#1 shouldn't be taken for #10");
+    }
+
+    #[tokio::test]
+    async fn whole_stack() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let bottom_oid =
+            testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/bottom");
+        let left_oid = testing::git::add_commit_on_and_push_to_remote(
+            &jj.git_repo,
+            "spr/test/left",
+            [bottom_oid],
+        );
+        let _ = testing::git::add_commit_on_and_push_to_remote(
+            &jj.git_repo,
+            "spr/test/right",
+            [bottom_oid],
+        );
+        let _ = testing::git::add_commit_on_and_push_to_remote(
+            &jj.git_repo,
+            "spr/test/closed",
+            [left_oid],
+        );
+
+        let mut closed = crate::github::fakes::PullRequest::new(
+            "spr/test/left",
+            "spr/test/closed",
+            4,
+            "Closed",
+            "",
+        );
+        closed.closed = true;
+        let gh = crate::github::fakes::GitHub {
+            pull_requests: std::collections::BTreeMap::from([
+                (
+                    1,
+                    crate::github::fakes::PullRequest::new(
+                        "main",
+                        "spr/test/bottom",
+                        1,
+                        "Bottom",
+                        "",
+                    ),
+                ),
+                (
+                    2,
+                    crate::github::fakes::PullRequest::new(
+                        "spr/test/bottom",
+                        "spr/test/left",
+                        2,
+                        "Left",
+                        "",
+                    ),
+                ),
+                (
+                    3,
+                    crate::github::fakes::PullRequest::new(
+                        "spr/test/bottom",
+                        "spr/test/right",
+                        3,
+                        "Right",
+                        "",
+                    ),
+                ),
+                (4, closed),
+            ]),
+            ..Default::default()
+        };
+
+        // The bottom PR is already local and has to be reused.
+        super::adopt(
+            AdoptOptions {
+                pull_request: 1,
+                no_checkout: true,
+                stack: false,
+            },
+            &mut jj,
+            &mut gh.clone(),
+            &config,
+        )
+        .await
+        .expect("adopt() should not fail");
+        super::adopt(
+            AdoptOptions {
+                pull_request: 1,
+                no_checkout: true,
+                stack: true,
+            },
+            &mut jj,
+            &mut gh.clone(),
+            &config,
+        )
+        .await
+        .expect("adopt() should not fail");
+
+        let bottom = super::find_commit_for_pr(&jj, &config, 1)
+            .expect("Bottom PR should be adopted exactly once");
+        for nr in [2, 3] {
+            let rev =
+                super::find_commit_for_pr(&jj, &config, nr).expect("Stacked PR should be adopted");
+            assert_eq!(
+                rev.parent_ids,
+                vec![bottom.id.clone()],
+                "PR #{} should be based on the bottom PR",
+                nr
+            );
+        }
+        assert!(
+            super::find_commit_for_pr(&jj, &config, 4).is_err(),
+            "Closed PR shouldn't be adopted"
+        );
+    }
 }
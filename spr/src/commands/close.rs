@@ -0,0 +1,305 @@
+use std::iter::zip;
+
+use crate::{
+    error::{Error, Result},
+    github::GHPullRequest,
+    jj::RevSet,
+};
+
+#[derive(Debug, clap::Parser, Default)]
+pub struct CloseOptions {
+    /// Revisions whose PRs should be closed
+    #[clap(long, short = 'r')]
+    revset: Option<String>,
+
+    /// Comment to leave on each PR before closing it
+    #[clap(long, short = 'm')]
+    message: Option<String>,
+
+    /// Also close open PRs that are stacked on top of the closed ones
+    #[clap(long)]
+    cascade: bool,
+}
+
+#[cfg(test)]
+impl CloseOptions {
+    pub fn with_revset<S>(mut self, revset: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.revset = revset.map(|s| s.into());
+        self
+    }
+
+    pub fn with_message<S>(mut self, message: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.message = message.map(|s| s.into());
+        self
+    }
+
+    pub fn with_cascade(mut self, val: bool) -> Self {
+        self.cascade = val;
+        self
+    }
+}
+
+pub async fn close<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    opts: CloseOptions,
+) -> Result<()>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let with_pr = RevSet::mutable().and(&RevSet::description("glob:\"*Pull Request:*\""));
+    let selected = opts
+        .revset
+        .as_ref()
+        .map(RevSet::from_arg)
+        .unwrap_or(RevSet::current())
+        .and(&with_pr);
+    let selected_ids = jj.revset_to_change_ids(&selected)?;
+    if selected_ids.is_empty() {
+        crate::output::output(
+            &config.icons.wave,
+            "Nothing to be done. Either the revset was empty or none of the revisions have a PR attached.",
+        )?;
+        return Ok(());
+    }
+
+    // Descendants are read as well, so PRs stacked on top of the closed ones are noticed.
+    let revisions = jj.read_revision_range(config, &selected.descendants().and(&with_pr))?;
+    let pull_requests = gh
+        .pull_requests(revisions.iter().map(|r| r.pull_request_number))
+        .await?;
+    let (mut to_close, rest): (Vec<_>, Vec<_>) = zip(revisions, pull_requests)
+        .partition(|(revision, _)| opts.cascade || selected_ids.contains(&revision.id));
+
+    let closed_heads: Vec<String> = to_close
+        .iter()
+        .filter_map(|(_, pr)| pr.as_ref().map(|pr| pr.head_branch_name().to_string()))
+        .collect();
+    let mut blockers: Vec<u64> = rest
+        .iter()
+        .filter_map(|(_, pr)| pr.as_ref())
+        .filter(|pr| !pr.closed() && closed_heads.iter().any(|h| h == pr.base_branch_name()))
+        .map(|pr| pr.pr_number())
+        .collect();
+    // PRs based on a closed one may not be in the local stack, so ask GitHub as well.
+    let closed_numbers: Vec<u64> = to_close
+        .iter()
+        .filter_map(|(_, pr)| pr.as_ref().map(|pr| pr.pr_number()))
+        .collect();
+    for head in closed_heads.iter() {
+        for pr in gh.open_pull_requests_by_base(head).await? {
+            if !closed_numbers.contains(&pr.pr_number()) && !blockers.contains(&pr.pr_number()) {
+                blockers.push(pr.pr_number());
+            }
+        }
+    }
+    if !blockers.is_empty() {
+        let blockers: Vec<String> = blockers
+            .into_iter()
+            .map(|number| config.pull_request_url(number))
+            .collect();
+        return Err(Error::new(format!(
+            "Open PRs are based on the PRs to close: {}. Use --cascade to close them as well.",
+            blockers.join(", ")
+        )));
+    }
+
+    // Close from the top of the stack down, so no PR is left without its base in between.
+    to_close.reverse();
+    for (revision, pr) in to_close.iter() {
+        let Some(pr) = pr else {
+            crate::output::output(
+                &config.icons.error,
+                format!("Couldn't find the PR for {:?} on GitHub", revision.id),
+            )?;
+            continue;
+        };
+        let url = config.pull_request_url(pr.pr_number());
+        if pr.closed() {
+            crate::output::output(&config.icons.info, format!("{} is already closed", url))?;
+            continue;
+        }
+
+        if let Some(message) = opts.message.as_ref() {
+            gh.post_comment(pr, message).await?;
+        }
+        crate::output::output(&config.icons.stop, format!("Closing {}", url))?;
+        gh.close_pull_request(pr).await?;
+        gh.delete_branch(pr.head_branch_name()).await?;
+    }
+
+    // Drop the remote bookmarks of the deleted branches.
+    jj.run_git_fetch_for(config)?;
+    for (revision, _) in to_close.into_iter().rev() {
+        crate::commands::detach::detach_revision(jj, config, revision)?;
+    }
+
+    crate::output::output(&config.icons.wave, "Done")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        jj::{ChangeId, RevSet},
+        message::MessageSection,
+        testing,
+    };
+
+    async fn pushed_stack(
+        jj: &mut crate::jj::Jujutsu,
+        config: &crate::config::Config,
+    ) -> (ChangeId, ChangeId, crate::github::fakes::GitHub) {
+        let bottom = testing::jj::create_jujutsu_commit(jj, "Bottom commit", "bottom");
+        let top = testing::jj::create_jujutsu_commit(jj, "Top commit", "top");
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            jj,
+            &mut gh,
+            config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+        (bottom, top, gh)
+    }
+
+    #[tokio::test]
+    async fn closes_and_detaches() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let (_, top, mut gh) = pushed_stack(&mut jj, &config).await;
+
+        super::close(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::CloseOptions::default()
+                .with_revset(Some(RevSet::from(&top).as_ref()))
+                .with_message(Some("Not needed anymore")),
+        )
+        .await
+        .expect("Close shouldn't fail");
+
+        let closed = gh.pull_requests.get(&2).expect("Top PR should exist");
+        assert!(closed.closed, "Top PR wasn't closed");
+        assert!(!closed.merged, "Top PR shouldn't be merged");
+        assert!(
+            closed
+                .comments
+                .iter()
+                .any(|c| c.content == "Not needed anymore"),
+            "Closing comment wasn't posted"
+        );
+        assert!(
+            !gh.pull_requests
+                .get(&1)
+                .expect("Bottom PR should exist")
+                .closed,
+            "Bottom PR shouldn't be closed"
+        );
+
+        let top = jj
+            .read_revision(&config, top)
+            .expect("Top revision should remain");
+        assert!(top.pull_request_number.is_none());
+        assert!(!top.message.contains_key(&MessageSection::LastCommit));
+    }
+
+    #[tokio::test]
+    async fn refuses_stacked_without_cascade() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let (bottom, _, mut gh) = pushed_stack(&mut jj, &config).await;
+
+        super::close(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::CloseOptions::default().with_revset(Some(RevSet::from(&bottom).as_ref())),
+        )
+        .await
+        .expect_err("Close should refuse to orphan a stacked PR");
+
+        assert!(
+            gh.pull_requests.values().all(|pr| !pr.closed),
+            "No PR should be closed"
+        );
+        let bottom = jj
+            .read_revision(&config, bottom)
+            .expect("Bottom revision should remain");
+        assert_eq!(bottom.pull_request_number, Some(1));
+    }
+
+    #[tokio::test]
+    async fn refuses_pr_stacked_only_on_github() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let (_, top, mut gh) = pushed_stack(&mut jj, &config).await;
+        // Someone else stacked a PR on the top one, which isn't in the local repository.
+        let head = gh
+            .pull_requests
+            .get(&2)
+            .expect("Top PR should exist")
+            .head
+            .clone();
+        gh.pull_requests.insert(
+            3,
+            crate::github::fakes::PullRequest::new(head, "other/feature", 3, "Other", ""),
+        );
+
+        super::close(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::CloseOptions::default()
+                .with_revset(Some(RevSet::from(&top).as_ref()))
+                .with_cascade(true),
+        )
+        .await
+        .expect_err("Close should refuse to orphan a PR stacked on GitHub");
+
+        assert!(
+            gh.pull_requests.values().all(|pr| !pr.closed),
+            "No PR should be closed"
+        );
+    }
+
+    #[tokio::test]
+    async fn cascade_closes_stack() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let (bottom, top, mut gh) = pushed_stack(&mut jj, &config).await;
+
+        super::close(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::CloseOptions::default()
+                .with_revset(Some(RevSet::from(&bottom).as_ref()))
+                .with_cascade(true),
+        )
+        .await
+        .expect("Close shouldn't fail");
+
+        assert!(
+            gh.pull_requests.values().all(|pr| pr.closed),
+            "All PRs should be closed"
+        );
+        for change in [bottom, top] {
+            let revision = jj
+                .read_revision(&config, change)
+                .expect("Revision should remain");
+            assert!(revision.pull_request_number.is_none());
+        }
+    }
+}
@@ -15,6 +15,44 @@ impl DetachOptions {
     }
 }
 
+/// Strip the PR tracking sections from a single revision's description.
+pub(crate) fn detach_revision(
+    jj: &mut crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    mut revision: crate::jj::Revision,
+) -> crate::error::Result<()> {
+    revision
+        .message
+        .remove(&crate::message::MessageSection::LastCommit);
+    revision
+        .message
+        .remove(&crate::message::MessageSection::Synced);
+    revision
+        .message
+        .remove(&crate::message::MessageSection::SyncedLabels);
+    revision
+        .message
+        .remove(&crate::message::MessageSection::SyncedReviewers);
+    let pr = revision
+        .message
+        .remove(&crate::message::MessageSection::PullRequest);
+
+    jj.update_revision_message(&revision)?;
+    if let Some(pr) = pr {
+        crate::output::output(
+            &config.icons.info,
+            format!("Detached {} from {}", revision.id, pr),
+        )?;
+    } else {
+        // This should be unreachable by the revision selector further up, but ehh...
+        crate::output::output(
+            &config.icons.error,
+            format!("Detached {} from PR but don't know which one", revision.id),
+        )?;
+    }
+    Ok(())
+}
+
 pub async fn detach(
     jj: &mut crate::jj::Jujutsu,
     config: &crate::config::Config,
@@ -28,6 +66,7 @@ pub async fn detach(
 
     // These are only revisions that have a jj-spr style description AND can be modified.
     let revisions = jj.read_revision_range(
+        config,
         &revset.and(
             &crate::jj::RevSet::description("glob:\"*Pull Request:*\"")
                 .without(&crate::jj::RevSet::immutable()),
@@ -42,27 +81,8 @@ pub async fn detach(
         return Ok(());
     }
 
-    for mut revision in revisions.into_iter() {
-        revision
-            .message
-            .remove(&crate::message::MessageSection::LastCommit);
-        let pr = revision
-            .message
-            .remove(&crate::message::MessageSection::PullRequest);
-
-        jj.update_revision_message(&revision)?;
-        if let Some(pr) = pr {
-            crate::output::output(
-                &config.icons.info,
-                format!("Detached {} from {}", revision.id, pr),
-            )?;
-        } else {
-            // This should be unreachable by the revision selector further up, but ehh...
-            crate::output::output(
-                &config.icons.error,
-                format!("Detached {} from PR but don't know which one", revision.id),
-            )?;
-        }
+    for revision in revisions.into_iter() {
+        detach_revision(jj, config, revision)?;
     }
 
     crate::output::output(&config.icons.wave, "Done")?;
@@ -108,7 +128,7 @@ mod tests {
 
         let change = create_jujutsu_commit(
             &mut jj,
-            "My commit\n\nPull Request: https://github.com/Ongy/jj-spr/pull/1\nLast Commit: Some Such",
+            "My commit\n\nPull Request: https://github.com/test_owner/test_repo/pull/1\nLast Commit: Some Such",
             "content",
         );
 
@@ -122,7 +142,7 @@ mod tests {
         .expect("Detach shouldn't fail");
 
         let rev = jj
-            .read_revision(change)
+            .read_revision(&testing::config::basic(), change)
             .expect("Shouldn't fail to read revision after detaching it");
 
         assert!(rev.pull_request_number.is_none());
@@ -0,0 +1,327 @@
+use git2::Oid;
+
+use crate::{
+    error::{Error, Result},
+    github::GHPullRequest,
+    jj::RevSet,
+    message::MessageSection,
+};
+
+#[derive(Debug, clap::Parser, Default)]
+pub struct DiffOptions {
+    /// Revision to compare. Defaults to the closest ancestor of `@` with a Pull Request.
+    #[clap(long, short = 'r')]
+    revision: Option<String>,
+
+    /// Compare against the commit the latest review was left on, instead of the last pushed one
+    #[clap(long)]
+    since_review: bool,
+}
+
+#[cfg(test)]
+impl DiffOptions {
+    pub fn with_revision<S>(mut self, revision: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.revision = revision.map(|s| s.into());
+        self
+    }
+
+    pub fn with_since_review(mut self, val: bool) -> Self {
+        self.since_review = val;
+        self
+    }
+}
+
+/// Find the tree `old_head` would have if it was based on `new_base`.
+/// This keeps changes that came in through the base (e.g. trunk moving) out of the diff.
+fn rebased_tree(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    old_head: Oid,
+    base_branch: &str,
+    new_base: Oid,
+) -> Result<Oid> {
+    let repo = &jj.git_repo;
+    let old_head_tree = jj.get_tree_oid_for_commit(old_head)?;
+
+    // PR branches are only ever appended to, so the last base merged into the old head is the
+    // merge-base with the remote base branch. If that branch is gone, fall back to the new base.
+    let remote_base = repo
+        .revparse_single(
+            format!(
+                "{}/{}",
+                config.branch_remote(repo, base_branch),
+                base_branch
+            )
+            .as_str(),
+        )
+        .map_or(new_base, |o| o.id());
+    let old_base = repo.merge_base(old_head, remote_base)?;
+
+    let old_base_tree = jj.get_tree_oid_for_commit(old_base)?;
+    let new_base_tree = jj.get_tree_oid_for_commit(new_base)?;
+    if old_base_tree == new_base_tree {
+        return Ok(old_head_tree);
+    }
+
+    let mut index = repo.merge_trees(
+        &repo.find_tree(old_base_tree)?,
+        &repo.find_tree(new_base_tree)?,
+        &repo.find_tree(old_head_tree)?,
+        None,
+    )?;
+    if index.has_conflicts() {
+        crate::output::output(
+            &config.icons.info,
+            "The old head doesn't apply cleanly on the new base. Showing changes in the base as well.",
+        )?;
+        return Ok(old_head_tree);
+    }
+
+    Ok(index.write_tree_to(repo)?)
+}
+
+fn render_diff(repo: &git2::Repository, old_tree: Oid, new_tree: Oid) -> Result<Vec<String>> {
+    let diff = repo.diff_tree_to_tree(
+        Some(&repo.find_tree(old_tree)?),
+        Some(&repo.find_tree(new_tree)?),
+        None,
+    )?;
+
+    let mut lines = Vec::new();
+    diff.print(git2::DiffFormat::Patch, |_, _, line| {
+        let content = String::from_utf8_lossy(line.content());
+        let content = content.trim_end_matches('\n');
+        lines.push(match line.origin() {
+            origin @ ('+' | '-' | ' ') => format!("{}{}", origin, content),
+            _ => content.to_string(),
+        });
+        true
+    })?;
+    Ok(lines)
+}
+
+async fn collect_diff<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    opts: &DiffOptions,
+) -> Result<Vec<String>>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let revset = match opts.revision.as_ref() {
+        Some(revision) => RevSet::from_arg(revision).unique(),
+        None => RevSet::current()
+            .ancestors()
+            .and(&RevSet::mutable())
+            .and(&RevSet::description("glob:\"*Pull Request:*\""))
+            .heads()
+            .unique(),
+    };
+    let revision = jj.read_revision(config, jj.revset_to_change_id(&revset)?)?;
+    let number = revision.pull_request_number.ok_or_else(|| {
+        Error::new(format!(
+            "Revision {:?} doesn't have a pull request yet",
+            revision.id
+        ))
+    })?;
+    let pr = gh.pull_request(number).await?;
+
+    let old_head = if opts.since_review {
+        pr.reviewed_commit().ok_or_else(|| {
+            Error::new(format!(
+                "{} doesn't have any reviews yet",
+                config.pull_request_url(number)
+            ))
+        })?
+    } else {
+        revision
+            .message
+            .get(&MessageSection::LastCommit)
+            .ok_or_else(|| {
+                Error::new(format!(
+                    "Revision {:?} doesn't record a last pushed commit",
+                    revision.id
+                ))
+            })?
+    };
+    let old_head = Oid::from_str(old_head)?;
+
+    let new_head = jj.resolve_revision_to_commit_id(revision.id.as_ref())?;
+    let new_base =
+        jj.resolve_revision_to_commit_id(RevSet::from(&revision.id).parent().as_ref())?;
+    let old_tree = rebased_tree(jj, config, old_head, pr.base_branch_name(), new_base)?;
+
+    render_diff(
+        &jj.git_repo,
+        old_tree,
+        jj.get_tree_oid_for_commit(new_head)?,
+    )
+}
+
+pub async fn diff<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    gh: GH,
+    config: &crate::config::Config,
+    opts: DiffOptions,
+) -> Result<()>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    jj.run_git_fetch_for(config)?;
+    let lines = collect_diff(jj, gh, config, &opts).await?;
+    if lines.is_empty() {
+        crate::output::output(&config.icons.ok, "No changes")?;
+        return Ok(());
+    }
+
+    let term = console::Term::stdout();
+    for line in lines {
+        let styled = if line.starts_with('+') {
+            console::style(line).green()
+        } else if line.starts_with('-') {
+            console::style(line).red()
+        } else {
+            console::style(line)
+        };
+        term.write_line(&styled.to_string())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        jj::{ChangeId, RevSet},
+        testing,
+    };
+
+    fn change_file(jj: &mut crate::jj::Jujutsu, path: &str, content: &str) {
+        std::fs::write(
+            jj.git_repo
+                .workdir()
+                .expect("Failed to extract workdir from JJ handle")
+                .join(path),
+            content,
+        )
+        .expect("Failed to write test file");
+        jj.squash().expect("Failed to squash change into revision");
+    }
+
+    async fn push(
+        jj: &mut crate::jj::Jujutsu,
+        gh: &mut crate::github::fakes::GitHub,
+        config: &crate::config::Config,
+    ) {
+        crate::commands::push::push(
+            jj,
+            gh,
+            config,
+            crate::commands::push::PushOptions::default().with_message(Some("Update")),
+        )
+        .await
+        .expect("Push shouldn't fail");
+    }
+
+    #[tokio::test]
+    async fn shows_local_changes() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+        let mut gh = crate::github::fakes::GitHub::new();
+        push(&mut jj, &mut gh, &config).await;
+
+        let lines = super::collect_diff(&mut jj, &mut gh, &config, &super::DiffOptions::default())
+            .await
+            .expect("Diff shouldn't fail");
+        assert!(lines.is_empty(), "Unexpected diff: {:?}", lines);
+
+        change_file(&mut jj, "file", "changed");
+        let lines = super::collect_diff(&mut jj, &mut gh, &config, &super::DiffOptions::default())
+            .await
+            .expect("Diff shouldn't fail");
+        assert!(lines.contains(&String::from("-Test commit")));
+        assert!(lines.contains(&String::from("+changed")));
+    }
+
+    #[tokio::test]
+    async fn ignores_base_changes() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let change = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+        let mut gh = crate::github::fakes::GitHub::new();
+        push(&mut jj, &mut gh, &config).await;
+
+        let main = jj
+            .git_repo
+            .revparse_single("origin/main")
+            .expect("Trunk should exist")
+            .id();
+        testing::git::add_commit_on_and_push_to_remote_file(
+            &jj.git_repo,
+            "main",
+            [main],
+            "trunk-file",
+        );
+        jj.run_git_fetch().expect("Fetch shouldn't fail");
+        jj.rebase_branch(&RevSet::from(&change), ChangeId::from("main@origin"))
+            .expect("Rebase shouldn't fail");
+
+        let lines = super::collect_diff(
+            &mut jj,
+            &mut gh,
+            &config,
+            &super::DiffOptions::default().with_revision(Some(RevSet::from(&change).as_ref())),
+        )
+        .await
+        .expect("Diff shouldn't fail");
+        assert!(
+            lines.is_empty(),
+            "Trunk changes leaked into diff: {:?}",
+            lines
+        );
+    }
+
+    #[tokio::test]
+    async fn since_review() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+        let mut gh = crate::github::fakes::GitHub::new();
+        push(&mut jj, &mut gh, &config).await;
+
+        let reviewed = jj
+            .git_repo
+            .revparse_single("origin/spr/test/test-commit")
+            .expect("Pushed branch should exist")
+            .id();
+        gh.pull_requests
+            .get_mut(&1)
+            .expect("PR should exist")
+            .reviewed_commit = Some(reviewed.to_string());
+
+        change_file(&mut jj, "file", "changed");
+        push(&mut jj, &mut gh, &config).await;
+
+        let lines = super::collect_diff(&mut jj, &mut gh, &config, &super::DiffOptions::default())
+            .await
+            .expect("Diff shouldn't fail");
+        assert!(lines.is_empty(), "Unexpected diff: {:?}", lines);
+
+        let lines = super::collect_diff(
+            &mut jj,
+            &mut gh,
+            &config,
+            &super::DiffOptions::default().with_since_review(true),
+        )
+        .await
+        .expect("Diff shouldn't fail");
+        assert!(lines.contains(&String::from("+changed")));
+    }
+}
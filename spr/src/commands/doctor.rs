@@ -0,0 +1,636 @@
+use crate::{
+    config::{forge::Forge, icons::Icons},
+    error::{Error, Result},
+    jj::RevSet,
+};
+
+/// Scopes of a classic personal access token that spr needs. Same as asked for by `init`.
+const REQUIRED_SCOPES: [&str; 3] = ["repo", "user", "read:org"];
+
+enum Outcome {
+    Pass(String),
+    Fail { problem: String, hint: String },
+}
+
+struct Check {
+    name: &'static str,
+    outcome: Outcome,
+}
+
+impl Check {
+    fn pass<S: Into<String>>(name: &'static str, detail: S) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Pass(detail.into()),
+        }
+    }
+
+    fn fail<P: Into<String>, H: Into<String>>(name: &'static str, problem: P, hint: H) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Fail {
+                problem: problem.into(),
+                hint: hint.into(),
+            },
+        }
+    }
+
+    fn failed(&self) -> bool {
+        matches!(self.outcome, Outcome::Fail { .. })
+    }
+}
+
+fn tool_version(name: &'static str, bin: &std::ffi::OsStr) -> Check {
+    match std::process::Command::new(bin).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            Check::pass(name, String::from_utf8_lossy(&output.stdout).trim())
+        }
+        Ok(output) => Check::fail(
+            name,
+            format!(
+                "`{} --version` failed: {}",
+                bin.to_string_lossy(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            "Make sure a working version is installed",
+        ),
+        Err(err) => Check::fail(
+            name,
+            format!("Couldn't run {}: {}", bin.to_string_lossy(), err),
+            "Install it or make sure it is in your PATH",
+        ),
+    }
+}
+
+/// Checks on the local configuration. Returns the GitHub owner and repository if they could be
+/// determined.
+fn repository_checks(jj: &crate::jj::Jujutsu) -> (Vec<Check>, Option<(String, String)>) {
+    let mut checks = Vec::new();
+
+    let remote = match crate::config::remote_from_jj(jj) {
+        Ok(remote) => {
+            checks.push(Check::pass("remote", remote.clone()));
+            remote
+        }
+        Err(err) => {
+            checks.push(Check::fail(
+                "remote",
+                err.to_string(),
+                "Set `spr.githubRemoteName`, or make the `trunk()` alias look like `main@origin`",
+            ));
+            return (checks, None);
+        }
+    };
+
+    let forge = crate::config::forge_from_jj(jj, &remote)
+        .map(|(forge, _)| forge)
+        .unwrap_or_default();
+    let owner_and_repo = match crate::config::repo_and_owner_from_jj(jj, &remote, forge) {
+        Ok((repo, owner)) => {
+            checks.push(Check::pass("repository", format!("{}/{}", owner, repo)));
+            Some((owner, repo))
+        }
+        Err(err) => {
+            checks.push(Check::fail(
+                "repository",
+                err.to_string(),
+                "Set `spr.githubRepository` to 'OWNER/REPO'",
+            ));
+            None
+        }
+    };
+
+    checks.push(match crate::config::default_branch_from_jj(jj) {
+        Ok(master_ref) if master_ref.is_empty() => Check::fail(
+            "target branch",
+            "Couldn't determine the branch PRs should target",
+            "Set `spr.githubMasterBranch`, or define the `trunk()` alias like `main@origin`",
+        ),
+        Ok(master_ref) => {
+            let remote_ref = format!("refs/remotes/{}/{}", remote, master_ref);
+            if jj.git_repo.find_reference(&remote_ref).is_ok() {
+                Check::pass("target branch", format!("{}@{}", master_ref, remote))
+            } else {
+                Check::fail(
+                    "target branch",
+                    format!("{} doesn't exist on {}", master_ref, remote),
+                    "Run `jj git fetch`, or set `spr.githubMasterBranch` to the branch PRs should target",
+                )
+            }
+        }
+        Err(err) => Check::fail(
+            "target branch",
+            err.to_string(),
+            "Set `spr.githubMasterBranch`, or make the `trunk()` alias look like `main@origin`",
+        ),
+    });
+
+    (checks, owner_and_repo)
+}
+
+fn revision_checks(jj: &crate::jj::Jujutsu) -> Check {
+    let revset = RevSet::mutable()
+        .and(&RevSet::description("glob:\"*Pull Request:*\""))
+        .and(&RevSet::conflicts().or(&RevSet::divergent()));
+    match jj.revset_to_change_ids(&revset) {
+        Ok(ids) if ids.is_empty() => Check::pass("revisions", "No divergent or conflicted PRs"),
+        Ok(ids) => Check::fail(
+            "revisions",
+            format!("Divergent or conflicted revisions with PRs: {:?}", ids),
+            "Resolve the conflicts, or abandon one side of a divergent change",
+        ),
+        Err(err) => Check::fail(
+            "revisions",
+            err.to_string(),
+            "Make sure `jj log` works in this repository",
+        ),
+    }
+}
+
+fn branch_prefix_check(branch_prefix: &str) -> Check {
+    match super::init::validate_branch_prefix(branch_prefix) {
+        Ok(()) => Check::pass("branch prefix", branch_prefix),
+        Err(err) => Check::fail(
+            "branch prefix",
+            err.to_string(),
+            "Change `spr.branchPrefix`",
+        ),
+    }
+}
+
+/// Checks against GitHub. Returns the login of the token's user, if the token works.
+async fn github_checks(
+    token: Option<String>,
+    host: &str,
+    api_url: &str,
+    owner_and_repo: Option<&(String, String)>,
+) -> (Vec<Check>, Option<String>) {
+    let mut checks = Vec::new();
+    let Some(token) = token else {
+        checks.push(Check::fail(
+            "token",
+            "No GitHub token configured",
+            "Run `jj spr init`, log in with the GitHub CLI or set `spr.githubAuthToken`",
+        ));
+        return (checks, None);
+    };
+
+    let crab = match octocrab::OctocrabBuilder::default()
+        .personal_token(token)
+        .base_uri(api_url)
+        .and_then(|builder| builder.build())
+    {
+        Ok(crab) => crab,
+        Err(err) => {
+            checks.push(Check::fail(
+                "token",
+                err.to_string(),
+                "Run `jj spr init` to configure a new token",
+            ));
+            return (checks, None);
+        }
+    };
+
+    match crab._get("/user").await {
+        Ok(response) if response.status().is_success() => {
+            match response
+                .headers()
+                .get("x-oauth-scopes")
+                .and_then(|h| h.to_str().ok())
+            {
+                Some(scopes) => {
+                    let scopes: Vec<_> = scopes.split(',').map(|s| s.trim()).collect();
+                    let missing: Vec<_> = REQUIRED_SCOPES
+                        .iter()
+                        .filter(|s| !scopes.contains(s))
+                        .collect();
+                    checks.push(if missing.is_empty() {
+                        Check::pass("token", format!("Scopes: {}", scopes.join(", ")))
+                    } else {
+                        Check::fail(
+                            "token",
+                            format!("Token is missing scopes {:?}", missing),
+                            format!("Add them at https://{}/settings/tokens", host),
+                        )
+                    });
+                }
+                // Fine-grained tokens don't have scopes. They are covered by the permission check.
+                None => checks.push(Check::pass("token", "Fine-grained token")),
+            }
+        }
+        Ok(response) => {
+            checks.push(Check::fail(
+                "token",
+                format!("GitHub rejected the token ({})", response.status()),
+                "The token is invalid or expired. Create a new one and run `jj spr init`",
+            ));
+            return (checks, None);
+        }
+        Err(err) => {
+            checks.push(Check::fail(
+                "token",
+                format!("Couldn't reach GitHub at {}: {}", api_url, err),
+                "Check your network connection, or `spr.githubHost` and `spr.githubApiUrl`",
+            ));
+            return (checks, None);
+        }
+    }
+
+    let login = match crab.current().user().await {
+        Ok(user) => Some(user.login),
+        Err(err) => {
+            checks.push(Check::fail(
+                "user",
+                err.to_string(),
+                "Make sure the token can read your user profile",
+            ));
+            None
+        }
+    };
+
+    if let Some((owner, repo)) = owner_and_repo {
+        checks.push(match crab.repos(owner, repo).get().await {
+            Ok(repository) if repository.permissions.as_ref().is_some_and(|p| p.push) => {
+                Check::pass("permissions", format!("Can push to {}/{}", owner, repo))
+            }
+            Ok(_) => Check::fail(
+                "permissions",
+                format!("No write access to {}/{}", owner, repo),
+                "Ask for write access, or give a fine-grained token contents and pull request permissions",
+            ),
+            Err(err) => Check::fail(
+                "permissions",
+                format!("Couldn't read {}/{}: {}", owner, repo, err),
+                "Check `spr.githubRepository` and that the token can access the repository",
+            ),
+        });
+    }
+
+    (checks, login)
+}
+
+/// Checks against GitLab. Returns the login of the token's user, if the token works.
+async fn gitlab_checks(
+    token: Option<String>,
+    api_url: &str,
+    owner_and_repo: Option<&(String, String)>,
+) -> (Vec<Check>, Option<String>) {
+    let mut checks = Vec::new();
+    let Some(token) = token else {
+        checks.push(Check::fail(
+            "token",
+            "No GitLab token configured",
+            "Set `spr.gitlabAuthToken` or `GITLAB_TOKEN` to a token with the `api` scope",
+        ));
+        return (checks, None);
+    };
+
+    let crab = match octocrab::OctocrabBuilder::default()
+        .personal_token(token)
+        .base_uri(api_url)
+        .and_then(|builder| builder.build())
+    {
+        Ok(crab) => crab,
+        Err(err) => {
+            checks.push(Check::fail(
+                "token",
+                err.to_string(),
+                "Set `spr.gitlabAuthToken` to a new token",
+            ));
+            return (checks, None);
+        }
+    };
+
+    let login = match crate::github::gitlab::current_user(&crab).await {
+        Ok(login) => {
+            checks.push(Check::pass("token", format!("Belongs to {}", login)));
+            login
+        }
+        Err(err) => {
+            checks.push(Check::fail(
+                "token",
+                format!("Couldn't read the user from GitLab at {}: {}", api_url, err),
+                "The token is invalid or expired, or check `spr.gitlabApiUrl`",
+            ));
+            return (checks, None);
+        }
+    };
+
+    if let Some((owner, repo)) = owner_and_repo {
+        checks.push(
+            match crate::github::gitlab::can_push(&crab, owner, repo).await {
+                Ok(true) => Check::pass("permissions", format!("Can push to {}/{}", owner, repo)),
+                Ok(false) => Check::fail(
+                    "permissions",
+                    format!("No write access to {}/{}", owner, repo),
+                    "Ask for at least the Developer role in the project",
+                ),
+                Err(err) => Check::fail(
+                    "permissions",
+                    format!("Couldn't read {}/{}: {}", owner, repo, err),
+                    "Check `spr.githubRepository` and that the token can access the project",
+                ),
+            },
+        );
+    }
+
+    (checks, Some(login))
+}
+
+/// Checks against Forgejo or Gitea. Returns the login of the token's user, if the token works.
+async fn gitea_checks(
+    token: Option<String>,
+    api_url: &str,
+    owner_and_repo: Option<&(String, String)>,
+) -> (Vec<Check>, Option<String>) {
+    let mut checks = Vec::new();
+    let Some(token) = token else {
+        checks.push(Check::fail(
+            "token",
+            "No Forgejo or Gitea token configured",
+            "Set `spr.giteaAuthToken` or `GITEA_TOKEN` to a token with read and write access to repositories",
+        ));
+        return (checks, None);
+    };
+
+    let crab = match octocrab::OctocrabBuilder::default()
+        .personal_token(token)
+        .base_uri(api_url)
+        .and_then(|builder| builder.build())
+    {
+        Ok(crab) => crab,
+        Err(err) => {
+            checks.push(Check::fail(
+                "token",
+                err.to_string(),
+                "Set `spr.giteaAuthToken` to a new token",
+            ));
+            return (checks, None);
+        }
+    };
+
+    let login = match crate::github::gitea::current_user(&crab).await {
+        Ok(login) => {
+            checks.push(Check::pass("token", format!("Belongs to {}", login)));
+            login
+        }
+        Err(err) => {
+            checks.push(Check::fail(
+                "token",
+                format!("Couldn't read the user from Gitea at {}: {}", api_url, err),
+                "The token is invalid or expired, or check `spr.giteaApiUrl`",
+            ));
+            return (checks, None);
+        }
+    };
+
+    if let Some((owner, repo)) = owner_and_repo {
+        checks.push(
+            match crate::github::gitea::can_push(&crab, owner, repo).await {
+                Ok(true) => Check::pass("permissions", format!("Can push to {}/{}", owner, repo)),
+                Ok(false) => Check::fail(
+                    "permissions",
+                    format!("No write access to {}/{}", owner, repo),
+                    "Ask for write access to the repository",
+                ),
+                Err(err) => Check::fail(
+                    "permissions",
+                    format!("Couldn't read {}/{}: {}", owner, repo, err),
+                    "Check `spr.githubRepository` and that the token can access the repository",
+                ),
+            },
+        );
+    }
+
+    (checks, Some(login))
+}
+
+async fn run_checks(
+    github_auth_token: Option<String>,
+    gitlab_auth_token: Option<String>,
+    gitea_auth_token: Option<String>,
+) -> Vec<Check> {
+    let mut checks = vec![
+        tool_version("jj", crate::jj::get_jj_bin().as_os_str()),
+        tool_version("git", std::ffi::OsStr::new("git")),
+    ];
+
+    let jj = match std::env::current_dir()
+        .map_err(Error::from)
+        .and_then(crate::jj::Jujutsu::new)
+    {
+        Ok(jj) => {
+            checks.push(Check::pass(
+                "git root",
+                jj.git_repo.path().to_string_lossy(),
+            ));
+            jj
+        }
+        Err(err) => {
+            checks.push(Check::fail(
+                "git root",
+                err.to_string(),
+                "Run spr inside a jj repository backed by git, e.g. created by `jj git init --colocate`",
+            ));
+            return checks;
+        }
+    };
+
+    let (repository, owner_and_repo) = repository_checks(&jj);
+    checks.extend(repository);
+
+    let git_config = jj.git_repo.config().ok();
+    let (forge, host) = crate::config::remote_from_jj(&jj)
+        .and_then(|remote| crate::config::forge_from_jj(&jj, &remote))
+        .unwrap_or_else(|_| (Forge::GitHub, String::from("github.com")));
+    let (forge_checks, login) = match forge {
+        Forge::GitLab => {
+            let token = gitlab_auth_token.or_else(|| {
+                git_config
+                    .as_ref()
+                    .and_then(crate::config::get_gitlab_auth_token)
+            });
+            let api_url = crate::config::gitlab_api_url(&jj, &host);
+            gitlab_checks(token, &api_url, owner_and_repo.as_ref()).await
+        }
+        Forge::Gitea => {
+            let token = gitea_auth_token.or_else(|| {
+                git_config
+                    .as_ref()
+                    .and_then(crate::config::get_gitea_auth_token)
+            });
+            let api_url = crate::config::gitea_api_url(&jj, &host);
+            gitea_checks(token, &api_url, owner_and_repo.as_ref()).await
+        }
+        // Plugins bring their own credentials.
+        Forge::Plugin { .. } => (Vec::new(), None),
+        Forge::GitHub => {
+            let token = github_auth_token
+                .or_else(|| git_config.as_ref().and_then(crate::config::get_auth_token));
+            let api_url = crate::config::github_api_url(&jj, &host);
+            github_checks(token, &host, &api_url, owner_and_repo.as_ref()).await
+        }
+    };
+    checks.extend(forge_checks);
+
+    match jj
+        .config_get("spr.branchPrefix")
+        .ok()
+        .or_else(|| login.map(|l| format!("spr/{}/", l)))
+    {
+        Some(branch_prefix) => checks.push(branch_prefix_check(&branch_prefix)),
+        None => checks.push(Check::fail(
+            "branch prefix",
+            "Not configured and no user to derive it from",
+            "Set `spr.branchPrefix`",
+        )),
+    }
+
+    checks.push(revision_checks(&jj));
+    checks
+}
+
+pub async fn doctor(
+    github_auth_token: Option<String>,
+    gitlab_auth_token: Option<String>,
+    gitea_auth_token: Option<String>,
+) -> Result<()> {
+    let icons = Icons::default();
+    let checks = run_checks(github_auth_token, gitlab_auth_token, gitea_auth_token).await;
+
+    for check in checks.iter() {
+        match &check.outcome {
+            Outcome::Pass(detail) => {
+                crate::output::output(&icons.ok, format!("{}: {}", check.name, detail))?
+            }
+            Outcome::Fail { problem, hint } => {
+                crate::output::output(&icons.error, format!("{}: {}", check.name, problem))?;
+                crate::output::output(&icons.info, hint)?;
+            }
+        }
+    }
+
+    let failed = checks.iter().filter(|c| c.failed()).count();
+    if failed > 0 {
+        return Err(Error::new(format!(
+            "{} of {} checks failed",
+            failed,
+            checks.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Outcome;
+    use crate::testing;
+
+    #[test]
+    fn repository_checks() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let outcomes = |jj: &crate::jj::Jujutsu| -> Vec<_> {
+            super::repository_checks(jj)
+                .0
+                .iter()
+                .map(|c| (c.name, c.failed()))
+                .collect()
+        };
+
+        // The test origin is a local path, not on github.com.
+        assert_eq!(
+            outcomes(&jj),
+            vec![
+                ("remote", false),
+                ("repository", true),
+                ("target branch", true)
+            ]
+        );
+
+        jj.config_set("spr.githubMasterBranch", "main", false)
+            .expect("Setting config shouldn't fail");
+        jj.config_set("spr.githubRepository", "owner/repo", false)
+            .expect("Setting config shouldn't fail");
+        let (checks, owner_and_repo) = super::repository_checks(&jj);
+        assert!(checks.iter().all(|c| !c.failed()));
+        assert_eq!(
+            owner_and_repo,
+            Some((String::from("owner"), String::from("repo")))
+        );
+        assert!(matches!(&checks[0].outcome, Outcome::Pass(remote) if remote == "origin"));
+    }
+
+    #[tokio::test]
+    async fn gitlab_without_token() {
+        let (checks, login) =
+            super::gitlab_checks(None, "https://gitlab.example.com/api/v4", None).await;
+        assert_eq!(login, None);
+        assert!(
+            matches!(&checks[..], [check] if check.name == "token" && matches!(
+                &check.outcome,
+                Outcome::Fail { hint, .. } if hint.contains("spr.gitlabAuthToken")
+            )),
+            "Missing GitLab token should be reported"
+        );
+    }
+
+    #[tokio::test]
+    async fn gitea_without_token() {
+        let (checks, login) = super::gitea_checks(None, "https://codeberg.org/api/v1", None).await;
+        assert_eq!(login, None);
+        assert!(
+            matches!(&checks[..], [check] if check.name == "token" && matches!(
+                &check.outcome,
+                Outcome::Fail { hint, .. } if hint.contains("spr.giteaAuthToken")
+            )),
+            "Missing Gitea token should be reported"
+        );
+    }
+
+    #[test]
+    fn branch_prefix() {
+        assert!(!super::branch_prefix_check("spr/user/").failed());
+        assert!(super::branch_prefix_check("spr/.user/").failed());
+    }
+
+    #[test]
+    fn conflicted_revisions() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        assert!(!super::revision_checks(&jj).failed());
+
+        let path = jj
+            .git_repo
+            .workdir()
+            .expect("Failed to extract workdir from JJ handle")
+            .join("file");
+        std::fs::write(&path, "base").expect("Failed to write test file");
+        jj.commit("Base").expect("Failed to commit revision");
+        std::fs::write(&path, "left").expect("Failed to write test file");
+        jj.commit(format!(
+            "Left\n\nPull Request: {}",
+            testing::config::basic().pull_request_url(1)
+        ))
+        .expect("Failed to commit revision");
+        let left = jj
+            .revset_to_change_id(&crate::jj::RevSet::current().parent())
+            .expect("Failed to get changeid of '@-'");
+        jj.new_revision(
+            Some(crate::jj::RevSet::from(&left).parent()),
+            None as Option<String>,
+            false,
+        )
+        .expect("Failed to create revision");
+        std::fs::write(&path, "right").expect("Failed to write test file");
+        jj.commit("Right").expect("Failed to commit revision");
+        let right = jj
+            .revset_to_change_id(&crate::jj::RevSet::current().parent())
+            .expect("Failed to get changeid of '@-'");
+        jj.rebase(
+            &crate::jj::RevSet::from(&left),
+            &crate::jj::RevSet::from(&right),
+        )
+        .expect("Failed to rebase");
+
+        assert!(super::revision_checks(&jj).failed());
+    }
+}
@@ -9,6 +9,8 @@ use crate::{
     error::{Error, Result},
     jj::RevSet,
     message::{MessageSection, validate_commit_message},
+    plan::{Action, Plan, PlanFormat},
+    snapshot::Merge,
 };
 
 #[derive(Debug, clap::Parser, Default)]
@@ -39,6 +41,20 @@ pub struct FetchOptions {
     /// Requires 'pull_code_changes' since it might mess up state otherwise.
     #[clap(long)]
     rebase: bool,
+
+    /// Only print what would be done. Optionally as `json`.
+    #[clap(long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+    dry_run: Option<PlanFormat>,
+}
+
+impl FetchOptions {
+    /// Only update the messages of `revset` from their PRs, without touching code.
+    pub(crate) fn metadata_only(revset: Option<String>) -> Self {
+        Self {
+            revset,
+            ..Default::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -61,6 +77,11 @@ impl FetchOptions {
         self.rebase = true;
         self
     }
+
+    pub fn with_dry_run(mut self, format: PlanFormat) -> Self {
+        self.dry_run = Some(format);
+        self
+    }
 }
 
 enum WorkEvent<'a> {
@@ -68,6 +89,7 @@ enum WorkEvent<'a> {
     Code(&'a crate::config::Config),
     Title(&'a crate::config::Config),
     Summary(&'a crate::config::Config),
+    Labels(&'a crate::config::Config),
 }
 
 type WorkLog<'a> = Vec<WorkEvent<'a>>;
@@ -78,6 +100,7 @@ impl<'a> std::fmt::Display for WorkEvent<'a> {
             WorkEvent::Code(c) => ("Updated Code", c.icons.working.as_ref()),
             WorkEvent::Title(c) => ("Updated Title", c.icons.info.as_ref()),
             WorkEvent::Summary(c) => ("Updated Summary", c.icons.info.as_ref()),
+            WorkEvent::Labels(c) => ("Updated Labels", c.icons.info.as_ref()),
         };
 
         f.write_str(name)?;
@@ -100,12 +123,29 @@ struct WorkItem<PR> {
     progress: indicatif::ProgressBar,
 }
 
+fn plan_rebase(
+    jj: &crate::jj::Jujutsu,
+    plan: &mut Plan,
+    revision: &crate::jj::Revision,
+    parent: &crate::jj::ChangeId,
+    target: RevSet,
+) -> Result<()> {
+    if jj.revset_to_change_id(&target)? != *parent {
+        plan.push(Action::Rebase {
+            revisions: revision.id.to_string(),
+            onto: target.as_ref().to_string(),
+        });
+    }
+    Ok(())
+}
+
 async fn do_fetch<I: IntoIterator<Item = WorkItem<PR>>, GH, PR>(
-    opts: FetchOptions,
+    opts: &FetchOptions,
     jj: &mut crate::jj::Jujutsu,
     mut gh: GH,
     config: &crate::config::Config,
     commits: I,
+    mut plan: Option<&mut Plan>,
 ) -> Result<()>
 where
     PR: crate::github::GHPullRequest,
@@ -114,8 +154,10 @@ where
     let mut failure = false;
     let mut items: Vec<_> = commits.into_iter().collect();
 
+    let mut changed = Vec::new();
     for work in items.iter_mut() {
         let mut log = WorkLog::default();
+        let original = work.revision.message.clone();
 
         // Ok, we want to update our local change with any code changes that were done upstream
         if opts.pull_code_changes
@@ -123,16 +165,13 @@ where
             && let Some(old_rev) = work.revision.message.get(&MessageSection::LastCommit)
         {
             let head_revset = {
+                let head = work.pull_request.head_branch_name();
+                let remote = config.branch_remote(&jj.git_repo, head);
                 let head_branch = jj.git_repo.find_branch(
-                    format!(
-                        "{}/{}",
-                        config.remote_name,
-                        work.pull_request.head_branch_name()
-                    )
-                    .as_str(),
+                    format!("{}/{}", remote, head).as_str(),
                     git2::BranchType::Remote,
                 )?;
-                RevSet::from_remote_branch(&head_branch, &config.remote_name)?
+                RevSet::from_remote_branch(&head_branch, remote)?
             };
 
             if opts.rebase {
@@ -155,7 +194,11 @@ where
                         )?;
                         crate::jj::RevSet::from_remote_branch(&branch, &config.remote_name)?
                     };
-                    jj.rebase(&revset, &head_revset.fork_point(&base_revset))?;
+                    let target = head_revset.fork_point(&base_revset);
+                    match plan.as_mut() {
+                        Some(plan) => plan_rebase(jj, plan, &work.revision, &pre_parent, target)?,
+                        None => jj.rebase(&revset, &target)?,
+                    }
                 } else {
                     let base_pr = gh
                         .pull_request_by_head(work.pull_request.base_branch_name())
@@ -163,10 +206,11 @@ where
 
                     let url = config.pull_request_url(base_pr.pr_number());
 
-                    jj.rebase(
-                        &revset,
-                        &RevSet::description(format!("substring:\"{}\"", url)).unique(),
-                    )?;
+                    let target = RevSet::description(format!("substring:\"{}\"", url)).unique();
+                    match plan.as_mut() {
+                        Some(plan) => plan_rebase(jj, plan, &work.revision, &pre_parent, target)?,
+                        None => jj.rebase(&revset, &target)?,
+                    }
                 }
                 let post_parent = jj.revset_to_change_id(&revset.parent())?;
 
@@ -182,7 +226,15 @@ where
             };
             let new_latest_commit = jj.resolve_revision_to_commit_id(head_revset.as_ref())?;
             if new_latest_commit.to_string() != *old_rev {
-                jj.squash_copy(&base_revset.to(&head_revset), work.revision.id.clone())?;
+                match plan.as_mut() {
+                    Some(plan) => plan.push(Action::MergeCode {
+                        revision: work.revision.id.to_string(),
+                        commit: new_latest_commit.to_string(),
+                    }),
+                    None => {
+                        jj.squash_copy(&base_revset.to(&head_revset), work.revision.id.clone())?
+                    }
+                }
                 log.push(WorkEvent::Code(config));
                 work.revision
                     .message
@@ -191,38 +243,86 @@ where
         }
 
         work.progress.set_message("Updating metadata");
-        if work
-            .revision
-            .message
-            .get(&MessageSection::Title)
-            .map(|t| t != work.pull_request.title())
-            .unwrap_or(true)
-        {
+        // Edits made here since the last sync stay, for `push` to send them.
+        let (title, summary) = crate::snapshot::remote(&work.pull_request);
+        let changes = crate::snapshot::compare(&work.revision.message, &title, &summary)?;
+        if matches!(changes.title, Merge::Remote | Merge::Unknown) {
             work.revision
                 .message
-                .insert(MessageSection::Title, work.pull_request.title().into());
+                .insert(MessageSection::Title, title.clone());
             log.push(WorkEvent::Title(config));
         }
-        if work
-            .revision
-            .message
-            .get(&MessageSection::Summary)
-            .map(|t| t != work.pull_request.body())
-            .unwrap_or(true)
-        {
+        if matches!(changes.summary, Merge::Remote | Merge::Unknown) {
             work.revision
                 .message
-                .insert(MessageSection::Summary, work.pull_request.body().into());
+                .insert(MessageSection::Summary, summary.clone());
             log.push(WorkEvent::Summary(config));
         }
+        for field in changes.conflicts() {
+            crate::output::output(
+                &config.icons.error,
+                format!(
+                    "The {} of {} changed here and on GitHub. Make them match, or overwrite the PR with `jj spr push --force`",
+                    field,
+                    config.pull_request_url(work.pull_request.pr_number())
+                ),
+            )?;
+            failure = true;
+        }
+        crate::snapshot::update(&mut work.revision.message, &title, &summary)?;
+        let local = work
+            .revision
+            .message
+            .get(&MessageSection::Labels)
+            .map(|section| crate::labels::parse(section))
+            .unwrap_or_default();
+        // Labels of the `spr.push.labels` rules are derived on every push, not kept here.
+        let remote: Vec<&String> = work
+            .pull_request
+            .labels()
+            .iter()
+            .filter(|label| {
+                local.contains(label)
+                    || !config.push.labels.iter().any(|rule| &rule.label == *label)
+            })
+            .collect();
+        // Keep the order of the section, labels added on GitHub go last.
+        let labels: Vec<&str> = local
+            .iter()
+            .filter(|label| remote.contains(label))
+            .chain(
+                remote
+                    .iter()
+                    .copied()
+                    .filter(|label| !local.contains(label)),
+            )
+            .map(|label| label.as_str())
+            .collect();
+        if labels.len() != local.len() || remote.iter().any(|label| !local.contains(label)) {
+            if labels.is_empty() {
+                work.revision.message.remove(&MessageSection::Labels);
+            } else {
+                work.revision
+                    .message
+                    .insert(MessageSection::Labels, labels.join(", "));
+            }
+            log.push(WorkEvent::Labels(config));
+        }
 
         failure = validate_commit_message(config, &work.revision.message).is_err() || failure;
         work.progress
             .finish_with_message(format_worklog(&log, config));
+        changed.push(original != work.revision.message);
     }
 
-    for work in items.into_iter() {
-        jj.update_revision_message(&work.revision)?;
+    for (work, changed) in std::iter::zip(items, changed) {
+        match plan.as_mut() {
+            Some(plan) if changed => plan.push(Action::UpdateDescription {
+                revision: work.revision.id.to_string(),
+            }),
+            Some(_) => {}
+            None => jj.update_revision_message(&work.revision)?,
+        }
     }
 
     if failure { Err(Error::empty()) } else { Ok(()) }
@@ -258,6 +358,7 @@ where
             RevSet::current()
         });
     let revisions = jj.read_revision_range(
+        config,
         &revset
             .ancestors()
             .without(&RevSet::immutable().or(&RevSet::description("exact:\"\""))),
@@ -269,7 +370,11 @@ where
     }
 
     setup.set_message("Fetching current state from github");
-    jj.run_git_fetch()?;
+    let mut plan = opts.dry_run.map(|_| Plan::new());
+    match plan.as_mut() {
+        Some(plan) => plan.push(Action::Fetch),
+        None => jj.run_git_fetch_for(config)?,
+    }
     let pull_requests = gh
         .pull_requests(revisions.iter().map(|r| r.pull_request_number))
         .await?;
@@ -298,9 +403,12 @@ where
         })
     });
 
-    do_fetch(opts, jj, gh, config, work).await?;
+    let result = do_fetch(&opts, jj, gh, config, work, plan.as_mut()).await;
     setup.finish_and_clear();
-    Ok(())
+    if let (Some(plan), Some(format)) = (plan, opts.dry_run) {
+        plan.print(config, format)?;
+    }
+    result
 }
 
 #[cfg(test)]
@@ -370,6 +478,7 @@ mod tests {
                         "New Summary",
                     ),
                 )]),
+                ..Default::default()
             },
             &config,
         )
@@ -442,6 +551,7 @@ mod tests {
                         "New Summary",
                     ),
                 )]),
+                ..Default::default()
             },
             &config,
         )
@@ -480,6 +590,63 @@ mod tests {
         assert_ne!(pre_amend_tree, post_amend_tree, "Tree didn't change");
     }
 
+    #[tokio::test]
+    async fn dry_run_changes_nothing() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let pr_url = config.pull_request_url(1);
+
+        let trunk_oid = jj
+            .git_repo
+            .refname_to_id("HEAD")
+            .expect("Failed to revparse HEAD");
+        let rev = create_jujutsu_commit(
+            &mut jj,
+            format!("Test commit\n\n\nPull Request: {pr_url}\nLast Commit: {trunk_oid}",).as_str(),
+            "file 1",
+        );
+        let pre_commit = jj
+            .resolve_revision_to_commit_id(rev.as_ref())
+            .expect("Failed to get commit for revision");
+
+        jj.git_repo
+            .set_head_detached(trunk_oid)
+            .expect("Expected to be able to checkout trunk");
+        testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/test-commit");
+        jj.run_git_fetch().expect("Fetch shouldn't fail");
+
+        super::fetch(
+            FetchOptions::default()
+                .with_revset(Some(rev.as_ref()))
+                .with_pull_code()
+                .with_dry_run(crate::plan::PlanFormat::Text),
+            &mut jj,
+            &mut crate::github::fakes::GitHub {
+                pull_requests: std::collections::BTreeMap::from([(
+                    1,
+                    crate::github::fakes::PullRequest::new(
+                        "main",
+                        "spr/test/test-commit",
+                        1,
+                        "New Title",
+                        "New Summary",
+                    ),
+                )]),
+                ..Default::default()
+            },
+            &config,
+        )
+        .await
+        .expect("dry run should not error");
+
+        assert_eq!(
+            jj.resolve_revision_to_commit_id(rev.as_ref())
+                .expect("Failed to get commit for revision"),
+            pre_commit,
+            "Dry run changed the revision"
+        );
+    }
+
     #[tokio::test]
     async fn rebase_to_new_head() {
         let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
@@ -531,6 +698,7 @@ mod tests {
                         "New Summary",
                     ),
                 )]),
+                ..Default::default()
             },
             &config,
         )
@@ -602,6 +770,7 @@ mod tests {
                         "New Summary",
                     ),
                 )]),
+                ..Default::default()
             },
             &config,
         )
@@ -614,6 +783,110 @@ mod tests {
         assert_eq!(fork_point, head, "Revision was rebased to older HEAD")
     }
 
+    #[tokio::test]
+    async fn test_labels_from_github() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let pr_url = config.pull_request_url(1);
+
+        let _ = create_jujutsu_commit(
+            &mut jj,
+            format!(
+                "Test commit\n\nSummary\n\nLabels: stale, bug\nPull Request: {}\nLast Commit: My Last Commit",
+                pr_url,
+            )
+            .as_ref(),
+            "file 1",
+        );
+        let mut pr = crate::github::fakes::PullRequest::new(
+            "main",
+            "spr/test/test-commit",
+            1,
+            "Test commit",
+            "Summary",
+        );
+        pr.labels = vec![String::from("area/parser"), String::from("bug")];
+
+        super::fetch(
+            FetchOptions::default(),
+            &mut jj,
+            &mut crate::github::fakes::GitHub {
+                pull_requests: std::collections::BTreeMap::from([(1, pr)]),
+                ..Default::default()
+            },
+            &config,
+        )
+        .await
+        .expect("amend should not error");
+
+        let change = jj
+            .get_prepared_commit_for_revision(&testing::config::basic(), "@-")
+            .expect("Failed to prepare commit");
+        assert_eq!(
+            change.message.get(&MessageSection::Labels),
+            Some(&"bug, area/parser".into()),
+            "Labels were not updated"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keeps_local_edits() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let change = create_jujutsu_commit(&mut jj, "Test commit\n\nSummary", "file 1");
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+        let edit = |jj: &mut crate::jj::Jujutsu, text: &str| {
+            let mut revision = jj
+                .read_revision(&config, change.clone())
+                .expect("Revision should exist");
+            revision
+                .message
+                .insert(MessageSection::Summary, text.to_string());
+            jj.update_revision_message(&revision)
+                .expect("Failed to update description");
+        };
+
+        edit(&mut jj, "Local summary");
+        gh.pull_requests.get_mut(&1).expect("PR should exist").title = String::from("New title");
+        super::fetch(FetchOptions::default(), &mut jj, &mut gh, &config)
+            .await
+            .expect("Fetch shouldn't fail");
+        let revision = jj
+            .read_revision(&config, change.clone())
+            .expect("Revision should exist");
+        assert_eq!(
+            revision.message.get(&MessageSection::Title),
+            Some(&"New title".into())
+        );
+        assert_eq!(
+            revision.message.get(&MessageSection::Summary),
+            Some(&"Local summary".into())
+        );
+
+        gh.pull_requests.get_mut(&1).expect("PR should exist").body = String::from("Remote");
+        assert!(
+            super::fetch(FetchOptions::default(), &mut jj, &mut gh, &config)
+                .await
+                .is_err(),
+            "Fetch should fail on the conflict"
+        );
+        let revision = jj
+            .read_revision(&config, change)
+            .expect("Revision should exist");
+        assert_eq!(
+            revision.message.get(&MessageSection::Summary),
+            Some(&"Local summary".into())
+        );
+    }
+
     mod rebase_prs {
         use crate::testing;
 
@@ -655,7 +928,7 @@ mod tests {
             .expect("Should be able to fetch");
 
             let revision = jj
-                .read_revision(child_change)
+                .read_revision(&testing::config::basic(), child_change)
                 .expect("Should be able to read revision");
             assert_eq!(
                 revision.parent_ids.as_slice(),
@@ -695,12 +968,12 @@ mod tests {
             .expect("Should be able to push for setup");
 
             let base_pr = jj
-                .read_revision(base_change.clone())
+                .read_revision(&testing::config::basic(), base_change.clone())
                 .expect("should be able to read base revision")
                 .pull_request_number
                 .expect("base change should have a PR");
             let child_pr = jj
-                .read_revision(child_change.clone())
+                .read_revision(&testing::config::basic(), child_change.clone())
                 .expect("should be able to read child revision")
                 .pull_request_number
                 .expect("child change should have a PR");
@@ -728,7 +1001,7 @@ mod tests {
             .expect("Should be able to fetch");
 
             let revision = jj
-                .read_revision(child_change)
+                .read_revision(&testing::config::basic(), child_change)
                 .expect("Should be able to read revision");
             assert_eq!(
                 revision.parent_ids.as_slice(),
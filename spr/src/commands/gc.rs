@@ -0,0 +1,327 @@
+use std::collections::BTreeSet;
+
+use git2::Oid;
+
+use crate::{
+    error::{Result, ResultExt},
+    github::GHPullRequest,
+    jj::RevSet,
+    journal::{Journal, Mutation},
+    message::MessageSection,
+    plan::{Action, Plan, PlanFormat},
+    utils::run_command,
+};
+
+#[derive(Debug, clap::Parser, Default)]
+pub struct GcOptions {
+    /// Delete the branches without asking
+    #[clap(long, short = 'y')]
+    yes: bool,
+
+    /// Only print which branches would be deleted. Optionally as `json`.
+    #[clap(long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+    dry_run: Option<PlanFormat>,
+}
+
+#[cfg(test)]
+impl GcOptions {
+    pub fn with_yes(mut self, val: bool) -> Self {
+        self.yes = val;
+        self
+    }
+
+    pub fn with_dry_run(mut self, format: PlanFormat) -> Self {
+        self.dry_run = Some(format);
+        self
+    }
+}
+
+/// Remote branches under the branch prefix that nothing uses anymore, with the commit they
+/// point to.
+async fn stale_branches<GH, PR>(
+    jj: &crate::jj::Jujutsu,
+    gh: &mut GH,
+    config: &crate::config::Config,
+) -> Result<Vec<(String, Oid)>>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let remote_prefix = format!("{}/", config.push_remote());
+    let mut branches = Vec::new();
+    for branch in jj.git_repo.branches(Some(git2::BranchType::Remote))? {
+        let (branch, _) = branch?;
+        let (Some(name), Some(oid)) = (branch.name()?, branch.get().target()) else {
+            continue;
+        };
+        if let Some(name) = name.strip_prefix(&remote_prefix)
+            && name.starts_with(&config.branch_prefix)
+        {
+            branches.push((name.to_string(), oid));
+        }
+    }
+
+    let mut in_use = BTreeSet::new();
+    for pr in gh.open_pull_requests().await? {
+        in_use.insert(pr.head_branch_name().to_string());
+        // Open PRs stacked on a branch need it, even if its own PR is gone.
+        in_use.insert(pr.base_branch_name().to_string());
+    }
+
+    let revisions = jj.read_revision_range(config, &RevSet::mutable())?;
+    let local = gh
+        .pull_requests(revisions.iter().map(|r| r.pull_request_number))
+        .await?;
+    for pr in local.into_iter().flatten() {
+        in_use.insert(pr.head_branch_name().to_string());
+    }
+
+    // Revisions without a PR yet, e.g. after a failed push, still need what they pushed.
+    let mut in_use_commits = BTreeSet::new();
+    for revision in revisions.iter() {
+        in_use.extend(revision.bookmarks.iter().cloned());
+        if let Some(oid) = revision
+            .message
+            .get(&MessageSection::LastCommit)
+            .and_then(|c| Oid::from_str(c.trim()).ok())
+        {
+            in_use_commits.insert(oid);
+        }
+    }
+
+    Ok(branches
+        .into_iter()
+        .filter(|(branch, oid)| !in_use.contains(branch) && !in_use_commits.contains(oid))
+        .collect())
+}
+
+async fn delete_branches(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    branches: &[(String, Oid)],
+) -> Result<()> {
+    let mut cmd = tokio::process::Command::new("git");
+    cmd.arg("-C")
+        .arg(jj.git_repo.path())
+        .arg("push")
+        .arg("--atomic")
+        .arg("--no-verify")
+        .arg("--")
+        .arg(config.push_remote());
+    cmd.args(
+        branches
+            .iter()
+            .map(|(branch, _)| format!(":refs/heads/{}", branch)),
+    );
+
+    run_command(&mut cmd)
+        .await
+        .context(String::from("git push failed"))?;
+
+    let journal = Journal::new(jj);
+    for (branch, oid) in branches {
+        journal.record(Mutation::BranchDeleted {
+            branch: branch.clone(),
+            old: Some(oid.to_string()),
+        })?;
+    }
+    Ok(())
+}
+
+pub async fn gc<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    opts: GcOptions,
+) -> Result<()>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let mut plan = Plan::new();
+    match opts.dry_run {
+        Some(_) => plan.push(Action::Fetch),
+        None => jj.run_git_fetch_for(config)?,
+    }
+
+    let stale = stale_branches(jj, &mut gh, config).await?;
+    if stale.is_empty() && opts.dry_run.is_none() {
+        crate::output::output(&config.icons.sleeping, "No stale branches")?;
+        return Ok(());
+    }
+    for (branch, _) in stale.iter() {
+        plan.push(Action::DeleteBranch {
+            branch: branch.clone(),
+        });
+    }
+
+    if let Some(format) = opts.dry_run {
+        return plan.print(config, format);
+    }
+    plan.print(config, PlanFormat::Text)?;
+
+    if !opts.yes
+        && !dialoguer::Confirm::new()
+            .with_prompt(format!("Delete {} branches?", stale.len()))
+            .default(false)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    delete_branches(jj, config, &stale).await?;
+    crate::output::output(
+        &config.icons.ok,
+        format!("Deleted {} branches", stale.len()),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{github::fakes::PullRequest, testing};
+
+    fn closed(base: &str, head: &str, number: u64) -> PullRequest {
+        let mut pr = PullRequest::new(base, head, number, "Title", "");
+        pr.closed = true;
+        pr
+    }
+
+    #[tokio::test]
+    async fn keeps_used_branches() {
+        let (_temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let base = testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/base");
+        for branch in [
+            "spr/test/open",
+            "spr/test/dead",
+            "spr/test/local",
+            "other/branch",
+        ] {
+            testing::git::add_commit_and_push_to_remote(&jj.git_repo, branch);
+        }
+        testing::git::add_commit_on_and_push_to_remote(&jj.git_repo, "spr/test/stacked", [base]);
+        jj.run_git_fetch().expect("Fetch shouldn't fail");
+
+        std::fs::write(
+            jj.git_repo
+                .workdir()
+                .expect("Failed to extract workdir from JJ handle")
+                .join("file"),
+            "content",
+        )
+        .expect("Failed to write test file");
+        jj.commit(format!(
+            "Local change\n\nPull Request: {}",
+            config.pull_request_url(5)
+        ))
+        .expect("Failed to commit revision");
+
+        let mut gh = crate::github::fakes::GitHub {
+            pull_requests: std::collections::BTreeMap::from([
+                (1, PullRequest::new("main", "spr/test/open", 1, "Open", "")),
+                (2, closed("main", "spr/test/base", 2)),
+                (
+                    3,
+                    PullRequest::new("spr/test/base", "spr/test/stacked", 3, "Stacked", ""),
+                ),
+                (4, closed("main", "spr/test/dead", 4)),
+                (5, closed("main", "spr/test/local", 5)),
+            ]),
+            ..Default::default()
+        };
+
+        let stale = super::stale_branches(&jj, &mut &mut gh, &config)
+            .await
+            .expect("Listing stale branches shouldn't fail");
+        assert_eq!(
+            stale.iter().map(|(b, _)| b.as_str()).collect::<Vec<_>>(),
+            vec!["spr/test/dead"]
+        );
+
+        super::gc(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::GcOptions::default().with_yes(true),
+        )
+        .await
+        .expect("gc shouldn't fail");
+
+        for branch in [
+            "spr/test/open",
+            "spr/test/base",
+            "spr/test/stacked",
+            "spr/test/local",
+            "other/branch",
+        ] {
+            bare.find_branch(branch, git2::BranchType::Local)
+                .expect("Used branch should be kept");
+        }
+        assert!(
+            bare.find_branch("spr/test/dead", git2::BranchType::Local)
+                .is_err(),
+            "Stale branch should be deleted"
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_branches_of_revisions_without_pr() {
+        let (_temp_dir, mut jj, _bare) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let pushed = testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/pushed");
+        testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/bookmarked");
+        testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/dead");
+        jj.run_git_fetch().expect("Fetch shouldn't fail");
+
+        std::fs::write(
+            jj.git_repo
+                .workdir()
+                .expect("Failed to extract workdir from JJ handle")
+                .join("file"),
+            "content",
+        )
+        .expect("Failed to write test file");
+        jj.commit(format!("Failed push\n\nLast Commit: {}", pushed))
+            .expect("Failed to commit revision");
+        jj.bookmark_create("spr/test/bookmarked", Some("@-"))
+            .expect("Failed to create bookmark");
+
+        let mut gh = crate::github::fakes::GitHub::default();
+        let stale = super::stale_branches(&jj, &mut &mut gh, &config)
+            .await
+            .expect("Listing stale branches shouldn't fail");
+        assert_eq!(
+            stale.iter().map(|(b, _)| b.as_str()).collect::<Vec<_>>(),
+            vec!["spr/test/dead"]
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_deletes_nothing() {
+        let (_temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        testing::git::add_commit_and_push_to_remote(&jj.git_repo, "spr/test/dead");
+        jj.run_git_fetch().expect("Fetch shouldn't fail");
+
+        let mut gh = crate::github::fakes::GitHub {
+            pull_requests: std::collections::BTreeMap::from([(
+                1,
+                closed("main", "spr/test/dead", 1),
+            )]),
+            ..Default::default()
+        };
+        super::gc(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::GcOptions::default().with_dry_run(crate::plan::PlanFormat::Json),
+        )
+        .await
+        .expect("gc shouldn't fail");
+
+        bare.find_branch("spr/test/dead", git2::BranchType::Local)
+            .expect("Dry run shouldn't delete branches");
+    }
+}
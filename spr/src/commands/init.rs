@@ -21,6 +21,41 @@ pub async fn init() -> Result<()> {
 
     crate::output::output(&icons.wave, "Welcome to spr!")?;
 
+    // Name of remote
+    let remote = match crate::config::remote_from_jj(&jj) {
+        Ok(remote) => remote,
+        Err(_) => {
+            console::Term::stdout().write_line("")?;
+
+            // TODO: Improve this logic with suggestions.
+            let remote = dialoguer::Input::<String>::new()
+                .with_prompt("Name of remote for GitHub")
+                .with_initial_text("origin")
+                .interact_text()?;
+
+            jj.config_set("spr.githubRemoteName", &remote, false)?;
+            remote
+        }
+    };
+
+    // GitHub Enterprise Server, if the remote isn't on github.com
+    let detected = crate::config::forge_from_jj(&jj, &remote)
+        .map_or_else(|_| String::from("github.com"), |(_, host)| host);
+    let host = if detected == "github.com" {
+        detected
+    } else {
+        console::Term::stdout().write_line("")?;
+        let host = dialoguer::Input::<String>::new()
+            .with_prompt("Host of your GitHub Enterprise Server")
+            .with_initial_text(&detected)
+            .interact_text()?;
+        if host != "github.com" {
+            jj.config_set("spr.githubHost", &host, false)?;
+        }
+        host
+    };
+    let api_url = crate::config::github_api_url(&jj, &host);
+
     // GitHub Personal Access Token
 
     console::Term::stdout().write_line("")?;
@@ -59,14 +94,15 @@ pub async fn init() -> Result<()> {
                 "We need a 'Personal Access Token' from GitHub. This will \
              authorise spr to open/update/merge Pull Requests etc. on behalf of \
              your GitHub user.
-             You can get one by going to https://github.com/settings/tokens \
+             You can get one by going to https://{host}/settings/tokens \
              and clicking on 'Generate new token'. The token needs the 'repo', \
              'user' and 'read:org' permissions, so please tick those three boxes \
              in the 'Select scopes' section.
              You might want to set the 'Expiration' to 'No expiration', as \
              otherwise you will have to repeat this procedure soon. Even \
              if the token does not expire, you can always revoke it in case \
-             you fear someone got hold of it."
+             you fear someone got hold of it.",
+                host = host
             ),
         )?;
 
@@ -83,6 +119,7 @@ pub async fn init() -> Result<()> {
 
     let octocrab = octocrab::OctocrabBuilder::default()
         .personal_token(pat.clone())
+        .base_uri(api_url)?
         .build()?;
     let github_user = octocrab.current().user().await?;
 
@@ -108,24 +145,10 @@ pub async fn init() -> Result<()> {
         jj.config_set("spr.githubAuthToken", pat.as_str(), user)?;
     }
 
-    // Name of remote
-    let remote = match crate::config::remote_from_jj(&jj) {
-        Ok(remote) => remote,
-        Err(_) => {
-            console::Term::stdout().write_line("")?;
-
-            // TODO: Improve this logic with suggestions.
-            let remote = dialoguer::Input::<String>::new()
-                .with_prompt("Name of remote for GitHub")
-                .with_initial_text("origin")
-                .interact_text()?;
-
-            jj.config_set("spr.githubRemoteName", &remote, false)?;
-            remote
-        }
-    };
-
-    let owner_and_repo = match crate::config::repo_and_owner_from_jj(&jj, remote.as_ref()) {
+    let forge = crate::config::forge_from_jj(&jj, &remote)
+        .map(|(forge, _)| forge)
+        .unwrap_or_default();
+    let owner_and_repo = match crate::config::repo_and_owner_from_jj(&jj, remote.as_ref(), forge) {
         Ok((repo, owner)) => format!("{}/{}", owner, repo),
         Err(_) => {
             // Name of the GitHub repo
@@ -233,7 +256,7 @@ pub async fn init() -> Result<()> {
     Ok(())
 }
 
-fn validate_branch_prefix(branch_prefix: &str) -> Result<()> {
+pub(crate) fn validate_branch_prefix(branch_prefix: &str) -> Result<()> {
     // They can include slash / for hierarchical (directory) grouping, but no slash-separated component can begin with a dot . or end with the sequence .lock.
     if branch_prefix.contains("/.")
         || branch_prefix.contains(".lock/")
@@ -0,0 +1,246 @@
+use std::iter::zip;
+
+use crate::{
+    error::{Error, Result},
+    jj::{ChangeId, RevSet},
+    message::{MessageSection, build_github_body_for_merging},
+};
+
+#[derive(Debug, clap::Parser, Default)]
+pub struct LandOptions {
+    /// Revision whose stack should be landed. The lowest open PR in its ancestors is merged.
+    #[clap(long, short = 'r')]
+    revset: Option<String>,
+
+    /// How GitHub should merge the PR. Defaults to spr.land.method (squash if unset).
+    #[clap(long, value_enum)]
+    method: Option<crate::github::MergeMethod>,
+}
+
+#[cfg(test)]
+impl LandOptions {
+    pub fn with_revset<S>(mut self, revset: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.revset = revset.map(|s| s.into());
+        self
+    }
+
+    pub fn with_method(mut self, method: crate::github::MergeMethod) -> Self {
+        self.method = Some(method);
+        self
+    }
+}
+
+pub async fn land<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    opts: LandOptions,
+) -> Result<()>
+where
+    PR: crate::github::GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    jj.run_git_fetch_for(config)?;
+    let revset = opts
+        .revset
+        .as_ref()
+        .map(RevSet::from_arg)
+        .unwrap_or(RevSet::current())
+        .ancestors()
+        .and(&RevSet::mutable())
+        .and(&RevSet::description("glob:\"*Pull Request:*\""));
+
+    // Revisions are read bottom up, so the first open PR is the one to land.
+    let revisions = jj.read_revision_range(config, &revset)?;
+    let pull_requests = gh
+        .pull_requests(revisions.iter().map(|r| r.pull_request_number))
+        .await?;
+
+    let (revision, pr) = zip(revisions, pull_requests)
+        .filter_map(|(rev, pr)| pr.map(|pr| (rev, pr)))
+        .find(|(_, pr)| !pr.closed())
+        .ok_or_else(|| Error::new("Found no open pull request to land"))?;
+    let url = config.pull_request_url(pr.pr_number());
+
+    if pr.base_branch_name() != config.master_ref {
+        return Err(Error::new(format!(
+            "{} is based on '{}' instead of '{}'. Land its parent first.",
+            url,
+            pr.base_branch_name(),
+            config.master_ref
+        )));
+    }
+
+    // Only land what was reviewed. If GitHub has a different head than we last pushed, the user
+    // has to reconcile with `push` or `fetch` first.
+    let head = pr.head_branch_name();
+    let head_oid = jj
+        .git_repo
+        .revparse_single(format!("{}/{}", config.branch_remote(&jj.git_repo, head), head).as_str())?
+        .id();
+    match revision.message.get(&MessageSection::LastCommit) {
+        Some(last) if git2::Oid::from_str(last)? == head_oid => {}
+        _ => {
+            return Err(Error::new(format!(
+                "{} is out of date with its local revision {}. Use `push` or `fetch` before landing.",
+                url, revision.id
+            )));
+        }
+    }
+
+    let method = opts.method.unwrap_or(config.land.method);
+    crate::output::output(&config.icons.land, format!("Landing {}", url))?;
+    gh.merge_pull_request(
+        &pr,
+        method,
+        format!("{} (#{})", revision.title, pr.pr_number()),
+        build_github_body_for_merging(&revision.message),
+    )
+    .await?;
+
+    let children = jj.read_revision_range(
+        config,
+        &RevSet::from(&revision.id)
+            .children()
+            .and(&RevSet::mutable()),
+    )?;
+    let child_prs = gh
+        .pull_requests(children.iter().map(|r| r.pull_request_number))
+        .await?;
+    for child in child_prs.into_iter().flatten() {
+        if !child.closed() && child.base_branch_name() == pr.head_branch_name() {
+            crate::output::output(
+                &config.icons.refresh,
+                format!(
+                    "Retargeting {} onto {}",
+                    config.pull_request_url(child.pr_number()),
+                    config.master_ref
+                ),
+            )?;
+            gh.rebase_pr(child.pr_number(), &config.master_ref).await?;
+        }
+    }
+
+    jj.run_git_fetch_for(config)?;
+    crate::output::output(
+        &config.icons.land,
+        format!("{} landed. Abandoning {:?}", url, revision.id),
+    )?;
+    jj.abandon(&RevSet::from(&revision.id).unique())?;
+
+    if children.is_empty() {
+        crate::output::output(&config.icons.wave, "Nothing left to rebase")?;
+        return Ok(());
+    }
+
+    let trunk = ChangeId::from(format!("{}@{}", config.master_ref, config.remote_name));
+    for child in children {
+        crate::output::output(
+            &config.icons.refresh,
+            format!("Going to rebase {:?}", child.id),
+        )?;
+        jj.rebase_branch(&RevSet::from(&child.id), trunk.clone())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{jj::RevSet, testing};
+
+    #[tokio::test]
+    async fn lands_bottom_and_restacks() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let trunk = jj
+            .revset_to_change_id(&RevSet::current().parent())
+            .expect("Should find trunk revision");
+        let bottom = testing::jj::create_jujutsu_commit(&mut jj, "Bottom commit", "bottom");
+        let top = testing::jj::create_jujutsu_commit(&mut jj, "Top commit", "top");
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+
+        super::land(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::LandOptions::default().with_method(crate::github::MergeMethod::Squash),
+        )
+        .await
+        .expect("Land shouldn't fail");
+
+        let landed = gh.pull_requests.get(&1).expect("Bottom PR should exist");
+        assert!(landed.merged, "Bottom PR wasn't merged");
+        let child = gh.pull_requests.get(&2).expect("Top PR should exist");
+        assert!(!child.merged, "Top PR shouldn't be merged");
+        assert_eq!(child.base, config.master_ref, "Top PR wasn't retargeted");
+
+        assert!(
+            jj.read_revision(&config, bottom).is_err(),
+            "Landed revision wasn't abandoned"
+        );
+        let top = jj
+            .read_revision(&config, top)
+            .expect("Top revision should remain");
+        assert_eq!(
+            top.parent_ids.as_slice(),
+            &[trunk],
+            "Top revision wasn't rebased onto trunk"
+        );
+    }
+
+    #[tokio::test]
+    async fn refuses_outdated_pr() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+
+        let head = jj
+            .git_repo
+            .revparse_single("origin/spr/test/test-commit")
+            .expect("Pushed branch should exist")
+            .id();
+        testing::git::add_commit_on_and_push_to_remote_file(
+            &jj.git_repo,
+            "spr/test/test-commit",
+            [head],
+            "other-file",
+        );
+
+        super::land(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::LandOptions::default().with_revset(Some("@-")),
+        )
+        .await
+        .expect_err("Land should refuse a PR that moved upstream");
+
+        assert!(
+            !gh.pull_requests.get(&1).expect("PR should exist").merged,
+            "Outdated PR was merged"
+        );
+    }
+}
@@ -6,10 +6,19 @@
  */
 
 pub mod adopt;
+pub mod close;
 pub mod detach;
+pub mod diff;
+pub mod doctor;
 pub mod fetch;
+pub mod gc;
 pub mod init;
+pub mod land;
 pub mod push;
+pub mod ready;
+pub mod status;
 pub mod sync;
 #[cfg(test)]
 pub mod tests;
+pub mod undo;
+pub mod watch;
@@ -1,7 +1,14 @@
 use crate::{
+    codeowners::CodeOwners,
+    config::push::CodeOwnersMode,
     error::{Error, Result, ResultExt},
+    github::Reviewer,
     jj::RevSet,
+    journal::{Journal, Mutation},
     message::{MessageSection, build_github_body},
+    plan::{Action, Plan, PlanFormat},
+    snapshot::Merge,
+    template::Template,
     utils::run_command,
 };
 use git2::Oid;
@@ -29,6 +36,10 @@ pub struct PushOptions {
 
     #[clap(long, num_args = 0..=1, default_missing_value = "true")]
     pub draft: Option<bool>,
+
+    /// Only print what would be done. Optionally as `json`.
+    #[clap(long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+    dry_run: Option<PlanFormat>,
 }
 
 #[cfg(test)]
@@ -68,6 +79,11 @@ impl PushOptions {
         self.draft = Some(val);
         self
     }
+
+    pub fn with_dry_run(mut self, format: PlanFormat) -> Self {
+        self.dry_run = Some(format);
+        self
+    }
 }
 
 enum WorkEvent<'a> {
@@ -76,25 +92,303 @@ enum WorkEvent<'a> {
     PRCreated(&'a crate::config::Config),
     ReviewRequested(&'a crate::config::Config),
     Assigned(&'a crate::config::Config),
+    ReviewersSuggested(&'a crate::config::Config, String),
+    ReviewersUpdated(&'a crate::config::Config),
+    AssigneesUpdated(&'a crate::config::Config),
+    Labeled(&'a crate::config::Config),
+    Edited(&'a crate::config::Config),
+    KeptRemote(&'a crate::config::Config),
+    Draft(&'a crate::config::Config, bool),
 }
 
 type WorkLog<'a> = Vec<WorkEvent<'a>>;
 impl<'a> std::fmt::Display for WorkEvent<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let (name, icon) = match self {
-            WorkEvent::Rebased(c) => ("Rebased", c.icons.refresh.as_ref()),
-            WorkEvent::Updated(c) => ("Updated", c.icons.working.as_ref()),
-            WorkEvent::PRCreated(c) => ("Create Pull Request", c.icons.sparkle.as_ref()),
-            WorkEvent::ReviewRequested(c) => ("Requested Reviews", c.icons.eyes.as_ref()),
-            WorkEvent::Assigned(c) => ("Assigned users", c.icons.ok.as_ref()),
+            WorkEvent::Rebased(c) => ("Rebased".into(), c.icons.refresh.as_ref()),
+            WorkEvent::Updated(c) => ("Updated".into(), c.icons.working.as_ref()),
+            WorkEvent::PRCreated(c) => ("Create Pull Request".into(), c.icons.sparkle.as_ref()),
+            WorkEvent::ReviewRequested(c) => ("Requested Reviews".into(), c.icons.eyes.as_ref()),
+            WorkEvent::Assigned(c) => ("Assigned users".into(), c.icons.ok.as_ref()),
+            WorkEvent::ReviewersSuggested(c, owners) => {
+                (format!("Code owners {}", owners), c.icons.question.as_ref())
+            }
+            WorkEvent::ReviewersUpdated(c) => ("Updated reviewers".into(), c.icons.eyes.as_ref()),
+            WorkEvent::AssigneesUpdated(c) => ("Updated assignees".into(), c.icons.ok.as_ref()),
+            WorkEvent::Labeled(c) => ("Updated labels".into(), c.icons.ok.as_ref()),
+            WorkEvent::Edited(c) => ("Updated title and summary".into(), c.icons.info.as_ref()),
+            WorkEvent::KeptRemote(c) => (
+                "Kept title and summary of the PR, `fetch` takes them".into(),
+                c.icons.info.as_ref(),
+            ),
+            WorkEvent::Draft(c, true) => ("Converted to draft".into(), c.icons.sleeping.as_ref()),
+            WorkEvent::Draft(c, false) => ("Ready for review".into(), c.icons.eyes.as_ref()),
         };
 
-        f.write_str(name)?;
+        f.write_str(&name)?;
         f.write_str(icon)?;
         Ok(())
     }
 }
 
+/// Names in a comma separated `Reviewers` or `Assignees` section.
+fn reviewer_names(section: &str) -> impl Iterator<Item = &str> {
+    section
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+}
+
+/// Code owners of the files `revision` touches that aren't in its `Reviewers` section yet. The
+/// user is left out, since nobody can review their own PR.
+fn code_owners(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    revision: &crate::jj::Revision,
+) -> Result<Vec<String>> {
+    let commit = jj.resolve_revision_to_commit_id(RevSet::from(&revision.id).as_ref())?;
+    let Some(codeowners) = CodeOwners::from_commit(&jj.git_repo, commit)? else {
+        return Ok(Vec::new());
+    };
+    let requested: Vec<_> = revision
+        .message
+        .get(&MessageSection::Reviewers)
+        .map(|r| reviewer_names(r).map(Reviewer::parse).collect())
+        .unwrap_or_default();
+
+    Ok(codeowners
+        .owners_of(jj.changed_files(&revision.id)?)
+        .into_iter()
+        .filter(|owner| !requested.contains(&Reviewer::parse(owner)))
+        .filter(|owner| {
+            !config
+                .user
+                .as_deref()
+                .is_some_and(|user| Reviewer::parse(owner).is(user))
+        })
+        .collect())
+}
+
+/// Body for a new PR: the summary, inside the repository's pull request template if it has one.
+fn pull_request_body(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    revision: &crate::jj::Revision,
+) -> Result<String> {
+    let summary = revision
+        .message
+        .get(&MessageSection::Summary)
+        .map_or("", |s| s.as_str());
+    let name = revision
+        .message
+        .get(&MessageSection::Template)
+        .or(config.push.template.as_ref())
+        .map(|s| s.trim());
+    let commit = jj.resolve_revision_to_commit_id(RevSet::from(&revision.id).as_ref())?;
+
+    Ok(
+        match Template::from_commit(&jj.git_repo, commit, name)
+            .context(format!("Read pull request template for {}", revision.title))?
+        {
+            Some(template) => template.fill(&config.push.template_placeholder, summary),
+            None => summary.to_string(),
+        },
+    )
+}
+
+/// Labels `revision` should have: the ones in its `Labels` section, and the ones the rules in
+/// `spr.push.labels` derive from its title and files.
+fn wanted_labels(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    revision: &crate::jj::Revision,
+) -> Result<Vec<String>> {
+    let mut labels = revision
+        .message
+        .get(&MessageSection::Labels)
+        .map(|section| crate::labels::parse(section))
+        .unwrap_or_default();
+    let paths = if crate::labels::need_paths(&config.push.labels) {
+        jj.changed_files(&revision.id)?
+    } else {
+        Vec::new()
+    };
+    for label in crate::labels::derive(&config.push.labels, &revision.title, &paths) {
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    Ok(labels)
+}
+
+/// Labels to add to and remove from `pr`. Only labels spr manages are removed: the ones of the
+/// rules in `spr.push.labels`, and the ones that were in the `Labels` section when it was last
+/// pushed. Labels that were added on GitHub stay.
+fn label_changes<PR: crate::github::GHPullRequest>(
+    config: &crate::config::Config,
+    revision: &crate::jj::Revision,
+    pr: &PR,
+    wanted: &[String],
+) -> (Vec<String>, Vec<String>) {
+    let added = wanted
+        .iter()
+        .filter(|label| !pr.labels().contains(label))
+        .cloned()
+        .collect();
+    let synced = revision
+        .message
+        .get(&MessageSection::SyncedLabels)
+        .map(|section| crate::labels::parse(section))
+        .unwrap_or_default();
+    let removed = pr
+        .labels()
+        .iter()
+        .filter(|label| !wanted.contains(label))
+        .filter(|label| {
+            synced.contains(label) || config.push.labels.iter().any(|rule| &rule.label == *label)
+        })
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+/// Users or teams to add to and remove from `current`, so it matches `section`. Whoever is in
+/// `reviewed` already submitted a review and is left alone. Given `synced`, the section as it
+/// was last pushed, only the accounts in it are removed, so requests made on GitHub, like the
+/// ones for code owners, stay. Without `section` or `synced` nothing changes.
+fn account_changes(
+    section: Option<&String>,
+    synced: Option<&str>,
+    current: &[String],
+    reviewed: &[String],
+) -> (Vec<String>, Vec<String>) {
+    if section.is_none() && synced.is_none() {
+        return (Vec::new(), Vec::new());
+    }
+    let wanted: Vec<Reviewer> = section
+        .into_iter()
+        .flat_map(|section| reviewer_names(section))
+        .map(Reviewer::parse)
+        .collect();
+    let synced: Option<Vec<Reviewer>> =
+        synced.map(|synced| reviewer_names(synced).map(Reviewer::parse).collect());
+    let known = |name: &String| wanted.iter().any(|reviewer| reviewer.is(name));
+    let managed = |name: &String| {
+        synced
+            .as_ref()
+            .is_none_or(|synced| synced.iter().any(|reviewer| reviewer.is(name)))
+    };
+    let done = |reviewer: &Reviewer| {
+        current
+            .iter()
+            .chain(reviewed.iter())
+            .any(|name| reviewer.is(name))
+    };
+
+    let mut added: Vec<String> = Vec::new();
+    for reviewer in wanted.iter().filter(|reviewer| !done(reviewer)) {
+        if !added.iter().any(|name| reviewer.is(name)) {
+            added.push(reviewer.to_string());
+        }
+    }
+    let removed = current
+        .iter()
+        .filter(|name| !known(name) && !reviewed.contains(name) && managed(name))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+/// Whether the revision's copy of a field goes to the PR: if only it changed. Conflicts, and
+/// differences without a snapshot to tell which side changed, only with `--force`.
+fn outgoing(merge: Merge, force: bool) -> bool {
+    match merge {
+        Merge::Local => true,
+        Merge::Conflict | Merge::Unknown => force,
+        Merge::InSync | Merge::Remote => false,
+    }
+}
+
+/// Write the title and summary of `revision` to `pr`, where they changed only locally. Returns
+/// whether the PR was edited, whether a field without a snapshot kept the PR's copy, and the
+/// fields that conflict with edits on GitHub.
+async fn sync_text<GH, PR>(
+    gh: &mut GH,
+    revision: &mut crate::jj::Revision,
+    pr: &PR,
+    force: bool,
+) -> Result<(bool, bool, Vec<&'static str>)>
+where
+    PR: crate::github::GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let (mut title, mut summary) = crate::snapshot::remote(pr);
+    let changes = crate::snapshot::compare(&revision.message, &title, &summary)?;
+    let new_title = outgoing(changes.title, force).then(|| {
+        revision
+            .message
+            .get(&MessageSection::Title)
+            .map_or("", |s| s.as_str())
+            .to_string()
+    });
+    let new_summary = outgoing(changes.summary, force).then(|| {
+        revision
+            .message
+            .get(&MessageSection::Summary)
+            .map_or("", |s| s.as_str())
+            .to_string()
+    });
+
+    let edited = new_title.is_some() || new_summary.is_some();
+    if edited {
+        gh.edit_pull_request(
+            pr,
+            new_title.clone(),
+            new_summary
+                .as_ref()
+                .map(|summary| crate::template::replace(pr.body(), summary)),
+        )
+        .await?;
+        title = new_title.unwrap_or(title);
+        summary = new_summary.unwrap_or(summary);
+    }
+    crate::snapshot::update(&mut revision.message, &title, &summary)?;
+
+    let kept = !force && [changes.title, changes.summary].contains(&Merge::Unknown);
+    let conflicts = match force {
+        true => Vec::new(),
+        false => changes.conflicts().collect(),
+    };
+    Ok((edited, kept, conflicts))
+}
+
+/// The draft state `revision` asks for in its `Draft` section, if it has one.
+fn requested_draft(revision: &crate::jj::Revision) -> Result<Option<bool>> {
+    revision
+        .message
+        .get(&MessageSection::Draft)
+        .map(|value| {
+            crate::message::parse_draft(value)
+                .context(format!("Read Draft section of {}", revision.title))
+        })
+        .transpose()
+}
+
+/// Remember the entries of `section` in `synced`, so the ones removed from it are removed from
+/// the PR on the next push.
+fn set_synced(revision: &mut crate::jj::Revision, section: MessageSection, synced: MessageSection) {
+    let entries = revision
+        .message
+        .get(&section)
+        .map(|section| reviewer_names(section).collect::<Vec<_>>().join(", "))
+        .unwrap_or_default();
+    if entries.is_empty() {
+        revision.message.remove(&synced);
+    } else {
+        revision.message.insert(synced, entries);
+    }
+}
+
 struct WorkSet<'a, PR> {
     revision: crate::jj::Revision,
     progress_bar: indicatif::ProgressBar,
@@ -131,23 +425,29 @@ impl<'a, PR> WorkSet<'a, PR> {
     }
 }
 
-async fn do_push_single<'a, PR, H: AsRef<str>>(
-    jj: &mut crate::jj::Jujutsu,
-    config: &'a crate::config::Config,
+struct PushState {
+    base_oid: Oid,
+    head_oid: Oid,
+    target_oid: Oid,
+    base_base: Oid,
+    up_to_date: bool,
+}
+
+/// Compare a revision with its remote branch, without changing anything.
+fn push_state(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
     opts: &PushOptions,
     base_ref: &crate::jj::RevSet,
-    head_branch: H,
-    ws: &mut WorkSet<'a, PR>,
-) -> Result<Oid> {
-    ws.progress_bar.set_message("Building new commit");
+    head_branch: &str,
+    revision: &crate::jj::Revision,
+) -> Result<PushState> {
     let base_oid = jj
         .resolve_revision_to_commit_id(base_ref.as_ref())
         .context(String::from("Resolve base_ref to OID"))?;
     let head_oid = jj
         .git_repo
-        .revparse_single(
-            format!("{}/{}", config.remote_name.as_str(), head_branch.as_ref()).as_str(),
-        )
+        .revparse_single(format!("{}/{}", config.push_remote(), head_branch).as_str())
         .map(|o| o.id())
         .unwrap_or(base_oid.clone());
 
@@ -157,7 +457,7 @@ async fn do_push_single<'a, PR, H: AsRef<str>>(
     })?;
 
     let target_oid = jj
-        .resolve_revision_to_commit_id(ws.revision.id.as_ref())
+        .resolve_revision_to_commit_id(revision.id.as_ref())
         .map_err(|mut err| {
             err.push("resolve revision".into());
             err
@@ -171,27 +471,62 @@ async fn do_push_single<'a, PR, H: AsRef<str>>(
         .git_repo
         .merge_base(head_oid, base_oid)
         .map_err(|err| std::io::Error::new(ErrorKind::InvalidInput, err.to_string()))?;
+
+    let up_to_date = target_tree == head_tree && base_base == base_oid;
+    if !up_to_date
+        && !opts.force
+        && let Some(old) = revision.message.get(&MessageSection::LastCommit)
+        && git2::Oid::from_str(old)? != head_oid
+    {
+        return Err(crate::error::Error::new(format!(
+            "Cannot update {}. It has an unexpected upstream.\nUse `--force` to ignore remote changes.\nUse `fetch` to merge upstream changes into local revision.",
+            config.pull_request_url(revision.pull_request_number.unwrap_or(0))
+        )));
+    }
+
+    Ok(PushState {
+        base_oid,
+        head_oid,
+        target_oid,
+        base_base,
+        up_to_date,
+    })
+}
+
+async fn do_push_single<'a, PR, H: AsRef<str>>(
+    jj: &mut crate::jj::Jujutsu,
+    config: &'a crate::config::Config,
+    opts: &PushOptions,
+    base_ref: &crate::jj::RevSet,
+    head_branch: H,
+    ws: &mut WorkSet<'a, PR>,
+) -> Result<Oid> {
+    ws.progress_bar.set_message("Building new commit");
+    let PushState {
+        base_oid,
+        head_oid,
+        target_oid,
+        base_base,
+        up_to_date,
+    } = push_state(
+        jj,
+        config,
+        opts,
+        base_ref,
+        head_branch.as_ref(),
+        &ws.revision,
+    )?;
     let parents: &[Oid] = if base_base != base_oid {
         &[head_oid, base_oid]
     } else {
         &[head_oid]
     };
 
-    if target_tree == head_tree && base_base == base_oid {
+    if up_to_date {
         ws.progress_bar.set_message("Git is already up to date");
         return Ok(head_oid);
     }
 
-    if !opts.force
-        && let Some(old) = ws.revision.message.get(&MessageSection::LastCommit)
-        && git2::Oid::from_str(old)? != head_oid
-    {
-        return Err(crate::error::Error::new(format!(
-            "Cannot update {}. It has an unexpected upstream.\nUse `--force` to ignore remote changes.\nUse `fetch` to merge upstream changes into local revision.",
-            config.pull_request_url(ws.revision.pull_request_number.unwrap_or(0))
-        )));
-    }
-
     let real_change = parents.len() == 1 || {
         let pre_rev = {
             let pre_commit = jj
@@ -274,6 +609,8 @@ struct BranchAction<PR> {
     base_branch: String,
     old_pr: Option<PR>,
     last_commit: Oid,
+    /// Whether the branch has to be pushed to the remote.
+    pushed: bool,
 }
 
 async fn do_push<'a, I, PR>(
@@ -282,6 +619,7 @@ async fn do_push<'a, I, PR>(
     opts: &PushOptions,
     work: I,
     trunk_head: &crate::jj::RevSet,
+    plan: Option<&mut Plan>,
 ) -> Result<Vec<WorkSet<'a, BranchAction<PR>>>>
 where
     PR: crate::github::GHPullRequest,
@@ -306,17 +644,23 @@ where
                 .unwrap_or("");
             config.get_new_branch_name(&jj.get_all_ref_names()?, title)
         };
-        let (base_branch, base_ref) = if let Some(ba) = seen
+        let parent = seen
             .iter()
-            .find(|ba| ba.revision.id == ws.revision.parent_ids[0])
-        {
+            .find(|ba| ba.revision.id == ws.revision.parent_ids[0]);
+        let parent_pushed = parent.is_some_and(|ba| ba.pull_request.pushed);
+        let (base_branch, base_ref) = if let Some(ba) = parent {
             let commit = jj
                 .git_repo
                 .find_commit(ba.pull_request.last_commit.clone())
                 .context(String::from("Find commit to base on"))?;
 
+            // A PR can't be based on a branch in a fork. Stacked ones go against trunk then, and
+            // also show the changes of their parents.
             (
-                Some(ba.pull_request.head_branch.clone()),
+                config
+                    .fork
+                    .is_none()
+                    .then(|| ba.pull_request.head_branch.clone()),
                 RevSet::from(&commit),
             )
         } else {
@@ -326,12 +670,26 @@ where
             )
         };
 
-        let last_commit = do_push_single(jj, config, opts, &base_ref, &head_ref, &mut ws)
-            .await
-            .map_err(|mut err| {
-                err.push("do_push_single".into());
-                err
-            })?;
+        let (last_commit, changed) = if plan.is_some() {
+            // Nothing is created in a dry run. A revision changes if its content differs from the
+            // remote or if its parent gets a new commit that has to be merged in.
+            let state = push_state(jj, config, opts, &base_ref, &head_ref, &ws.revision)?;
+            (state.head_oid, !state.up_to_date || parent_pushed)
+        } else {
+            let last_commit = do_push_single(jj, config, opts, &base_ref, &head_ref, &mut ws)
+                .await
+                .map_err(|mut err| {
+                    err.push("do_push_single".into());
+                    err
+                })?;
+            (last_commit, false)
+        };
+        let pushed = changed
+            || ws
+                .revision
+                .message
+                .get(&MessageSection::LastCommit)
+                .is_none_or(|lc| *lc != last_commit.to_string());
 
         let owned_base = base_branch.unwrap_or(config.master_ref.clone()).into();
         seen.push(ws.map(|pr| BranchAction {
@@ -339,26 +697,50 @@ where
             base_branch: owned_base,
             old_pr: pr,
             last_commit,
+            pushed,
         }));
     }
 
+    if let Some(plan) = plan {
+        for ws in seen.iter().filter(|ws| ws.pull_request.pushed) {
+            plan.push(Action::PushBranch {
+                revision: ws.revision.id.to_string(),
+                branch: ws.pull_request.head_branch.clone(),
+            });
+        }
+        return Ok(seen);
+    }
+
     let updates: Vec<String> = seen
         .iter()
-        .filter_map(|ws| {
-            if let Some(lc) = ws.revision.message.get(&MessageSection::LastCommit)
-                && *lc == ws.pull_request.last_commit.to_string()
-            {
-                None
-            } else {
-                Some(format!(
-                    "{}:refs/heads/{}",
-                    ws.pull_request.last_commit, ws.pull_request.head_branch
-                ))
-            }
+        .filter(|ws| ws.pull_request.pushed)
+        .map(|ws| {
+            format!(
+                "{}:refs/heads/{}",
+                ws.pull_request.last_commit, ws.pull_request.head_branch
+            )
         })
         .collect();
 
     if !updates.is_empty() {
+        // Remember what the branches pointed to, so the push can be undone.
+        let journaled: Vec<Mutation> = seen
+            .iter()
+            .filter(|ws| ws.pull_request.pushed)
+            .map(|ws| Mutation::BranchPushed {
+                branch: ws.pull_request.head_branch.clone(),
+                old: jj
+                    .git_repo
+                    .revparse_single(
+                        format!("{}/{}", config.push_remote(), ws.pull_request.head_branch)
+                            .as_str(),
+                    )
+                    .ok()
+                    .map(|o| o.id().to_string()),
+                new: ws.pull_request.last_commit.to_string(),
+            })
+            .collect();
+
         let mut cmd = tokio::process::Command::new("git");
         cmd.arg("-C")
             .arg(jj.git_repo.path())
@@ -366,12 +748,16 @@ where
             .arg("--atomic")
             .arg("--no-verify")
             .arg("--")
-            .arg(&config.remote_name);
+            .arg(config.push_remote());
         cmd.args(updates);
 
         run_command(&mut cmd)
             .await
             .context(String::from("git push failed"))?;
+        let journal = Journal::new(jj);
+        for mutation in journaled {
+            journal.record(mutation)?;
+        }
         jj.update()?;
 
         for ws in seen.iter_mut() {
@@ -392,50 +778,21 @@ fn prepare_revision_comment_inner<T>(
 where
     T: AsRef<crate::jj::Revision>,
 {
-    let mut lines = Vec::new();
-    // The node itself doesn't need indents.
-    // It is indented by the parent if necessary
-    let revision = tree.get().as_ref();
-    lines.push(format!(
-        "• [{}]({})",
-        revision.title,
-        if let Some(num) = revision.pull_request_number {
-            config.pull_request_url(num)
-        } else {
-            format!(
-                "Revision {:?} doesn't have a pull request yet. This is a bug.",
-                revision.id
-            )
-        }
-    ));
-
-    let children = tree.get_children();
-    match children.as_slice() {
-        [] => {}
-        [next] => {
-            lines.extend(prepare_revision_comment_inner(next, config));
-        }
-        // We have more than one child branch.
-        // We need to actually build an unicode-art tree
-        children => {
-            let mut child_lines = Vec::new();
-            for child in children {
-                let indent: String = std::iter::repeat(config.drawing.space.clone())
-                    .take(child.width() * 2 - 1)
-                    .collect();
-                let drawing = std::iter::once(config.drawing.fork.clone())
-                    .chain(std::iter::repeat(config.drawing.cont.clone()));
-                let new_lines = prepare_revision_comment_inner(child, config);
-                let old_lines =
-                    std::iter::zip(drawing, child_lines).map(|(i, l)| format!("{i}{indent}{l}",));
-                child_lines = old_lines.chain(new_lines.into_iter()).collect();
+    tree.draw(&config.drawing, &|value: &T| {
+        let revision = value.as_ref();
+        format!(
+            "• [{}]({})",
+            revision.title,
+            if let Some(num) = revision.pull_request_number {
+                config.pull_request_url(num)
+            } else {
+                format!(
+                    "Revision {:?} doesn't have a pull request yet. This is a bug.",
+                    revision.id
+                )
             }
-
-            lines.extend(child_lines);
-        }
-    }
-
-    return lines;
+        )
+    })
 }
 
 fn prepare_revision_comment<T>(
@@ -482,6 +839,12 @@ fn finalize_revision_comment(
     ));
 
     lines.extend_from_slice(prepared.as_slice());
+    if config.fork.is_some() {
+        lines.push(format!(
+            "\nThe series comes from a fork, so each PR is based on `{}` and also contains the changes of the PRs listed before it.",
+            config.master_ref
+        ));
+    }
     if let Some(number) = revision.pull_request_number {
         let pattern = format!("[{}]({})", revision.title, config.pull_request_url(number));
         let simple_pattern = format!("* {}", config.pull_request_url(number));
@@ -502,6 +865,140 @@ fn finalize_revision_comment(
     lines.join("\n")
 }
 
+/// Add the GitHub side of a push to the plan: PRs created or retargeted, descriptions and
+/// stack comments updated.
+fn plan_pull_requests<PR>(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    plan: &mut Plan,
+    actions: &[WorkSet<'_, BranchAction<PR>>],
+    draft: bool,
+    force: bool,
+) -> Result<()>
+where
+    PR: crate::github::GHPullRequest,
+{
+    for ws in actions.iter() {
+        let action = &ws.pull_request;
+        match action.old_pr {
+            Some(ref pr) => {
+                if action.base_branch != pr.base_branch_name() {
+                    plan.push(Action::RetargetPullRequest {
+                        number: pr.pr_number(),
+                        from: pr.base_branch_name().to_string(),
+                        to: action.base_branch.clone(),
+                    });
+                }
+                if let Some(draft) = requested_draft(&ws.revision)?
+                    && draft != pr.draft()
+                {
+                    plan.push(Action::SetDraft {
+                        number: pr.pr_number(),
+                        draft,
+                    });
+                }
+                let (title, summary) = crate::snapshot::remote(pr);
+                let changes = crate::snapshot::compare(&ws.revision.message, &title, &summary)?;
+                let title = outgoing(changes.title, force);
+                let summary = outgoing(changes.summary, force);
+                if title || summary {
+                    plan.push(Action::EditPullRequest {
+                        number: pr.pr_number(),
+                        title,
+                        summary,
+                    });
+                }
+                let wanted = wanted_labels(jj, config, &ws.revision)?;
+                let (added, removed) = label_changes(config, &ws.revision, pr, &wanted);
+                if !added.is_empty() || !removed.is_empty() {
+                    plan.push(Action::UpdateLabels {
+                        number: pr.pr_number(),
+                        added,
+                        removed,
+                    });
+                }
+                let (added, removed) = account_changes(
+                    ws.revision.message.get(&MessageSection::Reviewers),
+                    Some(
+                        ws.revision
+                            .message
+                            .get(&MessageSection::SyncedReviewers)
+                            .map_or("", |s| s.as_str()),
+                    ),
+                    pr.requested_reviewers(),
+                    pr.reviewed_by(),
+                );
+                if !added.is_empty() || !removed.is_empty() {
+                    plan.push(Action::UpdateReviewers {
+                        number: pr.pr_number(),
+                        added,
+                        removed,
+                    });
+                }
+                let (added, removed) = account_changes(
+                    ws.revision.message.get(&MessageSection::Assignees),
+                    None,
+                    pr.assignees(),
+                    &[],
+                );
+                if !added.is_empty() || !removed.is_empty() {
+                    plan.push(Action::UpdateAssignees {
+                        number: pr.pr_number(),
+                        added,
+                        removed,
+                    });
+                }
+            }
+            None => plan.push(Action::CreatePullRequest {
+                revision: ws.revision.id.to_string(),
+                title: ws
+                    .revision
+                    .message
+                    .get(&MessageSection::Title)
+                    .map_or("Missing Title", |s| s.as_str())
+                    .to_string(),
+                head: config.head_label(&action.head_branch),
+                base: action.base_branch.clone(),
+                draft: requested_draft(&ws.revision)?.unwrap_or(draft),
+            }),
+        }
+        if action.pushed || action.old_pr.is_none() {
+            plan.push(Action::UpdateDescription {
+                revision: ws.revision.id.to_string(),
+            });
+        }
+    }
+
+    let mut forest: crate::tree::Forest<&WorkSet<'_, BranchAction<PR>>> =
+        crate::tree::Forest::new();
+    for ws in actions.iter() {
+        let parent = ws.revision.parent_ids.first();
+        forest.insert_below(&|p| Some(&p.revision.id) == parent, ws);
+    }
+    for tree in forest.into_trees() {
+        let prepared = prepare_revision_comment(&tree, config);
+        let worksets: Vec<_> = tree.into_iter().collect();
+        // New PRs change the stack comment of every PR in the tree, since they add a new link.
+        let complete = worksets.iter().all(|ws| ws.pull_request.old_pr.is_some());
+        for ws in worksets {
+            let Some(ref pr) = ws.pull_request.old_pr else {
+                continue;
+            };
+            if !complete
+                || crate::github::pr_comment_outdated(
+                    pr,
+                    &finalize_revision_comment(&ws.revision, config, &prepared),
+                )
+            {
+                plan.push(Action::UpdateComment {
+                    number: pr.pr_number(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
 pub async fn push<GH, PR>(
     jj: &mut crate::jj::Jujutsu,
     mut gh: GH,
@@ -546,13 +1043,19 @@ where
     let revset = heads
         .ancestors()
         .without(&RevSet::immutable().or(&RevSet::description("exact:\"\"")));
+    let mut plan = opts.dry_run.map(|_| Plan::new());
     if opts.fix.unwrap_or(config.push.autofix) {
         setup.set_message("Running jj fix");
-        jj.fix(&revset)?;
+        match plan.as_mut() {
+            Some(plan) => plan.push(Action::Fix {
+                revisions: revset.as_ref().to_string(),
+            }),
+            None => jj.fix(&revset)?,
+        }
     }
 
     setup.set_message("Reading revisions");
-    let revisions = jj.read_revision_range(&revset)?;
+    let revisions = jj.read_revision_range(config, &revset)?;
 
     setup.set_message("Checking revisions for bad states");
     let blockers = jj.revset_to_change_ids(
@@ -568,6 +1071,9 @@ where
             blockers
         )));
     }
+    for revision in revisions.iter() {
+        requested_draft(revision)?;
+    }
 
     // At this point it's guaranteed that our commits are single parent and the chain goes up to trunk()
     // We need the trunk's commit's OID. The first pull request (made against upstream trunk) needs it to start the chain.
@@ -644,8 +1150,16 @@ where
         });
 
     setup.set_message("Pushing revisions");
-    let mut actions = do_push(config, jj, &opts, work, &trunk).await?;
+    let mut actions = do_push(config, jj, &opts, work, &trunk, plan.as_mut()).await?;
     setup.set_message("Setting up PRs");
+    if let Some(mut plan) = plan {
+        plan_pull_requests(jj, config, &mut plan, &actions, draft, opts.force)?;
+        setup.finish_and_clear();
+        return plan.print(config, opts.dry_run.unwrap_or_default());
+    }
+    // Failing to request reviews shouldn't lose the link to the PR that was just created, so
+    // these are only reported at the end.
+    let mut failures = Error::empty();
     for workset in actions.iter_mut().into_iter() {
         // We don't know what to do with these yet...
         if let Some(ref pr) = workset.pull_request.old_pr {
@@ -654,6 +1168,117 @@ where
                 gh.rebase_pr(pr.pr_number(), &workset.pull_request.base_branch)
                     .await?;
             }
+            if let Some(draft) = requested_draft(&workset.revision)?
+                && draft != pr.draft()
+            {
+                workset.progress_bar.set_message(match draft {
+                    true => "Converting to draft",
+                    false => "Marking ready for review",
+                });
+                gh.set_draft(pr, draft).await?;
+                workset.work_done.push(WorkEvent::Draft(config, draft));
+            }
+            workset
+                .progress_bar
+                .set_message("Updating title and summary");
+            let (edited, kept, conflicts) =
+                sync_text(&mut gh, &mut workset.revision, pr, opts.force).await?;
+            if edited {
+                workset.work_done.push(WorkEvent::Edited(config));
+            }
+            if kept {
+                workset.work_done.push(WorkEvent::KeptRemote(config));
+            }
+            for field in conflicts {
+                failures.push(format!(
+                    "The {} of {} changed here and on GitHub. Make them match, or overwrite the PR with `--force`",
+                    field,
+                    config.pull_request_url(pr.pr_number())
+                ));
+            }
+            let wanted = wanted_labels(jj, config, &workset.revision)?;
+            let (added, removed) = label_changes(config, &workset.revision, pr, &wanted);
+            if !added.is_empty() || !removed.is_empty() {
+                workset.progress_bar.set_message("Updating labels");
+                if !added.is_empty() {
+                    gh.add_labels(pr, added).await?;
+                }
+                if !removed.is_empty() {
+                    gh.remove_labels(pr, removed).await?;
+                }
+                workset.work_done.push(WorkEvent::Labeled(config));
+            }
+            set_synced(
+                &mut workset.revision,
+                MessageSection::Labels,
+                MessageSection::SyncedLabels,
+            );
+            let (added, removed) = account_changes(
+                workset.revision.message.get(&MessageSection::Reviewers),
+                Some(
+                    workset
+                        .revision
+                        .message
+                        .get(&MessageSection::SyncedReviewers)
+                        .map_or("", |s| s.as_str()),
+                ),
+                pr.requested_reviewers(),
+                pr.reviewed_by(),
+            );
+            let mut result = Ok(());
+            if !added.is_empty() || !removed.is_empty() {
+                workset.progress_bar.set_message("Updating reviewers");
+                if !added.is_empty() {
+                    result = gh.add_reviewers(pr, added).await;
+                }
+                if result.is_ok() && !removed.is_empty() {
+                    result = gh.remove_reviewers(pr, removed).await;
+                }
+                if result.is_ok() {
+                    workset.work_done.push(WorkEvent::ReviewersUpdated(config));
+                }
+            }
+            match result {
+                // Until the changes went through, removed reviewers are still to be withdrawn.
+                Ok(()) => set_synced(
+                    &mut workset.revision,
+                    MessageSection::Reviewers,
+                    MessageSection::SyncedReviewers,
+                ),
+                Err(mut error) => {
+                    error.push(format!(
+                        "Update reviewers of {}",
+                        config.pull_request_url(pr.pr_number())
+                    ));
+                    failures.extend(error);
+                }
+            }
+            let (added, removed) = account_changes(
+                workset.revision.message.get(&MessageSection::Assignees),
+                None,
+                pr.assignees(),
+                &[],
+            );
+            if !added.is_empty() || !removed.is_empty() {
+                workset.progress_bar.set_message("Updating assignees");
+                let mut result = Ok(());
+                if !added.is_empty() {
+                    result = gh.add_assignees(pr, added).await;
+                }
+                if result.is_ok() && !removed.is_empty() {
+                    result = gh.remove_assignees(pr, removed).await;
+                }
+                match result {
+                    Ok(()) => workset.work_done.push(WorkEvent::AssigneesUpdated(config)),
+                    Err(mut error) => {
+                        error.push(format!(
+                            "Update assignees of {}",
+                            config.pull_request_url(pr.pr_number())
+                        ));
+                        failures.extend(error);
+                    }
+                }
+            }
             workset
                 .progress_bar
                 .set_message("Updating revision description");
@@ -669,39 +1294,96 @@ where
             .message
             .get(&MessageSection::Title)
             .map_or("Missing Title", |s| s.as_str());
-        let body = workset
-            .revision
-            .message
-            .get(&MessageSection::Summary)
-            .map_or("", |s| s.as_str());
+        let body = pull_request_body(jj, config, &workset.revision)?;
         let pr = gh
             .new_pull_request(
                 title,
                 body,
                 &workset.pull_request.base_branch,
-                &workset.pull_request.head_branch,
-                draft,
+                config.head_label(&workset.pull_request.head_branch),
+                requested_draft(&workset.revision)?.unwrap_or(draft),
             )
             .await?;
 
         workset.work_done.push(WorkEvent::PRCreated(config));
+        let (title, summary) = crate::snapshot::remote(&pr);
+        crate::snapshot::update(&mut workset.revision.message, &title, &summary)?;
         workset.progress_bar.set_prefix(format!(
             "{} ({})",
             workset.revision.title,
             config.pull_request_url(pr.pr_number())
         ));
+        let owners = match config.push.codeowners {
+            CodeOwnersMode::Off => Vec::new(),
+            CodeOwnersMode::Suggest | CodeOwnersMode::Auto => {
+                code_owners(jj, config, &workset.revision)?
+            }
+        };
+        match config.push.codeowners {
+            _ if owners.is_empty() => {}
+            CodeOwnersMode::Auto => {
+                let reviewers = workset
+                    .revision
+                    .message
+                    .get(&MessageSection::Reviewers)
+                    .into_iter()
+                    .cloned()
+                    .chain(owners)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                workset
+                    .revision
+                    .message
+                    .insert(MessageSection::Reviewers, reviewers);
+            }
+            CodeOwnersMode::Suggest | CodeOwnersMode::Off => workset
+                .work_done
+                .push(WorkEvent::ReviewersSuggested(config, owners.join(", "))),
+        }
         if let Some(reviewers) = workset.revision.message.get(&MessageSection::Reviewers) {
             workset.progress_bar.set_message("Requesting reviewers");
-            gh.add_reviewers(&pr, reviewers.split(",").map(|s| s.trim()))
-                .await?;
-            workset.work_done.push(WorkEvent::ReviewRequested(config));
+            match gh.add_reviewers(&pr, reviewer_names(reviewers)).await {
+                Ok(()) => {
+                    workset.work_done.push(WorkEvent::ReviewRequested(config));
+                    set_synced(
+                        &mut workset.revision,
+                        MessageSection::Reviewers,
+                        MessageSection::SyncedReviewers,
+                    );
+                }
+                Err(mut error) => {
+                    error.push(format!(
+                        "Request reviews on {}",
+                        config.pull_request_url(pr.pr_number())
+                    ));
+                    failures.extend(error);
+                }
+            }
         }
         if let Some(assignees) = workset.revision.message.get(&MessageSection::Assignees) {
             workset.progress_bar.set_message("Requesting assignees");
-            gh.add_assignees(&pr, assignees.split(",").map(|s| s.trim()))
-                .await?;
-            workset.work_done.push(WorkEvent::Assigned(config));
+            match gh.add_assignees(&pr, reviewer_names(assignees)).await {
+                Ok(()) => workset.work_done.push(WorkEvent::Assigned(config)),
+                Err(mut error) => {
+                    error.push(format!(
+                        "Assign {}",
+                        config.pull_request_url(pr.pr_number())
+                    ));
+                    failures.extend(error);
+                }
+            }
+        }
+        let labels = wanted_labels(jj, config, &workset.revision)?;
+        if !labels.is_empty() {
+            workset.progress_bar.set_message("Adding labels");
+            gh.add_labels(&pr, labels.iter()).await?;
+            workset.work_done.push(WorkEvent::Labeled(config));
         }
+        set_synced(
+            &mut workset.revision,
+            MessageSection::Labels,
+            MessageSection::SyncedLabels,
+        );
 
         let pull_request_url = config.pull_request_url(pr.pr_number());
         workset
@@ -762,7 +1444,10 @@ where
     }
     setup.finish_and_clear();
 
-    Ok(())
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(failures),
+    }
 }
 
 #[cfg(test)]
@@ -824,6 +1509,7 @@ pub mod tests {
 
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
         super::push(
             &mut jj,
@@ -858,6 +1544,7 @@ pub mod tests {
         let _ = create_jujutsu_commit(&mut jj, "Test commit", "file 1");
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
         super::push(
             &mut jj,
@@ -902,26 +1589,54 @@ pub mod tests {
     }
 
     #[tokio::test]
-    async fn test_stack_on_existing() {
+    async fn dry_run_changes_nothing() {
         let (_temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
-        let _ = create_jujutsu_commit(&mut jj, "Test commit", "file 1");
-        let mut gh = crate::github::fakes::GitHub {
-            pull_requests: std::collections::BTreeMap::new(),
-        };
+        let change = create_jujutsu_commit(&mut jj, "Test commit", "file 1");
+
+        let mut gh = crate::github::fakes::GitHub::new();
         super::push(
             &mut jj,
             &mut gh,
             &testing::config::basic(),
-            super::PushOptions::default().with_message(Some("message")),
+            super::PushOptions::default().with_dry_run(crate::plan::PlanFormat::Json),
         )
         .await
-        .expect("stacked shouldn't fail");
+        .expect("dry run shouldn't fail");
 
-        let pr_branch = bare
-            .find_branch("spr/test/test-commit", git2::BranchType::Local)
-            .expect("Expected to find branch on bare upstream");
-        let initial_pr_oid = pr_branch
-            .get()
+        assert!(gh.pull_requests.is_empty(), "Dry run created a PR");
+        assert!(
+            bare.find_branch("spr/test/test-commit", git2::BranchType::Local)
+                .is_err(),
+            "Dry run pushed a branch"
+        );
+        let revision = jj
+            .read_revision(&testing::config::basic(), change)
+            .expect("Revision should still exist");
+        assert!(revision.pull_request_number.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stack_on_existing() {
+        let (_temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
+        let _ = create_jujutsu_commit(&mut jj, "Test commit", "file 1");
+        let mut gh = crate::github::fakes::GitHub {
+            pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
+        };
+        super::push(
+            &mut jj,
+            &mut gh,
+            &testing::config::basic(),
+            super::PushOptions::default().with_message(Some("message")),
+        )
+        .await
+        .expect("stacked shouldn't fail");
+
+        let pr_branch = bare
+            .find_branch("spr/test/test-commit", git2::BranchType::Local)
+            .expect("Expected to find branch on bare upstream");
+        let initial_pr_oid = pr_branch
+            .get()
             .target()
             .expect("Failed to get oid from pr branch");
 
@@ -971,6 +1686,7 @@ pub mod tests {
         let _ = create_jujutsu_commit(&mut jj, "Test other commit", "file other");
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
         super::push(
             &mut jj,
@@ -1016,6 +1732,7 @@ pub mod tests {
         let _ = create_jujutsu_commit(&mut jj, "Test commit", "file 1");
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
         super::push(
             &mut jj,
@@ -1080,6 +1797,7 @@ pub mod tests {
         let rev = create_jujutsu_commit(&mut jj, "Test commit", "file 1");
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
         super::push(
             &mut jj,
@@ -1159,6 +1877,7 @@ pub mod tests {
 
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
         super::push(
             &mut jj,
@@ -1236,6 +1955,7 @@ pub mod tests {
         let _ = create_jujutsu_commit(&mut jj, "Test commit", "file 1");
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
         super::push(
             &mut jj,
@@ -1288,6 +2008,7 @@ pub mod tests {
 
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
         super::push(
             &mut jj,
@@ -1319,6 +2040,7 @@ pub mod tests {
             let _ = super::create_jujutsu_commit(&mut jj, "Test commit", "file 1");
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1367,6 +2089,7 @@ pub mod tests {
             );
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1406,6 +2129,7 @@ pub mod tests {
             let _ = super::create_jujutsu_commit(&mut jj, "Test commit", "file 1");
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1479,6 +2203,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1496,10 +2221,10 @@ pub mod tests {
             .expect("stacked shouldn't fail");
 
             let left_revision = jj
-                .read_revision(left_id)
+                .read_revision(&testing::config::basic(), left_id)
                 .expect("Couldn't read left revision");
             let right_revision = jj
-                .read_revision(right_id)
+                .read_revision(&testing::config::basic(), right_id)
                 .expect("Couldn't read right revision");
 
             assert_eq!(
@@ -1563,6 +2288,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1580,10 +2306,10 @@ pub mod tests {
             .expect("stacked shouldn't fail");
 
             let left_revision = jj
-                .read_revision(left_id.clone())
+                .read_revision(&testing::config::basic(), left_id.clone())
                 .expect("Couldn't read left revision");
             let right_revision = jj
-                .read_revision(right_id.clone())
+                .read_revision(&testing::config::basic(), right_id.clone())
                 .expect("Couldn't read right revision");
 
             assert_eq!(
@@ -1684,6 +2410,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1713,6 +2440,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1755,6 +2483,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1780,6 +2509,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1862,6 +2592,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1891,7 +2622,7 @@ pub mod tests {
                 .pull_requests
                 .get(&1)
                 .expect("Push must have created PR")
-                ._reviewers
+                .reviewers
                 .iter()
                 .map(|s| s.as_str())
                 .collect();
@@ -1918,6 +2649,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -1947,7 +2679,7 @@ pub mod tests {
                 .pull_requests
                 .get(&1)
                 .expect("Push must have created PR")
-                ._assignees
+                .assignees
                 .iter()
                 .map(|s| s.as_str())
                 .collect();
@@ -1957,6 +2689,420 @@ pub mod tests {
                 "Assignees didn't get updated"
             )
         }
+
+        #[tokio::test]
+        async fn team_reviewers_are_set() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let _ = super::create_jujutsu_commit(
+                &mut jj,
+                "Test commit\n\nReviewers: rev1, myorg/rust, @myorg/docs",
+                "file 1",
+            );
+
+            let mut gh = crate::github::fakes::GitHub {
+                teams: vec![String::from("myorg/rust"), String::from("myorg/docs")],
+                ..Default::default()
+            };
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &testing::config::basic(),
+                super::super::PushOptions::default(),
+            )
+            .await
+            .expect("Push shouldn't fail");
+
+            assert_eq!(
+                gh.pull_requests[&1].reviewers,
+                ["rev1", "myorg/rust", "myorg/docs"]
+            );
+        }
+
+        #[tokio::test]
+        async fn unknown_reviewers_are_reported() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let change = super::create_jujutsu_commit(
+                &mut jj,
+                "Test commit\n\nReviewers: rev1, ghost, myorg/nobody\nAssignees: ghost",
+                "file 1",
+            );
+
+            let mut gh = crate::github::fakes::GitHub {
+                users: Some(vec![String::from("rev1")]),
+                ..Default::default()
+            };
+            let error = super::super::push(
+                &mut jj,
+                &mut gh,
+                &testing::config::basic(),
+                super::super::PushOptions::default(),
+            )
+            .await
+            .expect_err("Push should report the unknown names");
+
+            assert!(
+                error
+                    .messages()
+                    .iter()
+                    .any(|m| m.contains("ghost, myorg/nobody")),
+                "Unknown reviewers should be named: {:?}",
+                error.messages()
+            );
+            // The PR exists anyway, so the next push mustn't create another one.
+            let revision = jj
+                .read_revision(&testing::config::basic(), change)
+                .expect("Revision should exist");
+            assert_eq!(revision.pull_request_number, Some(1));
+        }
+
+        #[tokio::test]
+        async fn reviewers_and_assignees_are_synced() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let config = testing::config::basic();
+            let change = super::create_jujutsu_commit(
+                &mut jj,
+                "Test commit\n\nReviewers: rev1, rev2\nAssignees: ass1",
+                "file 1",
+            );
+
+            let mut gh = crate::github::fakes::GitHub::new();
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default(),
+            )
+            .await
+            .expect("Push shouldn't fail");
+            // GitHub drops the request once the review is in.
+            let pr = gh.pull_requests.get_mut(&1).expect("PR should exist");
+            pr.reviewers.retain(|r| r != "rev2");
+            pr.reviewed_by = vec![String::from("rev2")];
+            // Requested by GitHub for CODEOWNERS, not by spr.
+            pr.reviewers.push(String::from("owner"));
+
+            let mut revision = jj
+                .read_revision(&config, change.clone())
+                .expect("Revision should exist");
+            revision.message.insert(
+                crate::message::MessageSection::Reviewers,
+                String::from("REV3, rev2"),
+            );
+            revision.message.insert(
+                crate::message::MessageSection::Assignees,
+                String::from("ass2"),
+            );
+            jj.update_revision_message(&revision)
+                .expect("Failed to update the description");
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default(),
+            )
+            .await
+            .expect("Push shouldn't fail");
+
+            assert_eq!(gh.pull_requests[&1].reviewers, ["owner", "REV3"]);
+            assert_eq!(gh.pull_requests[&1].assignees, ["ass2"]);
+            let revision = jj
+                .read_revision(&config, change)
+                .expect("Revision should exist");
+            assert_eq!(
+                revision
+                    .message
+                    .get(&crate::message::MessageSection::SyncedReviewers)
+                    .map(|s| s.as_str()),
+                Some("REV3, rev2")
+            );
+        }
+
+        fn push_with_codeowners(
+            jj: &mut crate::jj::Jujutsu,
+            mode: crate::config::push::CodeOwnersMode,
+        ) -> (crate::config::Config, crate::jj::ChangeId) {
+            let workdir = jj
+                .git_repo
+                .workdir()
+                .expect("Failed to extract workdir from JJ handle")
+                .to_path_buf();
+            std::fs::create_dir(workdir.join(".github")).expect("Failed to create .github");
+            std::fs::write(
+                workdir.join(".github/CODEOWNERS"),
+                "* @everyone\n*.rs @alice @myorg/rust\n.github/ @admin\n",
+            )
+            .expect("Failed to write CODEOWNERS");
+            let change = super::create_jujutsu_commit_in_file(
+                jj,
+                "Test commit\n\nReviewers: bob",
+                "fn main() {}",
+                "main.rs",
+            );
+
+            let mut config = testing::config::basic();
+            config.push.codeowners = mode;
+            (config, change)
+        }
+
+        #[tokio::test]
+        async fn code_owners_are_added() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let (config, change) =
+                push_with_codeowners(&mut jj, crate::config::push::CodeOwnersMode::Auto);
+
+            let mut gh = crate::github::fakes::GitHub::new();
+            gh.teams.push(String::from("myorg/rust"));
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push shouldn't fail");
+
+            assert_eq!(
+                gh.pull_requests[&1].reviewers,
+                ["bob", "admin", "alice", "myorg/rust"]
+            );
+            let revision = jj
+                .read_revision(&config, change)
+                .expect("Revision should exist");
+            assert_eq!(
+                revision
+                    .message
+                    .get(&crate::message::MessageSection::Reviewers)
+                    .map(|s| s.as_str()),
+                Some("bob, admin, alice, myorg/rust")
+            );
+
+            // Nobody can review their own PR, so the user isn't added.
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let (config, _) =
+                push_with_codeowners(&mut jj, crate::config::push::CodeOwnersMode::Auto);
+            let config = config.with_user(Some(String::from("Alice")));
+            let mut gh = crate::github::fakes::GitHub::new();
+            gh.teams.push(String::from("myorg/rust"));
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push shouldn't fail");
+
+            assert_eq!(
+                gh.pull_requests[&1].reviewers,
+                ["bob", "admin", "myorg/rust"]
+            );
+        }
+
+        #[tokio::test]
+        async fn code_owners_are_only_suggested() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let (config, change) =
+                push_with_codeowners(&mut jj, crate::config::push::CodeOwnersMode::Suggest);
+
+            let mut gh = crate::github::fakes::GitHub::new();
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push shouldn't fail");
+
+            assert_eq!(gh.pull_requests[&1].reviewers, ["bob"]);
+            let revision = jj
+                .read_revision(&config, change)
+                .expect("Revision should exist");
+            assert_eq!(
+                revision
+                    .message
+                    .get(&crate::message::MessageSection::Reviewers)
+                    .map(|s| s.as_str()),
+                Some("bob")
+            );
+        }
+    }
+
+    mod templates {
+        use crate::testing;
+
+        fn write_templates(jj: &crate::jj::Jujutsu) {
+            let workdir = jj
+                .git_repo
+                .workdir()
+                .expect("Failed to extract workdir from JJ handle")
+                .to_path_buf();
+            std::fs::create_dir_all(workdir.join(".github/PULL_REQUEST_TEMPLATE"))
+                .expect("Failed to create template directory");
+            std::fs::write(
+                workdir.join(".github/pull_request_template.md"),
+                "## Summary\n<!-- summary -->\n\n## Checklist\n- [ ] Tests\n",
+            )
+            .expect("Failed to write template");
+            std::fs::write(
+                workdir.join(".github/PULL_REQUEST_TEMPLATE/bug.md"),
+                "## Reproduction\n",
+            )
+            .expect("Failed to write template");
+        }
+
+        #[tokio::test]
+        async fn default_template_is_filled() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let config = testing::config::basic();
+            write_templates(&jj);
+            let _ = super::create_jujutsu_commit(&mut jj, "Test commit\n\nWhat it does", "file");
+
+            let mut gh = crate::github::fakes::GitHub::new();
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push shouldn't fail");
+
+            let body = &gh.pull_requests[&1].body;
+            assert!(body.starts_with("## Summary\n<!-- spr:summary -->\nWhat it does\n"));
+            assert!(body.ends_with("## Checklist\n- [ ] Tests\n"));
+            assert_eq!(crate::template::strip(body), "What it does");
+        }
+
+        #[tokio::test]
+        async fn named_template_is_used() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let config = testing::config::basic();
+            write_templates(&jj);
+            let _ = super::create_jujutsu_commit(
+                &mut jj,
+                "Test commit\n\nWhat it does\n\nTemplate: bug",
+                "file",
+            );
+
+            let mut gh = crate::github::fakes::GitHub::new();
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push shouldn't fail");
+
+            assert_eq!(
+                gh.pull_requests[&1].body,
+                "<!-- spr:summary -->\nWhat it does\n<!-- spr:summary-end -->\n\n## Reproduction\n"
+            );
+        }
+    }
+
+    mod labels {
+        use crate::{message::MessageSection, testing};
+
+        #[tokio::test]
+        async fn labels_are_added_and_derived() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let mut config = testing::config::basic();
+            config.push.labels = vec![crate::config::push::LabelRule {
+                label: String::from("bug"),
+                commit_type: Some(String::from("fix")),
+                paths: Vec::new(),
+            }];
+            let change = super::create_jujutsu_commit(
+                &mut jj,
+                "fix: Test commit\n\nLabels: area/parser",
+                "file",
+            );
+
+            let mut gh = crate::github::fakes::GitHub::new();
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push shouldn't fail");
+
+            assert_eq!(gh.pull_requests[&1].labels, ["area/parser", "bug"]);
+            let revision = jj
+                .read_revision(&config, change)
+                .expect("Revision should exist");
+            assert_eq!(
+                revision
+                    .message
+                    .get(&MessageSection::Labels)
+                    .map(|s| s.as_str()),
+                Some("area/parser"),
+                "Derived labels shouldn't be written into the description"
+            );
+            assert_eq!(
+                revision
+                    .message
+                    .get(&MessageSection::SyncedLabels)
+                    .map(|s| s.as_str()),
+                Some("area/parser")
+            );
+        }
+
+        #[tokio::test]
+        async fn labels_are_reconciled() {
+            let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+            let mut config = testing::config::basic();
+            config.push.labels = vec![crate::config::push::LabelRule {
+                label: String::from("bug"),
+                commit_type: Some(String::from("fix")),
+                paths: Vec::new(),
+            }];
+            let change =
+                super::create_jujutsu_commit(&mut jj, "fix: Test commit\n\nLabels: stale", "file");
+
+            let mut gh = crate::github::fakes::GitHub::new();
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push shouldn't fail");
+            assert_eq!(gh.pull_requests[&1].labels, ["stale", "bug"]);
+            gh.pull_requests
+                .get_mut(&1)
+                .expect("PR should exist")
+                .labels
+                .push(String::from("triage"));
+
+            let mut revision = jj
+                .read_revision(&config, change)
+                .expect("Revision should exist");
+            revision
+                .message
+                .insert(MessageSection::Title, String::from("Test commit"));
+            revision.message.remove(&MessageSection::Labels);
+            jj.update_revision_message(&revision)
+                .expect("Failed to update the description");
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push shouldn't fail");
+
+            assert_eq!(
+                gh.pull_requests[&1].labels,
+                ["triage"],
+                "Only labels added on GitHub should stay"
+            );
+        }
     }
 
     mod tree_formatting {
@@ -2126,6 +3272,71 @@ pub mod tests {
         }
     }
 
+    mod forks {
+        use crate::testing;
+
+        #[tokio::test]
+        async fn stack_is_pushed_to_fork() {
+            let (temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
+            let fork_path = temp_dir.path().join("fork");
+            let fork = git2::Repository::init_bare(&fork_path).expect("Failed to init fork repo");
+            jj.git_remote_add(
+                "fork",
+                format!(
+                    "file://{}",
+                    fork_path.to_str().expect("Path should be utf-8")
+                ),
+            )
+            .expect("Failed to add fork remote");
+            let config = testing::config::basic().with_fork(Some(crate::config::Fork {
+                remote: String::from("fork"),
+                owner: String::from("contributor"),
+                repo: String::from("test_repo"),
+            }));
+
+            let _ = super::create_jujutsu_commit(&mut jj, "Test commit", "file 1");
+            let _ = super::create_jujutsu_commit(&mut jj, "Test other commit", "file other");
+            let mut gh = crate::github::fakes::GitHub::new();
+            super::super::push(
+                &mut jj,
+                &mut gh,
+                &config,
+                super::super::PushOptions::default().with_message(Some("message")),
+            )
+            .await
+            .expect("push to fork shouldn't fail");
+
+            for branch in ["spr/test/test-commit", "spr/test/test-other-commit"] {
+                fork.find_branch(branch, git2::BranchType::Local)
+                    .expect("Expected to find branch in the fork");
+                assert!(
+                    bare.find_branch(branch, git2::BranchType::Local).is_err(),
+                    "Branch {} was pushed upstream",
+                    branch
+                );
+            }
+
+            let child = gh
+                .pull_requests
+                .get(&2)
+                .expect("Push must have created PR 2");
+            assert_eq!(child.head, "spr/test/test-other-commit");
+            assert_eq!(child.base, "main", "PRs from a fork can't be stacked");
+            assert_eq!(
+                gh.forks.get(&2).map(String::as_str),
+                Some("contributor"),
+                "PR wasn't opened from the fork"
+            );
+            assert!(
+                child
+                    .comments
+                    .iter()
+                    .any(|comment| comment.content.contains("comes from a fork")),
+                "Overview comment doesn't explain the series"
+            );
+        }
+    }
+
     mod overview_comments {
         use crate::testing;
 
@@ -2141,6 +3352,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -2187,6 +3399,7 @@ pub mod tests {
 
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::new(),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -2239,7 +3452,7 @@ pub mod tests {
 
             let _ = super::create_jujutsu_commit(
                 &mut jj,
-                "Test commit\n\nPull Request: https://github.com/Ongy/jj-spr/pull/1",
+                "Test commit\n\nPull Request: https://github.com/test_owner/test_repo/pull/1",
                 "file 1",
             );
 
@@ -2257,6 +3470,7 @@ pub mod tests {
             });
             let mut gh = crate::github::fakes::GitHub {
                 pull_requests: std::collections::BTreeMap::from([(1, pr)]),
+                ..Default::default()
             };
             super::super::push(
                 &mut jj,
@@ -2318,6 +3532,7 @@ pub mod tests {
 
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
 
         // Push it (creates a PR)
@@ -2431,8 +3646,8 @@ pub mod tests {
         assert_eq!(pr2.title, "Parent commit");
         // Parent PR should be based on main
         assert_eq!(pr2.base, "main", "Parent PR base should be main");
-        // PR 1 currently keeps its original title on GitHub because jj-spr doesn't update titles/bodies for existing PRs yet
-        assert_eq!(pr1.title, "Original commit");
+        // The description was replaced wholesale, without a snapshot, so the local title wins.
+        assert_eq!(pr1.title, "Child commit");
 
         // Assert that pr1 (child) now has pr2's branch as its base
         assert_eq!(pr1.base, pr2.head, "Child PR base should be parent PR head");
@@ -2486,6 +3701,7 @@ pub mod tests {
 
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
 
         super::push(
@@ -2512,6 +3728,7 @@ pub mod tests {
 
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
 
         super::push(
@@ -2538,6 +3755,7 @@ pub mod tests {
 
         let mut gh = crate::github::fakes::GitHub {
             pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
         };
 
         super::push(
@@ -2553,4 +3771,139 @@ pub mod tests {
         let pr = gh.pull_requests.get(&1).expect("PR 1 should exist");
         assert!(!pr.draft, "PR should NOT be a draft");
     }
+
+    #[tokio::test]
+    async fn test_push_draft_section() {
+        let (_temp_dir, mut jj, _bare) = testing::setup::repo_with_origin();
+        let change = create_jujutsu_commit(&mut jj, "Test commit\n\nDraft: yes", "file 1");
+        let config = testing::config::basic();
+
+        let mut gh = crate::github::fakes::GitHub {
+            pull_requests: std::collections::BTreeMap::new(),
+            ..Default::default()
+        };
+        super::push(&mut jj, &mut gh, &config, super::PushOptions::default())
+            .await
+            .expect("Push failed");
+        assert!(gh.pull_requests[&1].draft, "PR should be a draft");
+
+        let mut revision = jj
+            .read_revision(&config, change)
+            .expect("Revision should exist");
+        revision
+            .message
+            .insert(crate::message::MessageSection::Draft, String::from("no"));
+        jj.update_revision_message(&revision)
+            .expect("Failed to update description");
+        super::push(&mut jj, &mut gh, &config, super::PushOptions::default())
+            .await
+            .expect("Push failed");
+        assert!(!gh.pull_requests[&1].draft, "PR should be ready for review");
+    }
+
+    #[tokio::test]
+    async fn test_push_syncs_title_and_summary() {
+        let (_temp_dir, mut jj, _bare) = testing::setup::repo_with_origin();
+        let change = create_jujutsu_commit(&mut jj, "Test commit\n\nSummary", "file 1");
+        let config = testing::config::basic();
+        let edit = |jj: &mut crate::jj::Jujutsu, section, text: &str| {
+            let mut revision = jj
+                .read_revision(&config, change.clone())
+                .expect("Revision should exist");
+            revision.message.insert(section, text.to_string());
+            jj.update_revision_message(&revision)
+                .expect("Failed to update description");
+        };
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        super::push(&mut jj, &mut gh, &config, super::PushOptions::default())
+            .await
+            .expect("Push failed");
+
+        // A new title goes out, while the summary edited on GitHub stays.
+        edit(&mut jj, crate::message::MessageSection::Title, "New title");
+        gh.pull_requests.get_mut(&1).expect("PR should exist").body = String::from("Edited");
+        super::push(&mut jj, &mut gh, &config, super::PushOptions::default())
+            .await
+            .expect("Push failed");
+        assert_eq!(gh.pull_requests[&1].title, "New title");
+        assert_eq!(gh.pull_requests[&1].body, "Edited");
+
+        edit(&mut jj, crate::message::MessageSection::Summary, "Local");
+        let error = super::push(&mut jj, &mut gh, &config, super::PushOptions::default())
+            .await
+            .expect_err("Push should report the conflict");
+        assert!(error.messages()[0].starts_with("The summary of"));
+        assert_eq!(gh.pull_requests[&1].body, "Edited");
+
+        super::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::PushOptions::default().with_force(true),
+        )
+        .await
+        .expect("Push failed");
+        assert_eq!(gh.pull_requests[&1].body, "Local");
+    }
+
+    #[tokio::test]
+    async fn test_push_keeps_text_without_synced_section() {
+        let (_temp_dir, mut jj, _bare) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let change = create_jujutsu_commit(
+            &mut jj,
+            &format!(
+                "Local title\n\nLocal summary\n\nPull Request: {}",
+                config.pull_request_url(1)
+            ),
+            "file 1",
+        );
+        let mut gh = crate::github::fakes::GitHub {
+            pull_requests: std::collections::BTreeMap::from([(
+                1,
+                crate::github::fakes::PullRequest::new(
+                    "main",
+                    "spr/test/local-title",
+                    1,
+                    "Title on GitHub",
+                    "Body without markers",
+                ),
+            )]),
+            ..Default::default()
+        };
+
+        super::push(&mut jj, &mut gh, &config, super::PushOptions::default())
+            .await
+            .expect("Push failed");
+        assert_eq!(gh.pull_requests[&1].title, "Title on GitHub");
+        assert_eq!(gh.pull_requests[&1].body, "Body without markers");
+
+        // `fetch` agrees and takes the PR's copy.
+        crate::commands::fetch::fetch(
+            crate::commands::fetch::FetchOptions::default(),
+            &mut jj,
+            &mut gh,
+            &config,
+        )
+        .await
+        .expect("Fetch failed");
+        let revision = jj
+            .read_revision(&config, change)
+            .expect("Revision should exist");
+        assert_eq!(
+            revision
+                .message
+                .get(&crate::message::MessageSection::Title)
+                .map(|s| s.as_str()),
+            Some("Title on GitHub")
+        );
+        assert_eq!(
+            revision
+                .message
+                .get(&crate::message::MessageSection::Summary)
+                .map(|s| s.as_str()),
+            Some("Body without markers")
+        );
+    }
 }
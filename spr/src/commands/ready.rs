@@ -0,0 +1,148 @@
+use std::iter::zip;
+
+use crate::{error::Result, github::GHPullRequest, jj::RevSet, message::MessageSection};
+
+#[derive(Debug, clap::Parser, Default)]
+pub struct ReadyOptions {
+    /// Revisions whose PRs should be marked ready for review. Defaults to `@`.
+    #[clap(long, short = 'r')]
+    revset: Option<String>,
+}
+
+#[cfg(test)]
+impl ReadyOptions {
+    pub fn with_revset<S>(mut self, revset: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.revset = revset.map(|s| s.into());
+        self
+    }
+}
+
+pub async fn ready<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    opts: ReadyOptions,
+) -> Result<()>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let selected = opts
+        .revset
+        .as_ref()
+        .map(RevSet::from_arg)
+        .unwrap_or(RevSet::current())
+        .and(&RevSet::mutable())
+        .and(&RevSet::description("glob:\"*Pull Request:*\""));
+    let revisions = jj.read_revision_range(config, &selected)?;
+    if revisions.is_empty() {
+        crate::output::output(
+            &config.icons.wave,
+            "Nothing to be done. Either the revset was empty or none of the revisions have a PR attached.",
+        )?;
+        return Ok(());
+    }
+
+    let pull_requests = gh
+        .pull_requests(revisions.iter().map(|r| r.pull_request_number))
+        .await?;
+    for (mut revision, pr) in zip(revisions, pull_requests) {
+        let Some(pr) = pr else {
+            crate::output::output(
+                &config.icons.error,
+                format!("Couldn't find the PR for {:?} on GitHub", revision.id),
+            )?;
+            continue;
+        };
+        let url = config.pull_request_url(pr.pr_number());
+        if pr.closed() {
+            crate::output::output(&config.icons.info, format!("{} is closed", url))?;
+            continue;
+        }
+
+        if pr.draft() {
+            crate::output::output(
+                &config.icons.eyes,
+                format!("Marking {} ready for review", url),
+            )?;
+            gh.set_draft(&pr, false).await?;
+        } else {
+            crate::output::output(
+                &config.icons.info,
+                format!("{} is already ready for review", url),
+            )?;
+        }
+
+        // Otherwise the next push would turn the PR back into a draft.
+        if revision
+            .message
+            .get(&MessageSection::Draft)
+            .is_some_and(|draft| draft != "no")
+        {
+            revision
+                .message
+                .insert(MessageSection::Draft, String::from("no"));
+            jj.update_revision_message(&revision)?;
+        }
+    }
+
+    crate::output::output(&config.icons.wave, "Done")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{jj::RevSet, message::MessageSection, testing};
+
+    #[tokio::test]
+    async fn marks_ready_and_updates_section() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let bottom =
+            testing::jj::create_jujutsu_commit(&mut jj, "Bottom commit\n\nDraft: yes", "bottom");
+        let top = testing::jj::create_jujutsu_commit(&mut jj, "Top commit\n\nDraft: yes", "top");
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+        assert!(gh.pull_requests.values().all(|pr| pr.draft));
+
+        super::ready(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::ReadyOptions::default().with_revset(Some(RevSet::from(&bottom).as_ref())),
+        )
+        .await
+        .expect("Ready shouldn't fail");
+
+        assert!(!gh.pull_requests[&1].draft, "Bottom PR should be ready");
+        assert!(gh.pull_requests[&2].draft, "Top PR should still be a draft");
+        let bottom = jj
+            .read_revision(&config, bottom)
+            .expect("Revision should exist");
+        assert_eq!(
+            bottom
+                .message
+                .get(&MessageSection::Draft)
+                .map(|s| s.as_str()),
+            Some("no")
+        );
+        let top = jj
+            .read_revision(&config, top)
+            .expect("Revision should exist");
+        assert_eq!(
+            top.message.get(&MessageSection::Draft).map(|s| s.as_str()),
+            Some("yes")
+        );
+    }
+}
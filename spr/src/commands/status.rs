@@ -0,0 +1,319 @@
+use std::iter::zip;
+
+use crate::{
+    error::Result,
+    github::{CheckState, GHPullRequest, Mergeable, PullRequestState, ReviewDecision},
+    jj::RevSet,
+    message::MessageSection,
+};
+
+#[derive(Debug, clap::Parser, Default)]
+pub struct StatusOptions {
+    /// Show the stack(s) for this revset. Same semantics as for `push`.
+    #[clap(long, short = 'r', group = "revs")]
+    revset: Option<String>,
+
+    /// Show all mutable stacks
+    #[clap(long, short = 'a', group = "revs")]
+    all: bool,
+}
+
+#[cfg(test)]
+impl StatusOptions {
+    pub fn with_revset<S>(mut self, revset: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.revset = revset.map(|s| s.into());
+        self
+    }
+}
+
+struct StatusRow<PR> {
+    revision: crate::jj::Revision,
+    pull_request: Option<PR>,
+    local_changes: bool,
+    remote_moved: bool,
+}
+
+impl<PR> StatusRow<PR>
+where
+    PR: GHPullRequest,
+{
+    fn format(&self) -> String {
+        let Some(pr) = self.pull_request.as_ref() else {
+            return format!("{} (not pushed)", self.revision.title);
+        };
+
+        let mut details = vec![
+            match (pr.state(), pr.draft()) {
+                (PullRequestState::Merged, _) => "merged",
+                (PullRequestState::Closed, _) => "closed",
+                (PullRequestState::Open, true) => "draft",
+                (PullRequestState::Open, false) => "open",
+            }
+            .to_string(),
+        ];
+
+        if pr.state() == PullRequestState::Open {
+            if let Some(decision) = pr.review_decision() {
+                details.push(
+                    match decision {
+                        ReviewDecision::Approved => "approved",
+                        ReviewDecision::ChangesRequested => "changes requested",
+                        ReviewDecision::ReviewRequired => "review required",
+                    }
+                    .to_string(),
+                );
+            }
+            if !pr.requested_reviewers().is_empty() {
+                details.push(format!(
+                    "waiting on {}",
+                    pr.requested_reviewers().join(", ")
+                ));
+            }
+            if let Some(checks) = pr.checks() {
+                details.push(
+                    match checks {
+                        CheckState::Pending => "checks pending",
+                        CheckState::Success => "checks passed",
+                        CheckState::Failure => "checks failed",
+                    }
+                    .to_string(),
+                );
+            }
+            match pr.mergeable() {
+                Mergeable::Mergeable => {}
+                Mergeable::Conflicting => details.push("conflicts".to_string()),
+                Mergeable::Unknown => details.push("mergeability unknown".to_string()),
+            }
+        }
+
+        if self.local_changes {
+            details.push("local changes".to_string());
+        }
+        if self.remote_moved {
+            details.push("remote moved".to_string());
+        }
+
+        format!(
+            "#{} {} [{}]",
+            pr.pr_number(),
+            self.revision.title,
+            details.join(", ")
+        )
+    }
+}
+
+fn compare_with_remote(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    revision: &crate::jj::Revision,
+    head_branch: &str,
+) -> Result<(bool, bool)> {
+    let Some(last) = revision.message.get(&MessageSection::LastCommit) else {
+        return Ok((true, false));
+    };
+    let last = git2::Oid::from_str(last)?;
+
+    let local_tree =
+        jj.get_tree_oid_for_commit(jj.resolve_revision_to_commit_id(revision.id.as_ref())?)?;
+    // The last pushed commit may be unknown locally if someone else rewrote the branch.
+    let local_changes = jj
+        .get_tree_oid_for_commit(last)
+        .map_or(true, |tree| tree != local_tree);
+
+    let remote_moved = jj
+        .resolve_reference(
+            format!(
+                "refs/remotes/{}/{}",
+                config.branch_remote(&jj.git_repo, head_branch),
+                head_branch
+            )
+            .as_str(),
+        )
+        .map_or(true, |head| head != last);
+
+    Ok((local_changes, remote_moved))
+}
+
+async fn collect_status<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    opts: &StatusOptions,
+) -> Result<Vec<String>>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let heads = opts
+        .revset
+        .as_ref()
+        .map(RevSet::from_arg)
+        .unwrap_or(if opts.all {
+            RevSet::mutable().heads()
+        } else {
+            RevSet::current()
+        });
+    // Same selection as `push`: everything between trunk and the heads that has a description.
+    let revset = heads
+        .ancestors()
+        .without(&RevSet::immutable().or(&RevSet::description("exact:\"\"")));
+
+    let revisions = jj.read_revision_range(config, &revset)?;
+    let pull_requests = gh
+        .pull_requests(revisions.iter().map(|r| r.pull_request_number))
+        .await?;
+
+    let mut forest = crate::tree::Forest::new();
+    for (revision, pull_request) in zip(revisions, pull_requests) {
+        let (local_changes, remote_moved) = match pull_request.as_ref() {
+            Some(pr) => compare_with_remote(jj, config, &revision, pr.head_branch_name())?,
+            None => (false, false),
+        };
+        let parent = revision.parent_ids.first().cloned();
+        forest.insert_below(
+            &|row: &StatusRow<PR>| Some(&row.revision.id) == parent.as_ref(),
+            StatusRow {
+                revision,
+                pull_request,
+                local_changes,
+                remote_moved,
+            },
+        );
+    }
+
+    Ok(forest
+        .trees()
+        .iter()
+        .flat_map(|tree| tree.draw(&config.drawing, &|row: &StatusRow<PR>| row.format()))
+        .collect())
+}
+
+pub async fn status<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    gh: GH,
+    config: &crate::config::Config,
+    opts: StatusOptions,
+) -> Result<()>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    jj.run_git_fetch_for(config)?;
+    let lines = collect_status(jj, gh, config, &opts).await?;
+    if lines.is_empty() {
+        crate::output::output(&config.icons.wave, "No revisions found - nothing to show")?;
+        return Ok(());
+    }
+
+    let term = console::Term::stdout();
+    for line in lines {
+        term.write_line(&line)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        github::{CheckState, Mergeable, ReviewDecision},
+        testing,
+    };
+
+    #[tokio::test]
+    async fn shows_pr_state() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Bottom commit", "bottom");
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Top commit", "top");
+        testing::jj::create_jujutsu_commit(&mut jj, "Unpushed commit", "unpushed");
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default().with_revset(Some("@--")),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+
+        {
+            let bottom = gh
+                .pull_requests
+                .get_mut(&1)
+                .expect("Bottom PR should exist");
+            bottom.review_decision = Some(ReviewDecision::Approved);
+            bottom.checks = Some(CheckState::Success);
+            bottom.mergeable = Mergeable::Mergeable;
+            let top = gh.pull_requests.get_mut(&2).expect("Top PR should exist");
+            top.draft = true;
+            top.reviewers = vec![String::from("alice")];
+            top.checks = Some(CheckState::Failure);
+            top.mergeable = Mergeable::Conflicting;
+        }
+
+        let lines = super::collect_status(
+            &mut jj,
+            &mut gh,
+            &config,
+            &super::StatusOptions::default().with_revset(Some("@-")),
+        )
+        .await
+        .expect("Status shouldn't fail");
+
+        assert_eq!(
+            lines,
+            vec![
+                "#1 Bottom commit [open, approved, checks passed]",
+                "#2 Top commit [draft, waiting on alice, checks failed, conflicts]",
+                "Unpushed commit (not pushed)",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn flags_local_changes() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+        gh.pull_requests
+            .get_mut(&1)
+            .expect("PR should exist")
+            .mergeable = Mergeable::Mergeable;
+
+        let opts = super::StatusOptions::default().with_revset(Some("@-"));
+        let lines = super::collect_status(&mut jj, &mut gh, &config, &opts)
+            .await
+            .expect("Status shouldn't fail");
+        assert_eq!(lines, vec!["#1 Test commit [open]"]);
+
+        std::fs::write(
+            jj.git_repo
+                .workdir()
+                .expect("Failed to extract workdir from JJ handle")
+                .join("file"),
+            "changed",
+        )
+        .expect("Failed to write test file");
+        jj.squash().expect("Failed to squash change into revision");
+
+        let lines = super::collect_status(&mut jj, &mut gh, &config, &opts)
+            .await
+            .expect("Status shouldn't fail");
+        assert_eq!(lines, vec!["#1 Test commit [open, local changes]"]);
+    }
+}
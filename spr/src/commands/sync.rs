@@ -1,22 +1,125 @@
-use std::iter::zip;
+use std::{collections::HashSet, iter::zip};
 
 use crate::{
     error::Result,
-    jj::{ChangeId, RevSet},
+    github::PullRequestState,
+    jj::{ChangeId, RevSet, change_id_trailers},
+    plan::{Action, Plan, PlanFormat},
 };
 
-#[derive(Debug, clap::Parser)]
+/// What to do with a revision whose PR was closed without being merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ClosedAction {
+    /// Remove the PR sections from the revision
+    Detach,
+    /// Reopen the PR on GitHub
+    Reopen,
+    /// Keep the revision as it is
+    Leave,
+}
+
+#[derive(Debug, clap::Parser, Default)]
 pub struct SyncOpts {
     #[clap(long, short = 'r', group = "revs")]
     revset: Option<String>,
 
     #[clap(long, short = 'a', group = "revs")]
     all: bool,
+
+    /// Only print what would be done. Optionally as `json`.
+    #[clap(long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+    dry_run: Option<PlanFormat>,
+
+    /// What to do with revisions whose PR was closed without merging. Asks if not given.
+    #[clap(long, value_enum)]
+    closed: Option<ClosedAction>,
+}
+
+impl SyncOpts {
+    /// Sync `revset` without asking anything. Revisions of closed PRs are left alone.
+    pub(crate) fn unattended(revset: Option<String>) -> Self {
+        Self {
+            revset,
+            closed: Some(ClosedAction::Leave),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+impl SyncOpts {
+    pub fn with_revset<S>(mut self, revset: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.revset = revset.map(|s| s.into());
+        self
+    }
+
+    pub fn with_dry_run(mut self, format: PlanFormat) -> Self {
+        self.dry_run = Some(format);
+        self
+    }
+
+    pub fn with_closed(mut self, action: ClosedAction) -> Self {
+        self.closed = Some(action);
+        self
+    }
+}
+
+fn ask_closed_action(url: &str) -> Result<ClosedAction> {
+    let actions = [
+        ClosedAction::Detach,
+        ClosedAction::Reopen,
+        ClosedAction::Leave,
+    ];
+    let selection = dialoguer::Select::new()
+        .with_prompt(format!("{} was closed without merging", url))
+        .items(["Detach the revision", "Reopen the PR", "Leave it alone"])
+        .default(2)
+        .interact()?;
+    Ok(actions[selection])
+}
+
+/// Revisions in `stack` whose changes are already in `trunk`, even if no merged PR says so.
+/// Trunk commits since the fork point are matched by the change id trailer spr adds to the
+/// commits it pushes, by patch-id, or by tree.
+fn landed_revisions(
+    jj: &crate::jj::Jujutsu,
+    stack: &RevSet,
+    trunk: &RevSet,
+) -> Result<Vec<ChangeId>> {
+    let mut change_ids = HashSet::new();
+    let mut patch_ids = HashSet::new();
+    let mut trees = HashSet::new();
+    for (_, oid) in jj.revset_to_commits(&trunk.ancestors().without(&stack.ancestors()))? {
+        let commit = jj.git_repo.find_commit(oid)?;
+        change_ids.extend(change_id_trailers(&String::from_utf8_lossy(
+            commit.message_bytes(),
+        )));
+        patch_ids.extend(jj.patch_id(oid)?);
+        trees.insert(commit.tree_id());
+    }
+
+    let mut landed = Vec::new();
+    for (change, oid) in jj.revset_to_commits(stack)? {
+        // Empty revisions would match any other empty commit.
+        let Some(patch_id) = jj.patch_id(oid)? else {
+            continue;
+        };
+        if change_ids.contains(&change)
+            || patch_ids.contains(&patch_id)
+            || trees.contains(&jj.get_tree_oid_for_commit(oid)?)
+        {
+            landed.push(change);
+        }
+    }
+    Ok(landed)
 }
 
 pub async fn sync<GH, PR>(
     jj: &mut crate::jj::Jujutsu,
-    mut gh: GH,
+    gh: GH,
     config: &crate::config::Config,
     opts: SyncOpts,
 ) -> Result<()>
@@ -24,8 +127,30 @@ where
     PR: crate::github::GHPullRequest,
     GH: crate::github::GitHubAdapter<PRAdapter = PR>,
 {
-    jj.run_git_fetch()?;
-    let revset = opts
+    let mut plan = opts.dry_run.map(|_| Plan::new());
+    sync_inner(jj, gh, config, &opts, plan.as_mut()).await?;
+    if let (Some(plan), Some(format)) = (plan, opts.dry_run) {
+        plan.print(config, format)?;
+    }
+    Ok(())
+}
+
+async fn sync_inner<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    opts: &SyncOpts,
+    mut plan: Option<&mut Plan>,
+) -> Result<()>
+where
+    PR: crate::github::GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    match plan.as_mut() {
+        Some(plan) => plan.push(Action::Fetch),
+        None => jj.run_git_fetch_for(config)?,
+    }
+    let stack = opts
         .revset
         .as_ref()
         .map(|s| RevSet::from_arg(s))
@@ -35,11 +160,35 @@ where
             RevSet::current()
         })
         .ancestors()
-        .and(&RevSet::mutable())
-        .and(&RevSet::description("glob:\"*Pull Request:*\""));
+        .and(&RevSet::mutable());
+    let revset = stack.and(&RevSet::description("glob:\"*Pull Request:*\""));
+    let trunk = ChangeId::from(format!("{}@{}", config.master_ref, config.remote_name));
+
+    // Whatever already is in trunk goes, no matter what happened to its PR.
+    let landed = landed_revisions(jj, &stack, &RevSet::from_arg(trunk.as_ref()))?;
+    let mut abandoned = Vec::new();
+    for rev in jj
+        .read_revision_range(config, &stack)?
+        .into_iter()
+        .filter(|rev| landed.contains(&rev.id))
+    {
+        if let Some(plan) = plan.as_mut() {
+            plan.push(Action::Abandon {
+                revision: rev.id.to_string(),
+                number: rev.pull_request_number,
+            });
+        } else {
+            crate::output::output(
+                &config.icons.land,
+                format!("{:?} is already in trunk. Abandoning it", rev.id),
+            )?;
+            jj.abandon(&RevSet::from(&rev.id).unique())?;
+        }
+        abandoned.push(rev.id);
+    }
 
     // We are interested in all revisions that have PRs
-    let revisions = jj.read_revision_range(&revset)?;
+    let revisions = jj.read_revision_range(config, &revset)?;
 
     let pull_requests = gh
         .pull_requests(revisions.iter().map(|n| n.pull_request_number))
@@ -51,32 +200,346 @@ where
         } else {
             continue;
         };
+        if abandoned.contains(&rev.id) {
+            continue;
+        }
 
-        // TODO: Should this only abandon changes of PRs that have been merged?
-        if pr.closed() {
-            crate::output::output(
-                &config.icons.land,
-                format!(
-                    "{} landed. Abandoning {:?}",
-                    config.pull_request_url(pr.pr_number()),
-                    rev.id,
-                ),
-            )?;
-            jj.abandon(&RevSet::from(&rev.id).unique())?;
+        match pr.state() {
+            PullRequestState::Open => {}
+            PullRequestState::Merged => {
+                if let Some(plan) = plan.as_mut() {
+                    plan.push(Action::Abandon {
+                        revision: rev.id.to_string(),
+                        number: Some(pr.pr_number()),
+                    });
+                    abandoned.push(rev.id);
+                    continue;
+                }
+                crate::output::output(
+                    &config.icons.land,
+                    format!(
+                        "{} landed. Abandoning {:?}",
+                        config.pull_request_url(pr.pr_number()),
+                        rev.id,
+                    ),
+                )?;
+                jj.abandon(&RevSet::from(&rev.id).unique())?;
+            }
+            PullRequestState::Closed => {
+                let url = config.pull_request_url(pr.pr_number());
+                let action = match opts.closed {
+                    Some(action) => action,
+                    None if plan.is_none() && console::user_attended() => ask_closed_action(&url)?,
+                    None => ClosedAction::Leave,
+                };
+                match (action, plan.as_mut()) {
+                    (ClosedAction::Detach, Some(plan)) => plan.push(Action::Detach {
+                        revision: rev.id.to_string(),
+                        number: pr.pr_number(),
+                    }),
+                    (ClosedAction::Detach, None) => {
+                        super::detach::detach_revision(jj, config, rev)?
+                    }
+                    (ClosedAction::Reopen, Some(plan)) => plan.push(Action::ReopenPullRequest {
+                        number: pr.pr_number(),
+                    }),
+                    (ClosedAction::Reopen, None) => {
+                        gh.reopen_pull_request(&pr).await?;
+                        crate::output::output(&config.icons.refresh, format!("Reopened {}", url))?;
+                    }
+                    (ClosedAction::Leave, Some(_)) => {}
+                    (ClosedAction::Leave, None) => crate::output::output(
+                        &config.icons.info,
+                        format!(
+                            "{} was closed without merging. Leaving {:?} alone",
+                            url, rev.id
+                        ),
+                    )?,
+                }
+            }
         }
     }
-    if jj.revset_to_change_ids(&revset)?.is_empty() {
+    if jj
+        .revset_to_change_ids(&revset)?
+        .iter()
+        .all(|id| abandoned.contains(id))
+    {
         crate::output::output(&config.icons.wave, "Nothing left to rebase")?;
         return Ok(());
     }
+    if let Some(plan) = plan {
+        plan.push(Action::Rebase {
+            revisions: revset.as_ref().to_string(),
+            onto: trunk.to_string(),
+        });
+        return Ok(());
+    }
     crate::output::output(
         &config.icons.refresh,
         format!("Going to rebase {:?}", revset),
     )?;
-    jj.rebase_branch(
-        &revset,
-        ChangeId::from(format!("{}@{}", config.master_ref, config.remote_name)),
-    )?;
+    jj.rebase_branch(&revset, trunk)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ClosedAction;
+    use crate::{
+        jj::{ChangeId, RevSet},
+        plan::{Action, Plan, PlanFormat},
+        testing,
+    };
+
+    #[tokio::test]
+    async fn dry_run_changes_nothing() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let bottom = testing::jj::create_jujutsu_commit(&mut jj, "Bottom commit", "bottom");
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Top commit", "top");
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+        let pr = gh.pull_requests.get_mut(&1).expect("PR should exist");
+        pr.closed = true;
+        pr.merged = true;
+
+        let opts = super::SyncOpts::default()
+            .with_revset(Some("@-"))
+            .with_dry_run(PlanFormat::Json);
+        let mut plan = Plan::new();
+        super::sync_inner(&mut jj, &mut gh, &config, &opts, Some(&mut plan))
+            .await
+            .expect("Dry run shouldn't fail");
+
+        assert_eq!(plan.actions()[0], Action::Fetch);
+        assert_eq!(
+            plan.actions()[1],
+            Action::Abandon {
+                revision: bottom.to_string(),
+                number: Some(1),
+            }
+        );
+        assert!(matches!(plan.actions()[2], Action::Rebase { .. }));
+        assert_eq!(plan.actions().len(), 3);
+        jj.read_revision(&config, bottom)
+            .expect("Revision shouldn't be abandoned in a dry run");
+    }
+
+    async fn push_and_close(
+        jj: &mut crate::jj::Jujutsu,
+        gh: &mut crate::github::fakes::GitHub,
+        config: &crate::config::Config,
+    ) -> ChangeId {
+        let change = testing::jj::create_jujutsu_commit(jj, "Test commit", "file");
+        crate::commands::push::push(
+            jj,
+            &mut *gh,
+            config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+        gh.pull_requests
+            .get_mut(&1)
+            .expect("PR should exist")
+            .closed = true;
+        change
+    }
+
+    #[tokio::test]
+    async fn keeps_closed_unmerged() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let mut gh = crate::github::fakes::GitHub::new();
+        let change = push_and_close(&mut jj, &mut gh, &config).await;
+
+        super::sync(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::SyncOpts::default()
+                .with_revset(Some("@-"))
+                .with_closed(ClosedAction::Leave),
+        )
+        .await
+        .expect("Sync shouldn't fail");
+        let revision = jj
+            .read_revision(&config, change.clone())
+            .expect("Closed PR's revision shouldn't be abandoned");
+        assert_eq!(revision.pull_request_number, Some(1));
+
+        super::sync(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::SyncOpts::default()
+                .with_revset(Some("@-"))
+                .with_closed(ClosedAction::Detach),
+        )
+        .await
+        .expect("Sync shouldn't fail");
+        let revision = jj
+            .read_revision(&config, change)
+            .expect("Detached revision should still exist");
+        assert_eq!(revision.pull_request_number, None);
+        assert!(gh.pull_requests[&1].closed);
+    }
+
+    #[tokio::test]
+    async fn reopens_closed_unmerged() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let mut gh = crate::github::fakes::GitHub::new();
+        let change = push_and_close(&mut jj, &mut gh, &config).await;
+
+        super::sync(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::SyncOpts::default()
+                .with_revset(Some("@-"))
+                .with_closed(ClosedAction::Reopen),
+        )
+        .await
+        .expect("Sync shouldn't fail");
+
+        assert!(!gh.pull_requests[&1].closed);
+        let revision = jj
+            .read_revision(&config, change)
+            .expect("Reopened PR's revision shouldn't be abandoned");
+        assert_eq!(revision.pull_request_number, Some(1));
+    }
+
+    /// Put a commit with `tree` on top of trunk on the remote, like a merge on GitHub would.
+    fn land(jj: &crate::jj::Jujutsu, tree: git2::Oid, message: &str) {
+        let repo = &jj.git_repo;
+        let trunk = repo
+            .find_commit(
+                jj.resolve_revision_to_commit_id("main@origin")
+                    .expect("Trunk should resolve"),
+            )
+            .expect("Failed to find trunk commit");
+        let sig = git2::Signature::now("User", "user@example.com")
+            .expect("Failed to build commit signature");
+        let oid = repo
+            .commit(
+                None,
+                &sig,
+                &sig,
+                message,
+                &repo.find_tree(tree).expect("Failed to find tree"),
+                &[&trunk],
+            )
+            .expect("Failed to commit to repo");
+        repo.find_remote("origin")
+            .expect("Expected to find origin as remote")
+            .push(&[format!("{oid}:refs/heads/main")], None)
+            .expect("Failed to push");
+    }
+
+    #[tokio::test]
+    async fn abandons_revisions_already_in_trunk() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let change = testing::jj::create_jujutsu_commit(&mut jj, "Local commit", "file");
+        let commit = jj
+            .resolve_revision_to_commit_id(RevSet::from(&change).as_ref())
+            .expect("Revision should resolve");
+
+        // Land the same change behind an unrelated one, so only the patch matches.
+        let main = jj
+            .resolve_revision_to_commit_id("main@origin")
+            .expect("Trunk should resolve");
+        testing::git::add_commit_on_and_push_to_remote_file(&jj.git_repo, "main", [main], "other");
+        jj.run_git_fetch().expect("Fetch shouldn't fail");
+        let other = jj
+            .resolve_revision_to_commit_id("main@origin")
+            .expect("Trunk should resolve");
+        let index = jj
+            .cherrypick(commit, other)
+            .expect("Cherry-pick shouldn't fail");
+        land(
+            &jj,
+            jj.write_index(index).expect("Failed to write tree"),
+            "Squashed",
+        );
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        super::sync(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::SyncOpts::default().with_revset(Some("@-")),
+        )
+        .await
+        .expect("Sync shouldn't fail");
+
+        assert!(
+            !jj.revset_to_change_ids(&RevSet::mutable())
+                .expect("Mutable revisions should resolve")
+                .contains(&change),
+            "Landed revision should be abandoned"
+        );
+    }
+
+    #[tokio::test]
+    async fn abandons_by_change_id_trailer() {
+        let (_temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let change = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+
+        let pushed = bare
+            .revparse_single("spr/test/test-commit")
+            .expect("Branch should be pushed")
+            .peel_to_commit()
+            .expect("Branch should point to a commit");
+        let message = pushed.message().expect("Message should be UTF-8");
+        assert!(message.contains(&format!("Change-Id: {}", change)));
+
+        // Landed with different content, and the PR was never merged.
+        let main = jj
+            .resolve_revision_to_commit_id("main@origin")
+            .expect("Trunk should resolve");
+        let tree = jj
+            .git_repo
+            .find_commit(main)
+            .expect("Failed to find trunk commit")
+            .tree_id();
+        land(&jj, tree, &format!("Squashed (#1)\n\n* {}", message));
+
+        super::sync(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::SyncOpts::default().with_revset(Some("@-")),
+        )
+        .await
+        .expect("Sync shouldn't fail");
+
+        assert!(
+            !jj.revset_to_change_ids(&RevSet::mutable())
+                .expect("Mutable revisions should resolve")
+                .contains(&change),
+            "Landed revision should be abandoned"
+        );
+        assert!(!gh.pull_requests[&1].closed);
+    }
+}
@@ -0,0 +1,517 @@
+use crate::{
+    error::{Error, Result, ResultExt},
+    github::GHPullRequest,
+    journal::{Journal, Mutation},
+    utils::run_command,
+};
+
+#[derive(Debug, clap::Parser, Default)]
+pub struct UndoOptions {
+    /// Undo even if the repository changed since the command ran
+    #[clap(long, short = 'f')]
+    force: bool,
+}
+
+#[cfg(test)]
+impl UndoOptions {
+    pub fn with_force(mut self, val: bool) -> Self {
+        self.force = val;
+        self
+    }
+}
+
+/// Move a pushed branch back to where it was, or delete it if it didn't exist before.
+/// The lease makes sure nobody pushed to it in the meantime.
+async fn restore_branch(
+    jj: &crate::jj::Jujutsu,
+    config: &crate::config::Config,
+    branch: &str,
+    old: Option<&str>,
+    new: &str,
+) -> Result<()> {
+    let mut cmd = tokio::process::Command::new("git");
+    cmd.arg("-C")
+        .arg(jj.git_repo.path())
+        .arg("push")
+        .arg("--no-verify")
+        .arg(format!("--force-with-lease=refs/heads/{}:{}", branch, new))
+        .arg("--")
+        .arg(config.push_remote())
+        .arg(format!("{}:refs/heads/{}", old.unwrap_or(""), branch));
+
+    run_command(&mut cmd)
+        .await
+        .context(format!("Restore branch {}", branch))
+}
+
+/// Revert a single mutation. Returns `false` if GitHub doesn't allow reverting it.
+async fn revert<GH, PR>(
+    jj: &crate::jj::Jujutsu,
+    gh: &mut GH,
+    config: &crate::config::Config,
+    mutation: &Mutation,
+) -> Result<bool>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    match mutation {
+        Mutation::PullRequestCreated { number } => {
+            let pr = gh.pull_request(*number).await?;
+            if !pr.closed() {
+                gh.close_pull_request(&pr).await?;
+            }
+        }
+        Mutation::PullRequestClosed { number } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.reopen_pull_request(&pr).await?
+        }
+        Mutation::PullRequestReopened { number } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.close_pull_request(&pr).await?
+        }
+        Mutation::BaseChanged {
+            number, old_base, ..
+        } => gh.rebase_pr(*number, old_base).await?,
+        Mutation::PullRequestEdited {
+            number,
+            old_title,
+            old_body,
+        } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.edit_pull_request(&pr, old_title.clone(), old_body.clone())
+                .await?
+        }
+        Mutation::DraftChanged { number, draft } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.set_draft(&pr, !draft).await?
+        }
+        Mutation::ReviewersRequested { number, reviewers } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.remove_reviewers(&pr, reviewers).await?
+        }
+        Mutation::ReviewersRemoved { number, reviewers } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.add_reviewers(&pr, reviewers).await?
+        }
+        Mutation::AssigneesAdded { number, assignees } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.remove_assignees(&pr, assignees).await?
+        }
+        Mutation::AssigneesRemoved { number, assignees } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.add_assignees(&pr, assignees).await?
+        }
+        Mutation::LabelsAdded { number, labels } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.remove_labels(&pr, labels).await?
+        }
+        Mutation::LabelsRemoved { number, labels } => {
+            let pr = gh.pull_request(*number).await?;
+            gh.add_labels(&pr, labels).await?
+        }
+        Mutation::CommentEdited {
+            id,
+            old_body: Some(old_body),
+        } => gh.update_issue_comment(id, old_body).await?,
+        Mutation::BranchPushed { branch, old, new } => {
+            restore_branch(jj, config, branch, old.as_deref(), new).await?
+        }
+        // An empty lease makes sure the branch wasn't recreated in the meantime.
+        Mutation::BranchDeleted {
+            branch,
+            old: Some(old),
+        } => restore_branch(jj, config, branch, Some(old), "").await?,
+        Mutation::CommentEdited { old_body: None, .. }
+        | Mutation::CommentPosted { .. }
+        | Mutation::PullRequestMerged { .. }
+        | Mutation::BranchDeleted { old: None, .. } => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn describe(config: &crate::config::Config, mutation: &Mutation) -> String {
+    match mutation {
+        Mutation::PullRequestCreated { number } => {
+            format!("creation of {}", config.pull_request_url(*number))
+        }
+        Mutation::BaseChanged {
+            number,
+            old_base,
+            new_base,
+        } => format!(
+            "retarget of {} from {} to {}",
+            config.pull_request_url(*number),
+            old_base,
+            new_base
+        ),
+        Mutation::CommentPosted { number } => {
+            format!("comment posted on {}", config.pull_request_url(*number))
+        }
+        Mutation::CommentEdited { id, .. } => format!("edit of comment {}", id),
+        Mutation::ReviewersRequested { number, reviewers } => format!(
+            "review request for {} on {}",
+            reviewers.join(", "),
+            config.pull_request_url(*number)
+        ),
+        Mutation::ReviewersRemoved { number, reviewers } => format!(
+            "withdrawn review request for {} on {}",
+            reviewers.join(", "),
+            config.pull_request_url(*number)
+        ),
+        Mutation::AssigneesAdded { number, assignees } => format!(
+            "assignment of {} to {}",
+            assignees.join(", "),
+            config.pull_request_url(*number)
+        ),
+        Mutation::AssigneesRemoved { number, assignees } => format!(
+            "unassignment of {} from {}",
+            assignees.join(", "),
+            config.pull_request_url(*number)
+        ),
+        Mutation::LabelsAdded { number, labels } => format!(
+            "labels {} added to {}",
+            labels.join(", "),
+            config.pull_request_url(*number)
+        ),
+        Mutation::LabelsRemoved { number, labels } => format!(
+            "labels {} removed from {}",
+            labels.join(", "),
+            config.pull_request_url(*number)
+        ),
+        Mutation::PullRequestEdited { number, .. } => {
+            format!("edit of {}", config.pull_request_url(*number))
+        }
+        Mutation::DraftChanged {
+            number,
+            draft: true,
+        } => format!(
+            "conversion of {} to a draft",
+            config.pull_request_url(*number)
+        ),
+        Mutation::DraftChanged {
+            number,
+            draft: false,
+        } => format!(
+            "marking {} ready for review",
+            config.pull_request_url(*number)
+        ),
+        Mutation::PullRequestMerged { number } => {
+            format!("merge of {}", config.pull_request_url(*number))
+        }
+        Mutation::PullRequestClosed { number } => {
+            format!("close of {}", config.pull_request_url(*number))
+        }
+        Mutation::PullRequestReopened { number } => {
+            format!("reopen of {}", config.pull_request_url(*number))
+        }
+        Mutation::BranchDeleted { branch, .. } => format!("deletion of branch {}", branch),
+        Mutation::BranchPushed { branch, .. } => format!("push to branch {}", branch),
+    }
+}
+
+pub async fn undo<GH, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    opts: UndoOptions,
+) -> Result<()>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let journal = Journal::new(jj);
+    let entry = journal
+        .last()?
+        .ok_or_else(|| Error::new("Nothing to undo"))?;
+
+    let current = jj.current_operation()?;
+    if !opts.force && entry.op_after.as_ref() != Some(&current) {
+        return Err(Error::new(format!(
+            "The repository changed since `jj spr {}` ran. Use `--force` to undo it anyway.",
+            entry.command
+        )));
+    }
+
+    let mut failed = Vec::new();
+    for mutation in entry.mutations.iter().rev() {
+        match revert(jj, &mut gh, config, mutation).await {
+            Ok(true) => crate::output::output(
+                &config.icons.refresh,
+                format!("Reverted {}", describe(config, mutation)),
+            )?,
+            Ok(false) => crate::output::output(
+                &config.icons.info,
+                format!("Can't revert {}", describe(config, mutation)),
+            )?,
+            Err(err) => {
+                crate::output::output(
+                    &config.icons.error,
+                    format!("Failed to revert {}", describe(config, mutation)),
+                )?;
+                for message in err.messages() {
+                    crate::output::output(&config.icons.error, message)?;
+                }
+                failed.push(mutation.clone());
+            }
+        }
+    }
+
+    // Keep the entry with only the failed changes, so `undo` can retry them.
+    if !failed.is_empty() {
+        failed.reverse();
+        let count = failed.len();
+        journal.retain_last(failed)?;
+        return Err(Error::new(format!(
+            "Failed to revert {} of the changes of `jj spr {}`. Run `undo` again to retry them.",
+            count, entry.command
+        )));
+    }
+
+    jj.restore_operation(&entry.op_before)?;
+    journal.pop()?;
+    crate::output::output(
+        &config.icons.ok,
+        format!("Undid `jj spr {}`", entry.command),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        github::Journaled,
+        journal::{Journal, Mutation},
+        message::MessageSection,
+        testing,
+    };
+
+    async fn journaled_push(
+        jj: &mut crate::jj::Jujutsu,
+        gh: &mut crate::github::fakes::GitHub,
+        config: &crate::config::Config,
+    ) {
+        let journal = Journal::new(jj);
+        journal
+            .begin(
+                "push",
+                jj.current_operation().expect("Operation should resolve"),
+            )
+            .expect("Begin shouldn't fail");
+        crate::commands::push::push(
+            jj,
+            &mut Journaled::new(gh, journal.clone()),
+            config,
+            crate::commands::push::PushOptions::default().with_message(Some("Update")),
+        )
+        .await
+        .expect("Push shouldn't fail");
+        journal
+            .finish(jj.current_operation().expect("Operation should resolve"))
+            .expect("Finish shouldn't fail");
+    }
+
+    #[tokio::test]
+    async fn reverts_push() {
+        let (_temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let bottom = testing::jj::create_jujutsu_commit(&mut jj, "Bottom commit", "bottom");
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Top commit", "top");
+        let mut gh = crate::github::fakes::GitHub::new();
+
+        journaled_push(&mut jj, &mut gh, &config).await;
+        assert_eq!(gh.pull_requests.len(), 2);
+        bare.find_branch("spr/test/bottom-commit", git2::BranchType::Local)
+            .expect("Branch should be pushed");
+
+        super::undo(&mut jj, &mut gh, &config, super::UndoOptions::default())
+            .await
+            .expect("Undo shouldn't fail");
+
+        assert!(gh.pull_requests.values().all(|pr| pr.closed));
+        assert!(
+            bare.find_branch("spr/test/bottom-commit", git2::BranchType::Local)
+                .is_err(),
+            "Branch should be deleted again"
+        );
+        let revision = jj
+            .read_revision(&config, bottom)
+            .expect("Revision should exist");
+        assert_eq!(revision.message.get(&MessageSection::PullRequest), None);
+        assert_eq!(Journal::new(&jj).last().expect("Journal should load"), None);
+    }
+
+    #[tokio::test]
+    async fn reverts_update() {
+        let (_temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+        let mut gh = crate::github::fakes::GitHub::new();
+
+        journaled_push(&mut jj, &mut gh, &config).await;
+        let first = bare
+            .revparse_single("spr/test/test-commit")
+            .expect("Branch should be pushed")
+            .id();
+        // An edit on GitHub has to survive, the stack comment is only restored to it.
+        let edited = format!("Edited\n{}", gh.pull_requests[&1].comments[0].content);
+        gh.pull_requests
+            .get_mut(&1)
+            .expect("PR should exist")
+            .comments[0]
+            .content = edited.clone();
+
+        std::fs::write(
+            jj.git_repo
+                .workdir()
+                .expect("Failed to extract workdir from JJ handle")
+                .join("file"),
+            "changed",
+        )
+        .expect("Failed to write test file");
+        jj.squash().expect("Failed to squash change into revision");
+        journaled_push(&mut jj, &mut gh, &config).await;
+        assert_ne!(
+            bare.revparse_single("spr/test/test-commit")
+                .expect("Branch should exist")
+                .id(),
+            first
+        );
+
+        super::undo(&mut jj, &mut gh, &config, super::UndoOptions::default())
+            .await
+            .expect("Undo shouldn't fail");
+
+        assert!(!gh.pull_requests[&1].closed);
+        assert_eq!(gh.pull_requests[&1].comments[0].content, edited);
+        assert_eq!(
+            bare.revparse_single("spr/test/test-commit")
+                .expect("Branch should still exist")
+                .id(),
+            first
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_failed_changes_for_retry() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let bottom = testing::jj::create_jujutsu_commit(&mut jj, "Bottom commit", "bottom");
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Top commit", "top");
+        let mut gh = crate::github::fakes::GitHub::new();
+        journaled_push(&mut jj, &mut gh, &config).await;
+        // Make closing the bottom PR fail.
+        let pr = gh.pull_requests.remove(&1).expect("Bottom PR should exist");
+
+        super::undo(&mut jj, &mut gh, &config, super::UndoOptions::default())
+            .await
+            .expect_err("Undo should report the failed revert");
+
+        assert!(gh.pull_requests[&2].closed, "Top PR wasn't closed");
+        let entry = Journal::new(&jj)
+            .last()
+            .expect("Journal should load")
+            .expect("Entry should be kept");
+        assert!(
+            entry
+                .mutations
+                .contains(&Mutation::PullRequestCreated { number: 1 })
+        );
+        assert!(
+            !entry
+                .mutations
+                .contains(&Mutation::PullRequestCreated { number: 2 })
+        );
+        let revision = jj
+            .read_revision(&config, bottom)
+            .expect("Revision should exist");
+        assert_eq!(
+            revision.pull_request_number,
+            Some(1),
+            "jj operation was restored despite the failure"
+        );
+
+        gh.pull_requests.insert(1, pr);
+        super::undo(&mut jj, &mut gh, &config, super::UndoOptions::default())
+            .await
+            .expect("Retried undo shouldn't fail");
+        assert!(gh.pull_requests[&1].closed, "Bottom PR wasn't closed");
+        assert_eq!(Journal::new(&jj).last().expect("Journal should load"), None);
+    }
+
+    #[tokio::test]
+    async fn reverts_close() {
+        let (_temp_dir, mut jj, bare) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+        let mut gh = crate::github::fakes::GitHub::new();
+        journaled_push(&mut jj, &mut gh, &config).await;
+        let pushed = bare
+            .revparse_single("spr/test/test-commit")
+            .expect("Branch should be pushed")
+            .id();
+
+        let journal = Journal::new(&jj);
+        journal
+            .begin(
+                "close",
+                jj.current_operation().expect("Operation should resolve"),
+            )
+            .expect("Begin shouldn't fail");
+        let mut journaled = Journaled::new(&mut gh, journal.clone())
+            .with_remote(jj.git_repo.path(), config.push_remote());
+        crate::commands::close::close(
+            &mut jj,
+            &mut journaled,
+            &config,
+            crate::commands::close::CloseOptions::default().with_revset(Some("@-")),
+        )
+        .await
+        .expect("Close shouldn't fail");
+        journal
+            .finish(jj.current_operation().expect("Operation should resolve"))
+            .expect("Finish shouldn't fail");
+        // The fake doesn't delete branches, so do what GitHub would have done.
+        bare.find_reference("refs/heads/spr/test/test-commit")
+            .expect("Branch should exist")
+            .delete()
+            .expect("Failed to delete branch");
+
+        super::undo(&mut jj, &mut gh, &config, super::UndoOptions::default())
+            .await
+            .expect("Undo shouldn't fail");
+
+        assert!(!gh.pull_requests[&1].closed, "PR wasn't reopened");
+        assert_eq!(
+            bare.revparse_single("spr/test/test-commit")
+                .expect("Branch should be restored")
+                .id(),
+            pushed
+        );
+    }
+
+    #[tokio::test]
+    async fn refuses_after_other_changes() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+        let mut gh = crate::github::fakes::GitHub::new();
+
+        journaled_push(&mut jj, &mut gh, &config).await;
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Other commit", "other");
+
+        super::undo(&mut jj, &mut gh, &config, super::UndoOptions::default())
+            .await
+            .expect_err("Undo should refuse when jj moved on");
+        super::undo(
+            &mut jj,
+            &mut gh,
+            &config,
+            super::UndoOptions::default().with_force(true),
+        )
+        .await
+        .expect("Forced undo shouldn't fail");
+        assert!(gh.pull_requests[&1].closed);
+    }
+}
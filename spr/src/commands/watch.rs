@@ -0,0 +1,382 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use git2::Oid;
+
+use crate::{
+    error::Result,
+    github::{GHPullRequest, PullRequestState, ReviewDecision},
+    jj::RevSet,
+};
+
+#[derive(Debug, clap::Parser, Default)]
+pub struct WatchOptions {
+    /// Watch the PRs of this revision and its ancestors. Defaults to `@`.
+    #[clap(long, short = 'r')]
+    revset: Option<String>,
+
+    /// Time between two polls, like `30s`, `5m` or `1h`
+    #[clap(long, default_value = "60s", value_parser = parse_interval)]
+    interval: Duration,
+
+    /// Update the local revisions from their PRs when something changed. Defaults to
+    /// `spr.watch.fetch`.
+    #[clap(long)]
+    fetch: bool,
+
+    /// Abandon landed revisions and rebase the rest when a PR was merged. Defaults to
+    /// `spr.watch.sync`.
+    #[clap(long)]
+    sync: bool,
+}
+
+fn parse_interval(s: &str) -> std::result::Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid interval '{}'", s))?;
+    let seconds = match unit {
+        "" | "s" => number,
+        "m" => number * 60,
+        "h" => number * 60 * 60,
+        _ => return Err(format!("Unknown unit '{}', use s, m or h", unit)),
+    };
+    if seconds == 0 {
+        return Err(String::from("Interval has to be at least one second"));
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// What was last seen of a PR, to tell what happened since.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Snapshot {
+    state: PullRequestState,
+    review_decision: Option<ReviewDecision>,
+    reviews: usize,
+    comments: usize,
+    head_commit: String,
+}
+
+impl Snapshot {
+    fn new<PR: GHPullRequest>(pr: &PR) -> Self {
+        Self {
+            state: pr.state(),
+            review_decision: pr.review_decision(),
+            reviews: pr.review_count(),
+            comments: pr.comments().len(),
+            head_commit: pr.head_commit().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Event {
+    Merged {
+        number: u64,
+    },
+    Closed {
+        number: u64,
+    },
+    Reopened {
+        number: u64,
+    },
+    Reviewed {
+        number: u64,
+        decision: Option<ReviewDecision>,
+    },
+    Commented {
+        number: u64,
+        count: usize,
+    },
+    /// Somebody else pushed to the PR's branch.
+    Pushed {
+        number: u64,
+    },
+}
+
+impl Event {
+    fn output(&self, config: &crate::config::Config) -> Result<()> {
+        let (icon, message) = match self {
+            Event::Merged { number } => (
+                &config.icons.land,
+                format!("{} was merged", config.pull_request_url(*number)),
+            ),
+            Event::Closed { number } => (
+                &config.icons.stop,
+                format!("{} was closed", config.pull_request_url(*number)),
+            ),
+            Event::Reopened { number } => (
+                &config.icons.refresh,
+                format!("{} was reopened", config.pull_request_url(*number)),
+            ),
+            Event::Reviewed { number, decision } => (
+                &config.icons.eyes,
+                format!(
+                    "New review on {}{}",
+                    config.pull_request_url(*number),
+                    match decision {
+                        Some(ReviewDecision::Approved) => ". It is approved",
+                        Some(ReviewDecision::ChangesRequested) => ". Changes are requested",
+                        Some(ReviewDecision::ReviewRequired) | None => "",
+                    }
+                ),
+            ),
+            Event::Commented { number, count } => (
+                &config.icons.eyes,
+                format!(
+                    "{} new comment(s) on {}",
+                    count,
+                    config.pull_request_url(*number)
+                ),
+            ),
+            Event::Pushed { number } => (
+                &config.icons.info,
+                format!(
+                    "New commits were pushed to {}",
+                    config.pull_request_url(*number)
+                ),
+            ),
+        };
+        crate::output::output(icon, message)
+    }
+}
+
+fn events(jj: &crate::jj::Jujutsu, number: u64, old: &Snapshot, new: &Snapshot) -> Vec<Event> {
+    let mut events = Vec::new();
+    if old.state != new.state {
+        events.push(match new.state {
+            PullRequestState::Merged => Event::Merged { number },
+            PullRequestState::Closed => Event::Closed { number },
+            PullRequestState::Open => Event::Reopened { number },
+        });
+    }
+    if new.reviews > old.reviews || new.review_decision != old.review_decision {
+        events.push(Event::Reviewed {
+            number,
+            decision: new.review_decision,
+        });
+    }
+    if new.comments > old.comments {
+        events.push(Event::Commented {
+            number,
+            count: new.comments - old.comments,
+        });
+    }
+    // Our own pushes create the commit locally first.
+    if new.head_commit != old.head_commit
+        && Oid::from_str(&new.head_commit)
+            .and_then(|oid| jj.git_repo.find_commit(oid))
+            .is_err()
+    {
+        events.push(Event::Pushed { number });
+    }
+    events
+}
+
+/// Why watching has to stop, since updating the stack automatically would get in the way.
+fn stop_reason(jj: &mut crate::jj::Jujutsu, stack: &RevSet) -> Result<Option<&'static str>> {
+    // Reading the operation snapshots the working copy, so edits since the last poll show up.
+    jj.current_operation()?;
+    if !jj.is_empty(&jj.revset_to_change_id(&RevSet::current())?)? {
+        return Ok(Some("The working copy has uncommitted changes"));
+    }
+    if !jj
+        .revset_to_change_ids(&stack.and(&RevSet::conflicts()))?
+        .is_empty()
+    {
+        return Ok(Some("The stack has conflicts"));
+    }
+    Ok(None)
+}
+
+/// Check the PRs of `stack` for changes. PRs seen for the first time only get remembered.
+async fn poll<GH, PR>(
+    jj: &crate::jj::Jujutsu,
+    mut gh: GH,
+    config: &crate::config::Config,
+    stack: &RevSet,
+    snapshots: &mut BTreeMap<u64, Snapshot>,
+) -> Result<Vec<Event>>
+where
+    PR: GHPullRequest,
+    GH: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let revisions = jj.read_revision_range(
+        config,
+        &stack.and(&RevSet::description("glob:\"*Pull Request:*\"")),
+    )?;
+
+    let mut ret = Vec::new();
+    for number in revisions.iter().filter_map(|rev| rev.pull_request_number) {
+        if !gh.pull_request_changed(number).await? {
+            continue;
+        }
+        let snapshot = Snapshot::new(&gh.pull_request(number).await?);
+        if let Some(old) = snapshots.insert(number, snapshot.clone()) {
+            ret.extend(events(jj, number, &old, &snapshot));
+        }
+    }
+    Ok(ret)
+}
+
+pub async fn watch<G, PR>(
+    jj: &mut crate::jj::Jujutsu,
+    gh: &mut G,
+    config: &crate::config::Config,
+    opts: WatchOptions,
+) -> Result<()>
+where
+    PR: GHPullRequest,
+    for<'a> &'a mut G: crate::github::GitHubAdapter<PRAdapter = PR>,
+{
+    let fetch = opts.fetch || config.watch.fetch;
+    let sync = opts.sync || config.watch.sync;
+    let stack = opts
+        .revset
+        .as_ref()
+        .map(RevSet::from_arg)
+        .unwrap_or(RevSet::current())
+        .ancestors()
+        .and(&RevSet::mutable());
+
+    crate::output::output(
+        &config.icons.eyes,
+        format!(
+            "Watching every {}s. Stop with Ctrl-C",
+            opts.interval.as_secs()
+        ),
+    )?;
+    let mut snapshots = BTreeMap::new();
+    loop {
+        if let Some(reason) = stop_reason(jj, &stack)? {
+            crate::output::output(&config.icons.stop, format!("{}. Stopped watching", reason))?;
+            return Ok(());
+        }
+
+        let events = poll(jj, &mut *gh, config, &stack, &mut snapshots).await?;
+        for event in events.iter() {
+            event.output(config)?;
+        }
+        if fetch && !events.is_empty() {
+            super::fetch::fetch(
+                super::fetch::FetchOptions::metadata_only(opts.revset.clone()),
+                jj,
+                &mut *gh,
+                config,
+            )
+            .await?;
+        }
+        if sync && events.iter().any(|e| matches!(e, Event::Merged { .. })) {
+            super::sync::sync(
+                jj,
+                &mut *gh,
+                config,
+                super::sync::SyncOpts::unattended(opts.revset.clone()),
+            )
+            .await?;
+        }
+
+        tokio::time::sleep(opts.interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use super::Event;
+    use crate::{github::ReviewDecision, jj::RevSet, testing};
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!(super::parse_interval("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(super::parse_interval("60s"), Ok(Duration::from_secs(60)));
+        assert_eq!(super::parse_interval("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(super::parse_interval("1h"), Ok(Duration::from_secs(3600)));
+        assert!(super::parse_interval("0s").is_err());
+        assert!(super::parse_interval("1d").is_err());
+        assert!(super::parse_interval("soon").is_err());
+    }
+
+    #[tokio::test]
+    async fn reports_changes() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Bottom commit", "bottom");
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Top commit", "top");
+        let mut gh = crate::github::fakes::GitHub::new();
+        crate::commands::push::push(
+            &mut jj,
+            &mut gh,
+            &config,
+            crate::commands::push::PushOptions::default(),
+        )
+        .await
+        .expect("Push for setup shouldn't fail");
+
+        let stack = RevSet::current().ancestors().and(&RevSet::mutable());
+        let mut snapshots = BTreeMap::new();
+        let events = super::poll(&jj, &mut gh, &config, &stack, &mut snapshots)
+            .await
+            .expect("Poll shouldn't fail");
+        assert_eq!(events, vec![]);
+
+        let bottom = gh.pull_requests.get_mut(&1).expect("PR should exist");
+        bottom.merged = true;
+        bottom.closed = true;
+        let top = gh.pull_requests.get_mut(&2).expect("PR should exist");
+        top.review_count = 1;
+        top.review_decision = Some(ReviewDecision::ChangesRequested);
+        top.comments.push(crate::github::fakes::PullRequestComment {
+            content: String::from("Please rename this"),
+            id: String::from("comment"),
+            editable: false,
+        });
+        top.head_commit = String::from("0123456789abcdef0123456789abcdef01234567");
+
+        let events = super::poll(&jj, &mut gh, &config, &stack, &mut snapshots)
+            .await
+            .expect("Poll shouldn't fail");
+        assert_eq!(
+            events,
+            vec![
+                Event::Merged { number: 1 },
+                Event::Reviewed {
+                    number: 2,
+                    decision: Some(ReviewDecision::ChangesRequested),
+                },
+                Event::Commented {
+                    number: 2,
+                    count: 1,
+                },
+                Event::Pushed { number: 2 },
+            ]
+        );
+
+        let events = super::poll(&jj, &mut gh, &config, &stack, &mut snapshots)
+            .await
+            .expect("Poll shouldn't fail");
+        assert_eq!(events, vec![]);
+    }
+
+    #[tokio::test]
+    async fn stops_on_uncommitted_changes() {
+        let (_temp_dir, mut jj, _) = testing::setup::repo_with_origin();
+        let config = testing::config::basic();
+        let _ = testing::jj::create_jujutsu_commit(&mut jj, "Test commit", "file");
+        std::fs::write(
+            jj.git_repo
+                .workdir()
+                .expect("Failed to extract workdir from JJ handle")
+                .join("file"),
+            "changed",
+        )
+        .expect("Failed to write test file");
+
+        let mut gh = crate::github::fakes::GitHub::new();
+        // Returns instead of polling forever.
+        super::watch(&mut jj, &mut gh, &config, super::WatchOptions::default())
+            .await
+            .expect("Watch should stop cleanly");
+    }
+}
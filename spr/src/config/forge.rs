@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+/// The service hosting the repository and its PRs. `spr.forge` names one, or is a table with the
+/// `command` of a plugin.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(from = "Setting", into = "Setting")]
+pub enum Forge {
+    #[default]
+    GitHub,
+    GitLab,
+    /// Forgejo and Gitea, which share their API.
+    Gitea,
+    /// An executable that speaks the plugin protocol on stdio.
+    Plugin {
+        command: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Name {
+    GitHub,
+    GitLab,
+    #[serde(alias = "forgejo")]
+    Gitea,
+}
+
+/// How `spr.forge` is written in the config.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+enum Setting {
+    Name(Name),
+    Plugin { command: String },
+}
+
+impl From<Setting> for Forge {
+    fn from(setting: Setting) -> Self {
+        match setting {
+            Setting::Name(Name::GitHub) => Forge::GitHub,
+            Setting::Name(Name::GitLab) => Forge::GitLab,
+            Setting::Name(Name::Gitea) => Forge::Gitea,
+            Setting::Plugin { command } => Forge::Plugin { command },
+        }
+    }
+}
+
+impl From<Forge> for Setting {
+    fn from(forge: Forge) -> Self {
+        match forge {
+            Forge::GitHub => Setting::Name(Name::GitHub),
+            Forge::GitLab => Setting::Name(Name::GitLab),
+            Forge::Gitea => Setting::Name(Name::Gitea),
+            Forge::Plugin { command } => Setting::Plugin { command },
+        }
+    }
+}
+
+impl Forge {
+    /// Guess the forge from the host of the remote, for when `spr.forge` isn't set.
+    pub fn from_host(host: &str) -> Self {
+        let host = host.to_lowercase();
+        if host.contains("gitlab") {
+            Forge::GitLab
+        } else if ["gitea", "forgejo", "codeberg.org"]
+            .iter()
+            .any(|name| host.contains(name))
+        {
+            Forge::Gitea
+        } else {
+            Forge::GitHub
+        }
+    }
+
+    /// The host of the public instance.
+    pub fn default_host(&self) -> &'static str {
+        match self {
+            Forge::GitHub => "github.com",
+            Forge::GitLab => "gitlab.com",
+            Forge::Gitea => "codeberg.org",
+            // Plugins have no website that spr knows about.
+            Forge::Plugin { .. } => "",
+        }
+    }
+}
+
+/// Host and path of a remote URL, like `git@host:owner/repo.git`,
+/// `ssh://git@host:22/owner/repo` or `https://host/owner/repo`.
+pub fn host_and_path(url: &str) -> Option<(&str, &str)> {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let (scheme, rest) = match url.split_once("://") {
+        Some((_, rest)) => (true, rest),
+        None => (false, url),
+    };
+    // Without a scheme, whatever comes before the user is dropped, like the directory jj puts in
+    // front of `git@host/owner/repo` when it takes it for a relative path.
+    let rest = match rest.split_once('@') {
+        Some((user, rest)) if !scheme || !user.contains('/') => rest,
+        _ => rest,
+    };
+    let end = rest.find([':', '/'])?;
+    let (host, path) = rest.split_at(end);
+    let path = match path.strip_prefix(':') {
+        // Only URLs with a scheme have ports, in scp-like ones the path follows the colon.
+        Some(path) if scheme => path.trim_start_matches(|c: char| c.is_ascii_digit()),
+        Some(path) => path,
+        None => path,
+    };
+    let path = path.trim_start_matches('/');
+    match host.is_empty() || path.is_empty() {
+        true => None,
+        false => Some((host, path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Forge, host_and_path};
+
+    #[test]
+    fn splits_remote_urls() {
+        assert_eq!(
+            host_and_path("git@github.com:Ongy/jj-spr.git"),
+            Some(("github.com", "Ongy/jj-spr"))
+        );
+        assert_eq!(
+            host_and_path("git@github.com/Ongy/jj-spr.git"),
+            Some(("github.com", "Ongy/jj-spr"))
+        );
+        assert_eq!(
+            host_and_path("https://gitlab.example.com/group/sub/project/"),
+            Some(("gitlab.example.com", "group/sub/project"))
+        );
+        assert_eq!(
+            host_and_path("ssh://git@gitlab.example.com:2222/group/project.git"),
+            Some(("gitlab.example.com", "group/project"))
+        );
+        assert_eq!(
+            host_and_path("/tmp/clone/git@github.com/Ongy/jj-spr.git"),
+            Some(("github.com", "Ongy/jj-spr"))
+        );
+        assert_eq!(host_and_path("/some/local/path"), None);
+    }
+
+    #[test]
+    fn guesses_forge() {
+        assert_eq!(Forge::from_host("gitlab.example.com"), Forge::GitLab);
+        assert_eq!(Forge::from_host("codeberg.org"), Forge::Gitea);
+        assert_eq!(Forge::from_host("github.com"), Forge::GitHub);
+    }
+
+    #[test]
+    fn parses_forge_setting() {
+        #[derive(serde::Deserialize)]
+        struct Spr {
+            forge: Forge,
+        }
+
+        let parse = |text: &str| toml::from_str::<Spr>(text).map(|spr| spr.forge).ok();
+        assert_eq!(parse(r#"forge = "forgejo""#), Some(Forge::Gitea));
+        assert_eq!(parse(r#"forge = "gitlab""#), Some(Forge::GitLab));
+        assert_eq!(
+            parse(r#"forge = { command = "review-plugin" }"#),
+            Some(Forge::Plugin {
+                command: String::from("review-plugin")
+            })
+        );
+        assert_eq!(parse(r#"forge = "gerrit""#), None);
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+fn default_method() -> crate::github::MergeMethod {
+    crate::github::MergeMethod::Squash
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LandConfig {
+    #[serde(default = "default_method")]
+    pub method: crate::github::MergeMethod,
+}
+
+impl Default for LandConfig {
+    fn default() -> Self {
+        Self {
+            method: default_method(),
+        }
+    }
+}
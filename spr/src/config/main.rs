@@ -7,17 +7,24 @@
 
 use std::collections::HashSet;
 
+use super::forge::Forge;
 use crate::{error::ResultExt, utils::slugify};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 struct ParseConfig {
+    #[serde(default)]
+    forge: Option<Forge>,
     #[serde(default)]
     drawing: super::drawing::Drawing,
     #[serde(default)]
     icons: super::icons::Icons,
     #[serde(default)]
     push: super::push::PushConfig,
+    #[serde(default)]
+    land: super::land::LandConfig,
+    #[serde(default)]
+    watch: super::watch::WatchConfig,
 }
 
 // Both `jj config list` and `jj config get` return valid yaml.
@@ -30,6 +37,16 @@ struct ConfigWrapper {
     spr: ParseConfig,
 }
 
+/// A fork of the repository that PR branches are pushed to, for contributors who can't push to
+/// the repository itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fork {
+    /// Name of the remote of the fork.
+    pub remote: String,
+    pub owner: String,
+    pub repo: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
     pub owner: String,
@@ -37,10 +54,19 @@ pub struct Config {
     pub remote_name: String,
     pub master_ref: String,
     pub branch_prefix: String,
+    pub forge: Forge,
+    /// Host of the forge's website, like `github.com` or a self-hosted GitLab.
+    pub host: String,
+    /// Where PR branches go, if not to `remote_name`.
+    pub fork: Option<Fork>,
+    /// Login of the user spr acts for, if something needed it.
+    pub user: Option<String>,
 
     pub drawing: super::drawing::Drawing,
     pub icons: super::icons::Icons,
     pub push: super::push::PushConfig,
+    pub land: super::land::LandConfig,
+    pub watch: super::watch::WatchConfig,
 }
 
 impl Config {
@@ -53,6 +79,8 @@ impl Config {
         drawing: super::drawing::Drawing,
         icons: super::icons::Icons,
         push: super::push::PushConfig,
+        land: super::land::LandConfig,
+        watch: super::watch::WatchConfig,
     ) -> Self {
         Self {
             owner,
@@ -60,20 +88,91 @@ impl Config {
             remote_name,
             master_ref,
             branch_prefix,
+            forge: Forge::GitHub,
+            host: String::from("github.com"),
+            fork: None,
+            user: None,
             drawing,
             icons,
             push,
+            land,
+            watch,
+        }
+    }
+
+    pub fn with_forge<S: Into<String>>(mut self, forge: Forge, host: S) -> Self {
+        self.forge = forge;
+        self.host = host.into();
+        self
+    }
+
+    pub fn with_fork(mut self, fork: Option<Fork>) -> Self {
+        self.fork = fork;
+        self
+    }
+
+    pub fn with_user(mut self, user: Option<String>) -> Self {
+        self.user = user;
+        self
+    }
+
+    /// The remote PR branches are pushed to.
+    pub fn push_remote(&self) -> &str {
+        self.fork
+            .as_ref()
+            .map_or(&self.remote_name, |fork| &fork.remote)
+    }
+
+    /// The remote that has `branch`: the fork, if the branch was pushed there, otherwise
+    /// upstream.
+    pub fn branch_remote(&self, git_repo: &git2::Repository, branch: &str) -> &str {
+        match self.fork {
+            Some(ref fork)
+                if git_repo
+                    .find_reference(&format!("refs/remotes/{}/{}", fork.remote, branch))
+                    .is_ok() =>
+            {
+                &fork.remote
+            }
+            _ => &self.remote_name,
+        }
+    }
+
+    /// How a new PR names its head branch. Branches in a fork are qualified with its owner.
+    pub fn head_label(&self, branch: &str) -> String {
+        match self.fork {
+            Some(ref fork) => format!("{}:{}", fork.owner, branch),
+            None => String::from(branch),
         }
     }
 
     pub fn pull_request_url(&self, number: u64) -> String {
-        format!(
-            "https://github.com/{owner}/{repo}/pull/{number}",
-            owner = &self.owner,
-            repo = &self.repo
-        )
+        match self.forge {
+            Forge::GitHub => format!(
+                "https://{host}/{owner}/{repo}/pull/{number}",
+                host = &self.host,
+                owner = &self.owner,
+                repo = &self.repo
+            ),
+            Forge::GitLab => format!(
+                "https://{host}/{owner}/{repo}/-/merge_requests/{number}",
+                host = &self.host,
+                owner = &self.owner,
+                repo = &self.repo
+            ),
+            Forge::Gitea => format!(
+                "https://{host}/{owner}/{repo}/pulls/{number}",
+                host = &self.host,
+                owner = &self.owner,
+                repo = &self.repo
+            ),
+            // Where the plugin's review tool shows PRs is up to the plugin.
+            Forge::Plugin { .. } => format!("#{}", number),
+        }
     }
 
+    /// Number of the PR a `Pull Request` section names: a bare number, or the URL of a PR of
+    /// this repository on its forge. URLs of other hosts or repositories don't count.
     pub fn parse_pull_request_field(&self, text: &str) -> Option<u64> {
         if text.is_empty() {
             return None;
@@ -85,15 +184,23 @@ impl Config {
             return Some(caps.get(1).unwrap().as_str().parse().unwrap());
         }
 
-        let regex = lazy_regex::regex!(
-            r#"^\s*https?://github.com/([\w\-\.]+)/([\w\-\.]+)/pull/(\d+)([/?#].*)?\s*$"#
-        );
-        let m = regex.captures(text);
-        if let Some(caps) = m
-        //            && self.owner == caps.get(1).unwrap().as_str()
-        //            && self.repo == caps.get(2).unwrap().as_str()
-        {
-            return Some(caps.get(3).unwrap().as_str().parse().unwrap());
+        let regex = match self.forge {
+            Forge::GitHub => lazy_regex::regex!(
+                r#"^\s*https?://([^/\s]+)/([\w\-\.]+/[\w\-\.]+)/pull/(\d+)([/?#].*)?\s*$"#
+            ),
+            Forge::GitLab => lazy_regex::regex!(
+                r#"^\s*https?://([^/\s]+)/([\w\-\./]+)/-/merge_requests/(\d+)([/?#].*)?\s*$"#
+            ),
+            Forge::Gitea => lazy_regex::regex!(
+                r#"^\s*https?://([^/\s]+)/([\w\-\.]+/[\w\-\.]+)/pulls/(\d+)([/?#].*)?\s*$"#
+            ),
+            // Plugins only name PRs by number.
+            Forge::Plugin { .. } => return None,
+        };
+        let caps = regex.captures(text)?;
+        let repository = format!("{}/{}", self.owner, self.repo);
+        if caps[1].eq_ignore_ascii_case(&self.host) && caps[2].eq_ignore_ascii_case(&repository) {
+            return Some(caps[3].parse().unwrap());
         }
 
         None
@@ -115,7 +222,7 @@ impl Config {
     }
 
     fn find_unused_branch_name(&self, existing_ref_names: &HashSet<String>, slug: &str) -> String {
-        let remote_name = &self.remote_name;
+        let remote_name = self.push_remote();
         let branch_prefix = &self.branch_prefix;
         let mut branch_name = format!("{branch_prefix}{slug}");
         let mut suffix = 0;
@@ -177,40 +284,131 @@ pub fn remote_from_jj(jj: &crate::jj::Jujutsu) -> crate::error::Result<String> {
     })
 }
 
-pub fn repo_and_owner_from_jj(
-    jj: &crate::jj::Jujutsu,
-    remote_name: &str,
-) -> crate::error::Result<(String, String)> {
-    let remote_info = jj
+fn remote_url_from_jj(jj: &crate::jj::Jujutsu, remote_name: &str) -> crate::error::Result<String> {
+    Ok(jj
         .git_remote_list()?
         .lines()
         .find(|line| line.starts_with(&remote_name))
         .and_then(|s| s.split(' ').last().map(|s| String::from(s)))
-        .unwrap_or(String::from(""));
+        .unwrap_or(String::from("")))
+}
 
-    let repo_with_owner = value_from_jj(jj, "spr.githubRepository").or_else(|_| {
-        let no_suffix = remote_info
-            .strip_suffix(".git")
-            .unwrap_or(remote_info.as_str());
-        if let Some(index) = no_suffix.find("github.com") {
-            if let Some((_, path)) = no_suffix.split_at_checked(index + "github.com".len() + 1) {
-                Ok(String::from(path))
-            } else {
-                Err(crate::error::Error::new(format!(
-                    "Couldn't split along 'github.com' in {}",
+fn forge_and_host(
+    configured: Option<Forge>,
+    github_host: Option<String>,
+    remote_info: &str,
+) -> (Forge, String) {
+    // `spr.githubHost` puts the repository on GitHub Enterprise Server, unless another forge is
+    // configured.
+    if let Some(host) = github_host
+        && configured
+            .as_ref()
+            .is_none_or(|forge| *forge == Forge::GitHub)
+    {
+        return (Forge::GitHub, host);
+    }
+    let host = super::forge::host_and_path(remote_info).map(|(host, _)| String::from(host));
+    let forge =
+        configured.unwrap_or_else(|| host.as_deref().map_or(Forge::GitHub, Forge::from_host));
+    let host = host.unwrap_or_else(|| String::from(forge.default_host()));
+    (forge, host)
+}
+
+fn github_host_from_jj(jj: &crate::jj::Jujutsu) -> Option<String> {
+    value_from_jj(jj, "spr.githubHost")
+        .ok()
+        .filter(|host| !host.is_empty())
+}
+
+/// The forge hosting the repository, and its host. `spr.forge` picks the forge, otherwise it is
+/// guessed from `spr.githubHost` or the URL of the remote.
+pub fn forge_from_jj(
+    jj: &crate::jj::Jujutsu,
+    remote_name: &str,
+) -> crate::error::Result<(Forge, String)> {
+    let parsed = parsed_from_jj(jj).context(String::from("read full config from jj"))?;
+    Ok(forge_and_host(
+        parsed.forge,
+        github_host_from_jj(jj),
+        &remote_url_from_jj(jj, remote_name)?,
+    ))
+}
+
+/// Base URL of the REST API of GitHub on `host`. GitHub Enterprise Server serves it under
+/// `/api/v3`.
+pub fn default_github_api_url(host: &str) -> String {
+    match host {
+        "github.com" => String::from("https://api.github.com"),
+        host => format!("https://{}/api/v3", host),
+    }
+}
+
+/// An API URL set in `key`, without a trailing slash.
+fn api_url_from_jj(jj: &crate::jj::Jujutsu, key: &str) -> Option<String> {
+    value_from_jj(jj, key)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .map(|url| String::from(url.trim_end_matches('/')))
+}
+
+/// Base URL of the REST API: `spr.githubApiUrl`, or the default one of `host`.
+pub fn github_api_url(jj: &crate::jj::Jujutsu, host: &str) -> String {
+    api_url_from_jj(jj, "spr.githubApiUrl").unwrap_or_else(|| default_github_api_url(host))
+}
+
+/// Base URL of the GitLab API: `spr.gitlabApiUrl`, or `/api/v4` on `host`.
+pub fn gitlab_api_url(jj: &crate::jj::Jujutsu, host: &str) -> String {
+    api_url_from_jj(jj, "spr.gitlabApiUrl").unwrap_or_else(|| format!("https://{}/api/v4", host))
+}
+
+/// Base URL of the Forgejo or Gitea API: `spr.giteaApiUrl`, or `/api/v1` on `host`.
+pub fn gitea_api_url(jj: &crate::jj::Jujutsu, host: &str) -> String {
+    api_url_from_jj(jj, "spr.giteaApiUrl").unwrap_or_else(|| format!("https://{}/api/v1", host))
+}
+
+/// Base URL of the GraphQL API. GitHub Enterprise Server serves it at `/api/graphql`, next to
+/// the REST API, while github.com serves both from the same base.
+pub fn github_graphql_url(api_url: &str) -> String {
+    String::from(api_url.strip_suffix("/v3").unwrap_or(api_url))
+}
+
+pub fn repo_and_owner_from_jj(
+    jj: &crate::jj::Jujutsu,
+    remote_name: &str,
+    forge: Forge,
+) -> crate::error::Result<(String, String)> {
+    let remote_info = remote_url_from_jj(jj, remote_name)?;
+
+    let repo_with_owner = match value_from_jj(jj, "spr.githubRepository") {
+        Ok(value) => value,
+        Err(_) => match super::forge::host_and_path(&remote_info) {
+            Some((_, path)) => String::from(path),
+            None => {
+                return Err(crate::error::Error::new(format!(
+                    "Couldn't find the repository in {}",
                     remote_info
-                )))
+                )));
             }
-        } else {
-            Err(crate::error::Error::new(format!(
-                "Couldn't find 'github.com' in {}",
-                remote_info
-            )))
-        }
-    })?;
-    let components: Vec<_> = std::path::Path::new(repo_with_owner.as_str())
-        .components()
-        .collect();
+        },
+    };
+    split_repository(&repo_with_owner, forge)
+}
+
+/// Split `owner/repo` into repo and owner.
+fn split_repository(repo_with_owner: &str, forge: Forge) -> crate::error::Result<(String, String)> {
+    // GitLab projects can be nested in subgroups, which all belong to the owner. Plugins may use
+    // any path as well.
+    if matches!(forge, Forge::GitLab | Forge::Plugin { .. }) {
+        return match repo_with_owner.trim_matches('/').rsplit_once('/') {
+            Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() => {
+                Ok((repo.into(), owner.into()))
+            }
+            _ => Err(crate::error::Error::new(
+                "Unexpected string for owner and repo...",
+            )),
+        };
+    }
+    let components: Vec<_> = std::path::Path::new(repo_with_owner).components().collect();
     match (
         components.get(0).and_then(|c| c.as_os_str().to_str()),
         components.get(1).and_then(|c| c.as_os_str().to_str()),
@@ -256,12 +454,23 @@ pub async fn from_jj<F: AsyncFnOnce() -> crate::error::Result<String>>(
     let parsed = parsed_from_jj(jj).context(String::from("read full config from jj"))?;
 
     let remote_name = remote_from_jj(jj)?;
-    let branch_prefix = match value_from_jj(jj, "spr.branchPrefix") {
-        Ok(val) => Ok(val),
-        Err(_) => user().await.map(|u| format!("spr/{}/", u)),
-    }?;
+    // Code owners are matched against the user, so it is looked up for them as well.
+    let (branch_prefix, user) = match value_from_jj(jj, "spr.branchPrefix") {
+        Ok(val) if parsed.push.codeowners == super::push::CodeOwnersMode::Off => (val, None),
+        Ok(val) => (val, Some(user().await?)),
+        Err(_) => {
+            let user = user().await?;
+            (format!("spr/{}/", user), Some(user))
+        }
+    };
     let master_branch = default_branch_from_jj(jj)?;
-    let (repo, owner) = repo_and_owner_from_jj(jj, remote_name.as_ref())?;
+    let (forge, host) = forge_and_host(
+        parsed.forge,
+        github_host_from_jj(jj),
+        &remote_url_from_jj(jj, &remote_name)?,
+    );
+    let (repo, owner) = repo_and_owner_from_jj(jj, remote_name.as_ref(), forge.clone())?;
+    let fork = fork_from_jj(jj, &forge)?;
 
     Ok(Config::new(
         owner,
@@ -272,7 +481,40 @@ pub async fn from_jj<F: AsyncFnOnce() -> crate::error::Result<String>>(
         parsed.drawing,
         parsed.icons,
         parsed.push,
-    ))
+        parsed.land,
+        parsed.watch,
+    )
+    .with_forge(forge, host)
+    .with_fork(fork)
+    .with_user(user))
+}
+
+/// The fork in `spr.pushRemote`, if PR branches don't go to the repository itself.
+fn fork_from_jj(jj: &crate::jj::Jujutsu, forge: &Forge) -> crate::error::Result<Option<Fork>> {
+    let Some(remote) = value_from_jj(jj, "spr.pushRemote")
+        .ok()
+        .filter(|remote| !remote.is_empty())
+    else {
+        return Ok(None);
+    };
+    if *forge == Forge::GitLab {
+        return Err(crate::error::Error::new(
+            "spr.pushRemote isn't supported on GitLab, which opens merge requests from forks by project",
+        ));
+    }
+    let url = remote_url_from_jj(jj, &remote)?;
+    let Some((_, path)) = super::forge::host_and_path(&url) else {
+        return Err(crate::error::Error::new(format!(
+            "Couldn't find the fork in the URL of remote {}, set in spr.pushRemote",
+            remote
+        )));
+    };
+    let (repo, owner) = split_repository(path, forge.clone())?;
+    Ok(Some(Fork {
+        remote,
+        owner,
+        repo,
+    }))
 }
 
 pub enum AuthTokenSource {
@@ -314,6 +556,25 @@ pub fn get_auth_token_with_source(git_config: &git2::Config) -> Option<AuthToken
     }
 }
 
+fn token_from(key: &str, variable: &str, git_config: &git2::Config) -> Option<String> {
+    get_config_value(key, git_config).or_else(|| {
+        std::env::var(variable)
+            .ok()
+            .filter(|token| !token.is_empty())
+    })
+}
+
+/// Token for GitLab: `spr.gitlabAuthToken`, or the `GITLAB_TOKEN` environment variable that
+/// `glab` uses as well.
+pub fn get_gitlab_auth_token(git_config: &git2::Config) -> Option<String> {
+    token_from("spr.gitlabAuthToken", "GITLAB_TOKEN", git_config)
+}
+
+/// Token for Forgejo and Gitea: `spr.giteaAuthToken`, or the `GITEA_TOKEN` environment variable.
+pub fn get_gitea_auth_token(git_config: &git2::Config) -> Option<String> {
+    token_from("spr.giteaAuthToken", "GITEA_TOKEN", git_config)
+}
+
 // Helper function to get config value from jj first, then git
 pub fn get_config_value(key: &str, git_config: &git2::Config) -> Option<String> {
     // Try jj config first
@@ -504,29 +765,135 @@ mod tests {
         let gh = testing::config::basic();
 
         assert_eq!(
-            gh.parse_pull_request_field("https://github.com/acme/codez/pull/123"),
+            gh.parse_pull_request_field("https://github.com/test_owner/test_repo/pull/123"),
             Some(123)
         );
         assert_eq!(
-            gh.parse_pull_request_field("  https://github.com/acme/codez/pull/123  "),
+            gh.parse_pull_request_field("  https://github.com/test_owner/test_repo/pull/123  "),
             Some(123)
         );
         assert_eq!(
-            gh.parse_pull_request_field("https://github.com/acme/codez/pull/123/"),
+            gh.parse_pull_request_field("https://github.com/test_owner/test_repo/pull/123/"),
             Some(123)
         );
         assert_eq!(
-            gh.parse_pull_request_field("https://github.com/acme/codez/pull/123?x=a"),
+            gh.parse_pull_request_field("https://github.com/test_owner/test_repo/pull/123?x=a"),
             Some(123)
         );
         assert_eq!(
-            gh.parse_pull_request_field("https://github.com/acme/codez/pull/123/foo"),
+            gh.parse_pull_request_field("https://github.com/test_owner/test_repo/pull/123/foo"),
             Some(123)
         );
         assert_eq!(
-            gh.parse_pull_request_field("https://github.com/acme/codez/pull/123#abc"),
+            gh.parse_pull_request_field("https://github.com/test_owner/test_repo/pull/123#abc"),
             Some(123)
         );
+        assert_eq!(
+            gh.parse_pull_request_field("https://github.com/acme/codez/pull/123"),
+            None,
+            "PRs of other repositories don't count"
+        );
+        assert_eq!(
+            gh.parse_pull_request_field("https://evil.example/test_owner/test_repo/pull/123"),
+            None,
+            "PRs on other hosts don't count"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_merge_request_url() {
+        let gl = testing::config::basic()
+            .with_forge(crate::config::forge::Forge::GitLab, "gitlab.example.com");
+
+        assert_eq!(
+            &gl.pull_request_url(123),
+            "https://gitlab.example.com/test_owner/test_repo/-/merge_requests/123"
+        );
+        assert_eq!(
+            gl.parse_pull_request_field(&gl.pull_request_url(123)),
+            Some(123)
+        );
+        assert_eq!(
+            gl.parse_pull_request_field(
+                "https://gitlab.example.com/test_owner/test_repo/-/merge_requests/7/diffs"
+            ),
+            Some(7)
+        );
+        assert_eq!(
+            gl.parse_pull_request_field(
+                "https://gitlab.example.com/group/sub/project/-/merge_requests/7/diffs"
+            ),
+            None
+        );
+        assert_eq!(
+            gl.parse_pull_request_field("https://gitlab.example.com/test_owner/test_repo/pull/7"),
+            None,
+            "GitHub URLs don't name merge requests"
+        );
+    }
+
+    #[test]
+    fn test_github_enterprise_urls() {
+        let ghes = testing::config::basic()
+            .with_forge(crate::config::forge::Forge::GitHub, "git.corp.example");
+
+        assert_eq!(
+            &ghes.pull_request_url(7),
+            "https://git.corp.example/test_owner/test_repo/pull/7"
+        );
+        assert_eq!(
+            ghes.parse_pull_request_field(&ghes.pull_request_url(7)),
+            Some(7)
+        );
+        assert_eq!(
+            super::default_github_api_url("github.com"),
+            "https://api.github.com"
+        );
+        assert_eq!(
+            super::default_github_api_url("git.corp.example"),
+            "https://git.corp.example/api/v3"
+        );
+        assert_eq!(
+            super::github_graphql_url("https://git.corp.example/api/v3"),
+            "https://git.corp.example/api"
+        );
+        assert_eq!(
+            super::github_graphql_url("https://api.github.com"),
+            "https://api.github.com"
+        );
+    }
+
+    #[test]
+    fn test_fork() {
+        let config = testing::config::basic();
+        assert_eq!(config.push_remote(), "origin");
+        assert_eq!(config.head_label("spr/test/branch"), "spr/test/branch");
+
+        let config = config.with_fork(Some(super::Fork {
+            remote: String::from("fork"),
+            owner: String::from("contributor"),
+            repo: String::from("test_repo"),
+        }));
+        assert_eq!(config.push_remote(), "fork");
+        assert_eq!(
+            config.head_label("spr/test/branch"),
+            "contributor:spr/test/branch"
+        );
+    }
+
+    #[test]
+    fn test_gitea_pull_request_url() {
+        let gt =
+            testing::config::basic().with_forge(crate::config::forge::Forge::Gitea, "codeberg.org");
+
+        assert_eq!(
+            &gt.pull_request_url(42),
+            "https://codeberg.org/test_owner/test_repo/pulls/42"
+        );
+        assert_eq!(
+            gt.parse_pull_request_field(&gt.pull_request_url(42)),
+            Some(42)
+        );
     }
 
     mod from_jj {
@@ -633,6 +1000,162 @@ mod tests {
                 "Failed to read target branch from config"
             );
         }
+
+        #[tokio::test]
+        async fn gitlab() {
+            let (_tmpdir, mut jj, _) = testing::setup::repo_with_origin();
+
+            jj.git_remote_remove("origin")
+                .expect("Failed to remove origin");
+            jj.git_remote_add("origin", "git@gitlab.example.com:group/sub/project.git")
+                .expect("Failed to add origin");
+            jj.config_set("revset-aliases.\"trunk()\"", "main@origin", false)
+                .expect("Failed to set trunk alias.");
+
+            let config = super::from_jj(&jj, async || Ok(String::from("user")))
+                .await
+                .expect("Failed to guess config from jj");
+
+            assert_eq!(config.forge, crate::config::forge::Forge::GitLab);
+            assert_eq!(config.host, "gitlab.example.com");
+            assert_eq!(config.owner, "group/sub", "Failed to guess owner of repo");
+            assert_eq!(config.repo, "project", "Failed to guess repo name");
+            assert_eq!(
+                super::gitlab_api_url(&jj, &config.host),
+                "https://gitlab.example.com/api/v4"
+            );
+
+            jj.config_set("spr.gitlabApiUrl", "http://localhost:8080/api/v4/", false)
+                .expect("Failed to set API URL config");
+            assert_eq!(
+                super::gitlab_api_url(&jj, &config.host),
+                "http://localhost:8080/api/v4"
+            );
+        }
+
+        #[tokio::test]
+        async fn forge_from_config() {
+            let (_tmpdir, mut jj, _) = testing::setup::repo_with_origin();
+
+            jj.git_remote_remove("origin")
+                .expect("Failed to remove origin");
+            jj.git_remote_add("origin", "https://code.example.com/team/project.git")
+                .expect("Failed to add origin");
+            jj.config_set("revset-aliases.\"trunk()\"", "main@origin", false)
+                .expect("Failed to set trunk alias.");
+            jj.config_set("spr.forge", "gitlab", false)
+                .expect("Failed to set forge config");
+
+            let config = super::from_jj(&jj, async || Ok(String::from("user")))
+                .await
+                .expect("Failed to guess config from jj");
+
+            assert_eq!(config.forge, crate::config::forge::Forge::GitLab);
+            assert_eq!(config.host, "code.example.com");
+            assert_eq!(
+                config.pull_request_url(3),
+                "https://code.example.com/team/project/-/merge_requests/3"
+            );
+        }
+
+        #[tokio::test]
+        async fn github_enterprise() {
+            let (_tmpdir, mut jj, _) = testing::setup::repo_with_origin();
+
+            jj.git_remote_remove("origin")
+                .expect("Failed to remove origin");
+            jj.git_remote_add("origin", "git@ghe:team/project.git")
+                .expect("Failed to add origin");
+            jj.config_set("revset-aliases.\"trunk()\"", "main@origin", false)
+                .expect("Failed to set trunk alias.");
+            jj.config_set("spr.githubHost", "git.corp.example", false)
+                .expect("Failed to set host config");
+
+            let config = super::from_jj(&jj, async || Ok(String::from("user")))
+                .await
+                .expect("Failed to guess config from jj");
+
+            assert_eq!(config.forge, crate::config::forge::Forge::GitHub);
+            assert_eq!(config.host, "git.corp.example");
+            assert_eq!(config.owner, "team", "Failed to guess owner of repo");
+            assert_eq!(config.repo, "project", "Failed to guess repo name");
+            assert_eq!(
+                super::github_api_url(&jj, &config.host),
+                "https://git.corp.example/api/v3"
+            );
+
+            jj.config_set("spr.githubApiUrl", "https://api.corp.example/", false)
+                .expect("Failed to set API URL config");
+            assert_eq!(
+                super::github_api_url(&jj, &config.host),
+                "https://api.corp.example"
+            );
+        }
+
+        #[tokio::test]
+        async fn fork() {
+            let (_tmpdir, mut jj, _) = testing::setup::repo_with_origin();
+
+            jj.git_remote_remove("origin")
+                .expect("Failed to remove origin");
+            jj.git_remote_add("origin", "git@github.com:upstream/project.git")
+                .expect("Failed to add origin");
+            jj.git_remote_add("fork", "git@github.com:contributor/project-fork.git")
+                .expect("Failed to add fork");
+            jj.config_set("revset-aliases.\"trunk()\"", "main@origin", false)
+                .expect("Failed to set trunk alias.");
+            jj.config_set("spr.pushRemote", "fork", false)
+                .expect("Failed to set push remote config");
+
+            let config = super::from_jj(&jj, async || Ok(String::from("user")))
+                .await
+                .expect("Failed to guess config from jj");
+
+            assert_eq!(config.remote_name, "origin");
+            assert_eq!(config.owner, "upstream", "Fork replaced the upstream owner");
+            assert_eq!(
+                config.fork,
+                Some(super::super::Fork {
+                    remote: String::from("fork"),
+                    owner: String::from("contributor"),
+                    repo: String::from("project-fork"),
+                })
+            );
+        }
+
+        #[tokio::test]
+        async fn gitea() {
+            let (_tmpdir, mut jj, _) = testing::setup::repo_with_origin();
+
+            jj.git_remote_remove("origin")
+                .expect("Failed to remove origin");
+            jj.git_remote_add("origin", "git@git.example.org:mirror/project.git")
+                .expect("Failed to add origin");
+            jj.config_set("revset-aliases.\"trunk()\"", "main@origin", false)
+                .expect("Failed to set trunk alias.");
+            jj.config_set("spr.forge", "forgejo", false)
+                .expect("Failed to set forge config");
+
+            let config = super::from_jj(&jj, async || Ok(String::from("user")))
+                .await
+                .expect("Failed to guess config from jj");
+
+            assert_eq!(config.forge, crate::config::forge::Forge::Gitea);
+            assert_eq!(config.host, "git.example.org");
+            assert_eq!(config.owner, "mirror", "Failed to guess owner of repo");
+            assert_eq!(config.repo, "project", "Failed to guess repo name");
+            assert_eq!(
+                super::gitea_api_url(&jj, &config.host),
+                "https://git.example.org/api/v1"
+            );
+
+            jj.config_set("spr.giteaApiUrl", "http://localhost:3000/api/v1", false)
+                .expect("Failed to set API URL config");
+            assert_eq!(
+                super::gitea_api_url(&jj, &config.host),
+                "http://localhost:3000/api/v1"
+            );
+        }
     }
 
     mod parsing {
@@ -2,5 +2,8 @@ mod main;
 pub use main::*;
 
 pub mod drawing;
+pub mod forge;
 pub mod icons;
+pub mod land;
 pub mod push;
+pub mod watch;
@@ -8,12 +8,50 @@ fn default_draft() -> bool {
     false
 }
 
+/// What `push` does with the code owners of the files a new PR touches.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeOwnersMode {
+    Off,
+    /// Print them as suggested reviewers
+    #[default]
+    Suggest,
+    /// Add them to the `Reviewers` section
+    Auto,
+}
+
+/// Derives a label for PRs. A rule applies if the revision matches all of its conditions.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LabelRule {
+    pub label: String,
+    /// Conventional commit type of the title, like `fix` in `fix(parser): Handle empty input`
+    #[serde(default, rename = "type")]
+    pub commit_type: Option<String>,
+    /// Applies if the revision touches a file matching any of these CODEOWNERS style globs
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+fn default_template_placeholder() -> String {
+    String::from("<!-- summary -->")
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PushConfig {
     #[serde(default = "default_autofix")]
     pub autofix: bool,
     #[serde(default = "default_draft")]
     pub draft: bool,
+    #[serde(default)]
+    pub codeowners: CodeOwnersMode,
+    /// Named pull request template for new PRs, unless a revision picks one in `Template`
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Where the summary goes in the template
+    #[serde(default = "default_template_placeholder")]
+    pub template_placeholder: String,
+    #[serde(default)]
+    pub labels: Vec<LabelRule>,
 }
 
 impl Default for PushConfig {
@@ -21,6 +59,10 @@ impl Default for PushConfig {
         Self {
             autofix: false,
             draft: false,
+            codeowners: CodeOwnersMode::default(),
+            template: None,
+            template_placeholder: default_template_placeholder(),
+            labels: Vec::new(),
         }
     }
 }
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WatchConfig {
+    /// Update the local revisions from their PRs when something changed
+    #[serde(default)]
+    pub fetch: bool,
+    /// Abandon landed revisions and rebase the rest when a PR was merged
+    #[serde(default)]
+    pub sync: bool,
+}
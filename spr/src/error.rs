@@ -39,6 +39,10 @@ impl Error {
     pub fn push(&mut self, message: String) {
         self.messages.push(message);
     }
+
+    pub fn extend(&mut self, other: Error) {
+        self.messages.extend(other.messages);
+    }
 }
 
 impl From<toml::de::Error> for Error {
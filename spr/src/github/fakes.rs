@@ -15,25 +15,45 @@ impl super::types::PullRequest {
             number,
             title: title.into(),
             body: body.into(),
-            _reviewers: Vec::new(),
-            _assignees: Vec::new(),
+            reviewers: Vec::new(),
+            assignees: Vec::new(),
+            reviewed_by: Vec::new(),
+            labels: Vec::new(),
             comments: Vec::new(),
             node: String::new(),
             closed: false,
+            merged: false,
             draft: false,
+            review_decision: None,
+            checks: None,
+            mergeable: super::Mergeable::Unknown,
+            reviewed_commit: None,
+            head_commit: String::new(),
+            review_count: 0,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GitHub {
     pub pull_requests: std::collections::BTreeMap<u64, super::types::PullRequest>,
+    /// Users that exist. `None` if any user does.
+    pub users: Option<Vec<String>>,
+    /// Teams that exist, as `org/team`.
+    pub teams: Vec<String>,
+    /// Owners of the forks that the heads of PRs are in, by PR number.
+    pub forks: std::collections::BTreeMap<u64, String>,
 }
 
 impl GitHub {
     pub fn new() -> Self {
-        Self {
-            pull_requests: std::collections::BTreeMap::new(),
+        Self::default()
+    }
+
+    fn exists(&self, reviewer: &super::Reviewer) -> bool {
+        match reviewer {
+            super::Reviewer::User(login) => self.users.as_ref().is_none_or(|u| u.contains(login)),
+            super::Reviewer::Team { .. } => self.teams.contains(&reviewer.to_string()),
         }
     }
 }
@@ -62,6 +82,35 @@ impl super::GitHubAdapter for &mut GitHub {
             })
     }
 
+    async fn pull_request_changed(&mut self, _number: u64) -> crate::error::Result<bool> {
+        Ok(true)
+    }
+
+    async fn open_pull_requests(&mut self) -> crate::error::Result<Vec<Self::PRAdapter>> {
+        Ok(self
+            .pull_requests
+            .values()
+            .filter(|pr| !pr.closed)
+            .cloned()
+            .collect())
+    }
+
+    async fn open_pull_requests_by_base<S>(
+        &mut self,
+        base: S,
+    ) -> crate::error::Result<Vec<Self::PRAdapter>>
+    where
+        S: Into<String>,
+    {
+        let base = base.into();
+        Ok(self
+            .pull_requests
+            .values()
+            .filter(|pr| pr.base == base && !pr.closed)
+            .cloned()
+            .collect())
+    }
+
     async fn new_pull_request<H, B, St, Sb>(
         &mut self,
         title: St,
@@ -82,14 +131,16 @@ impl super::GitHubAdapter for &mut GitHub {
             .map(|(k, _)| *k)
             .max()
             .unwrap_or(0);
-        let mut pr = Self::PRAdapter::new(
-            base_ref_name.as_ref(),
-            head_ref_name.as_ref(),
-            max + 1,
-            title,
-            body,
-        );
+        // Like GitHub, PRs only know the branch of an `owner:branch` head.
+        let (fork, head) = match head_ref_name.as_ref().split_once(':') {
+            Some((owner, branch)) => (Some(owner), branch),
+            None => (None, head_ref_name.as_ref()),
+        };
+        let mut pr = Self::PRAdapter::new(base_ref_name.as_ref(), head, max + 1, title, body);
         pr.draft = draft;
+        if let Some(owner) = fork {
+            self.forks.insert(pr.number, owner.to_string());
+        }
 
         self.pull_requests.insert(pr.number, pr.clone());
 
@@ -105,9 +156,22 @@ impl super::GitHubAdapter for &mut GitHub {
         S: Into<String>,
         I: IntoIterator<Item = S>,
     {
+        let reviewers: Vec<super::Reviewer> = reviewers
+            .into_iter()
+            .map(|s| super::Reviewer::parse(&s.into()))
+            .collect();
+        let unknown: Vec<String> = reviewers
+            .iter()
+            .filter(|reviewer| !self.exists(reviewer))
+            .map(|reviewer| reviewer.to_string())
+            .collect();
+        if !unknown.is_empty() {
+            return Err(super::traits::unknown_accounts(&unknown));
+        }
+
         if let Some(pr) = self.pull_requests.get_mut(&pr.number) {
-            pr._reviewers
-                .extend(reviewers.into_iter().map(|s| s.into()));
+            pr.reviewers
+                .extend(reviewers.iter().map(|reviewer| reviewer.to_string()));
         }
         Ok(())
     }
@@ -121,9 +185,116 @@ impl super::GitHubAdapter for &mut GitHub {
         S: Into<String>,
         I: IntoIterator<Item = S>,
     {
+        let assignees: Vec<String> = assignees.into_iter().map(|s| s.into()).collect();
+        let unknown: Vec<String> = assignees
+            .iter()
+            .filter(|login| !self.exists(&super::Reviewer::User(login.to_string())))
+            .cloned()
+            .collect();
+        if !unknown.is_empty() {
+            return Err(super::traits::unknown_accounts(&unknown));
+        }
+
         if let Some(pr) = self.pull_requests.get_mut(&pr.number) {
-            pr._assignees
-                .extend(assignees.into_iter().map(|s| s.into()));
+            pr.assignees.extend(assignees);
+        }
+        Ok(())
+    }
+
+    async fn remove_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let reviewers: Vec<super::Reviewer> = reviewers
+            .into_iter()
+            .map(|s| super::Reviewer::parse(&s.into()))
+            .collect();
+        if let Some(pr) = self.pull_requests.get_mut(&pr.number) {
+            pr.reviewers
+                .retain(|name| !reviewers.iter().any(|reviewer| reviewer.is(name)));
+        }
+        Ok(())
+    }
+
+    async fn remove_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let assignees: Vec<String> = assignees.into_iter().map(|s| s.into()).collect();
+        if let Some(pr) = self.pull_requests.get_mut(&pr.number) {
+            pr.assignees
+                .retain(|name| !assignees.iter().any(|a| a.eq_ignore_ascii_case(name)));
+        }
+        Ok(())
+    }
+
+    async fn add_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        if let Some(pr) = self.pull_requests.get_mut(&pr.number) {
+            for label in labels.into_iter().map(|s| s.into()) {
+                if !pr.labels.contains(&label) {
+                    pr.labels.push(label);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        if let Some(pr) = self.pull_requests.get_mut(&pr.number) {
+            let labels: Vec<String> = labels.into_iter().map(|s| s.into()).collect();
+            pr.labels.retain(|label| !labels.contains(label));
+        }
+        Ok(())
+    }
+
+    async fn edit_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+        title: Option<String>,
+        body: Option<String>,
+    ) -> crate::error::Result<()> {
+        let pr = self
+            .pull_requests
+            .get_mut(&pr.number)
+            .ok_or_else(|| crate::error::Error::new("No such PR"))?;
+        if let Some(title) = title {
+            pr.title = title;
+        }
+        if let Some(body) = body {
+            pr.body = body;
+        }
+        Ok(())
+    }
+
+    async fn set_draft(&mut self, pr: &Self::PRAdapter, draft: bool) -> crate::error::Result<()> {
+        if let Some(pr) = self.pull_requests.get_mut(&pr.number) {
+            pr.draft = draft;
         }
         Ok(())
     }
@@ -178,4 +349,67 @@ impl super::GitHubAdapter for &mut GitHub {
             .base = new_base.into();
         Ok(())
     }
+
+    async fn merge_pull_request<T, M>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        _method: super::MergeMethod,
+        _title: T,
+        _message: M,
+    ) -> crate::error::Result<()>
+    where
+        T: Into<String>,
+        M: Into<String>,
+    {
+        let pr = self
+            .pull_requests
+            .get_mut(&pr.number)
+            .ok_or_else(|| crate::error::Error::new("No such PR"))?;
+        if pr.closed {
+            return Err(crate::error::Error::new("PR is already closed"));
+        }
+
+        pr.closed = true;
+        pr.merged = true;
+        Ok(())
+    }
+
+    async fn close_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        let pr = self
+            .pull_requests
+            .get_mut(&pr.number)
+            .ok_or_else(|| crate::error::Error::new("No such PR"))?;
+        if pr.closed {
+            return Err(crate::error::Error::new("PR is already closed"));
+        }
+
+        pr.closed = true;
+        Ok(())
+    }
+
+    async fn reopen_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        let pr = self
+            .pull_requests
+            .get_mut(&pr.number)
+            .ok_or_else(|| crate::error::Error::new("No such PR"))?;
+        if !pr.closed || pr.merged {
+            return Err(crate::error::Error::new("PR isn't closed or was merged"));
+        }
+
+        pr.closed = false;
+        Ok(())
+    }
+
+    async fn delete_branch<S>(&mut self, branch: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        // The fake doesn't track branches, only make sure that a PR knew about it.
+        let branch = branch.into();
+        if self.pull_requests.values().any(|pr| pr.head == branch) {
+            Ok(())
+        } else {
+            Err(crate::error::Error::new("No such branch"))
+        }
+    }
 }
@@ -0,0 +1,900 @@
+//! Pull requests on Forgejo and Gitea, through their REST API. Like for GitLab, octocrab does the
+//! HTTP, with the instance's `/api/v1` as base URI.
+//!
+//! Drafts are a `WIP:` prefix on the title, which is hidden from the title of the PR. Teams that
+//! are asked for a review are named `owner/team`, like teams on GitHub.
+
+use serde::{Deserialize, Serialize};
+
+const DRAFT_PREFIX: &str = "WIP: ";
+const PAGE_SIZE: u64 = 50;
+
+#[derive(Debug, Clone, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Team {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Label {
+    id: u64,
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    name: String,
+    sha: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PullRequest {
+    id: u64,
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    #[serde(default)]
+    mergeable: bool,
+    head: Branch,
+    base: Branch,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    assignees: Option<Vec<User>>,
+    #[serde(default)]
+    requested_reviewers: Option<Vec<User>>,
+    #[serde(default)]
+    requested_reviewers_teams: Option<Vec<Team>>,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Review {
+    user: Option<User>,
+    state: String,
+    commit_id: Option<String>,
+    #[serde(default)]
+    dismissed: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Comment {
+    id: u64,
+    body: String,
+    user: User,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Status {
+    state: String,
+}
+
+/// Fields of a PR to change. Unset ones are left out of the request.
+#[derive(Debug, Default, Serialize)]
+struct Update {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    assignees: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReviewRequest {
+    reviewers: Vec<String>,
+    team_reviewers: Vec<String>,
+}
+
+/// The title without the prefixes Forgejo and Gitea use to mark drafts by default.
+fn strip_draft(title: &str) -> &str {
+    lazy_regex::regex_find!(r#"(?i)^\s*(wip:|\[wip\])\s*"#, title)
+        .map_or(title, |prefix| &title[prefix.len()..])
+}
+
+fn is_draft(title: &str) -> bool {
+    strip_draft(title).len() != title.len()
+}
+
+fn draft_title(title: &str, draft: bool) -> String {
+    match draft {
+        true => format!("{}{}", DRAFT_PREFIX, strip_draft(title)),
+        false => strip_draft(title).to_string(),
+    }
+}
+
+/// Login of the user the token belongs to.
+pub async fn current_user(crab: &octocrab::Octocrab) -> crate::error::Result<String> {
+    let user: User = crab.get("/user", None::<&()>).await?;
+    Ok(user.login)
+}
+
+/// Whether the token's user can push to `owner/repo`.
+pub async fn can_push(
+    crab: &octocrab::Octocrab,
+    owner: &str,
+    repo: &str,
+) -> crate::error::Result<bool> {
+    #[derive(Deserialize)]
+    struct Permissions {
+        push: bool,
+    }
+    #[derive(Deserialize)]
+    struct Repository {
+        permissions: Option<Permissions>,
+    }
+
+    let repository: Repository = crab
+        .get(
+            format!(
+                "/repos/{}/{}",
+                super::gitlab::encode(owner),
+                super::gitlab::encode(repo)
+            ),
+            None::<&()>,
+        )
+        .await?;
+    Ok(repository.permissions.is_some_and(|p| p.push))
+}
+
+#[derive(Clone)]
+pub struct Gitea {
+    config: crate::config::Config,
+    crab: octocrab::Octocrab,
+    /// Login of the user the token belongs to, to tell which comments are ours.
+    me: Option<String>,
+    /// Last update of the PRs polled through `pull_request_changed`.
+    updated: std::collections::HashMap<u64, String>,
+}
+
+impl Gitea {
+    pub fn new(config: crate::config::Config, crab: octocrab::Octocrab) -> Self {
+        Self {
+            config,
+            crab,
+            me: None,
+            updated: std::collections::HashMap::new(),
+        }
+    }
+
+    fn repo(&self) -> String {
+        format!(
+            "/repos/{}/{}",
+            super::gitlab::encode(&self.config.owner),
+            super::gitlab::encode(&self.config.repo)
+        )
+    }
+
+    /// Repository the PR branches are in, which is the fork if there is one.
+    fn branch_repo(&self) -> String {
+        match self.config.fork {
+            Some(ref fork) => format!(
+                "/repos/{}/{}",
+                super::gitlab::encode(&fork.owner),
+                super::gitlab::encode(&fork.repo)
+            ),
+            None => self.repo(),
+        }
+    }
+
+    fn pull(&self, number: u64) -> String {
+        format!("{}/pulls/{}", self.repo(), number)
+    }
+
+    /// All pages of a list.
+    async fn all<T, P>(&self, route: &str, parameters: &P) -> crate::error::Result<Vec<T>>
+    where
+        T: serde::de::DeserializeOwned,
+        P: Serialize,
+    {
+        #[derive(Serialize)]
+        struct Page<'a, P> {
+            #[serde(flatten)]
+            parameters: &'a P,
+            page: u64,
+            limit: u64,
+        }
+
+        let mut ret = Vec::new();
+        for page in 1.. {
+            let items: Vec<T> = self
+                .crab
+                .get(
+                    route,
+                    Some(&Page {
+                        parameters,
+                        page,
+                        limit: PAGE_SIZE,
+                    }),
+                )
+                .await?;
+            let done = (items.len() as u64) < PAGE_SIZE;
+            ret.extend(items);
+            if done {
+                break;
+            }
+        }
+        Ok(ret)
+    }
+
+    async fn me(&mut self) -> crate::error::Result<String> {
+        if let Some(ref me) = self.me {
+            return Ok(me.clone());
+        }
+        let me = current_user(&self.crab).await?;
+        self.me = Some(me.clone());
+        Ok(me)
+    }
+
+    /// The PR as it is now. Assignees and the draft prefix can only be set along with the rest of
+    /// their field, so changes start from this rather than from a PR that may have been read
+    /// before an earlier change.
+    async fn current(&self, number: u64) -> crate::error::Result<PullRequest> {
+        Ok(self.crab.get(self.pull(number), None::<&()>).await?)
+    }
+
+    async fn update(&self, number: u64, update: &Update) -> crate::error::Result<PullRequest> {
+        Ok(self.crab.patch(self.pull(number), Some(update)).await?)
+    }
+
+    /// The accounts among `names` that don't exist.
+    async fn unknown_users(&self, names: &[String]) -> crate::error::Result<Vec<String>> {
+        let mut unknown = Vec::new();
+        for name in names {
+            let response = self
+                .crab
+                ._get(format!("/users/{}", super::gitlab::encode(name)))
+                .await?;
+            if response.status().as_u16() == 404 {
+                unknown.push(name.clone());
+            } else {
+                octocrab::map_github_error(response).await?;
+            }
+        }
+        Ok(unknown)
+    }
+
+    /// Ids of the repository's labels called `names`.
+    async fn label_ids(&self, names: &[String]) -> crate::error::Result<Vec<u64>> {
+        let labels: Vec<Label> = self.all(&format!("{}/labels", self.repo()), &()).await?;
+        let mut ids = Vec::new();
+        let mut unknown = Vec::new();
+        for name in names {
+            match labels.iter().find(|l| l.name.eq_ignore_ascii_case(name)) {
+                Some(label) => ids.push(label.id),
+                None => unknown.push(name.as_str()),
+            }
+        }
+        match unknown.is_empty() {
+            true => Ok(ids),
+            false => Err(crate::error::Error::new(format!(
+                "These labels don't exist in the repository: {}",
+                unknown.join(", ")
+            ))),
+        }
+    }
+
+    async fn load(&mut self, pr: PullRequest) -> crate::error::Result<super::types::PullRequest> {
+        let me = self.me().await?;
+        let reviews: Vec<Review> = self
+            .all(&format!("{}/reviews", self.pull(pr.number)), &())
+            .await?;
+        // Comments of an issue come in one piece, the API doesn't page them.
+        let comments: Vec<Comment> = self
+            .crab
+            .get(
+                format!("{}/issues/{}/comments", self.repo(), pr.number),
+                None::<&()>,
+            )
+            .await?;
+        let status: Option<Status> = match self
+            .crab
+            .get(
+                format!("{}/commits/{}/status", self.repo(), pr.head.sha),
+                None::<&()>,
+            )
+            .await
+        {
+            Ok(status) => Some(status),
+            Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_u16() == 404 => {
+                None
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        // Only submitted reviews count, the latest one of each reviewer decides.
+        let reviews: Vec<Review> = reviews
+            .into_iter()
+            .filter(|r| !r.dismissed && r.user.is_some())
+            .filter(|r| matches!(r.state.as_str(), "APPROVED" | "REQUEST_CHANGES" | "COMMENT"))
+            .collect();
+        let mut latest: Vec<&Review> = Vec::new();
+        for review in reviews.iter().rev() {
+            let login = review.user.as_ref().map(|u| u.login.as_str());
+            if !latest
+                .iter()
+                .any(|r| r.user.as_ref().map(|u| u.login.as_str()) == login)
+            {
+                latest.push(review);
+            }
+        }
+
+        let mut reviewers: Vec<String> = pr
+            .requested_reviewers
+            .unwrap_or_default()
+            .into_iter()
+            .map(|u| u.login)
+            .collect();
+        reviewers.extend(
+            pr.requested_reviewers_teams
+                .unwrap_or_default()
+                .into_iter()
+                .map(|team| format!("{}/{}", self.config.owner, team.name)),
+        );
+        let review_decision = if latest.iter().any(|r| r.state == "REQUEST_CHANGES") {
+            Some(super::ReviewDecision::ChangesRequested)
+        } else if latest.iter().any(|r| r.state == "APPROVED") {
+            Some(super::ReviewDecision::Approved)
+        } else if !reviewers.is_empty() {
+            Some(super::ReviewDecision::ReviewRequired)
+        } else {
+            None
+        };
+        let checks = status.and_then(|status| match status.state.as_str() {
+            "success" => Some(super::CheckState::Success),
+            "pending" => Some(super::CheckState::Pending),
+            "error" | "failure" => Some(super::CheckState::Failure),
+            _ => None,
+        });
+        let mergeable = match (pr.state.as_str(), pr.mergeable) {
+            ("open", true) => super::Mergeable::Mergeable,
+            ("open", false) => super::Mergeable::Conflicting,
+            _ => super::Mergeable::Unknown,
+        };
+
+        Ok(super::types::PullRequest {
+            base: pr.base.name,
+            head: pr.head.name,
+            number: pr.number,
+            node: pr.id.to_string(),
+            draft: is_draft(&pr.title),
+            title: strip_draft(&pr.title).to_string(),
+            body: pr.body.unwrap_or_default(),
+            reviewers,
+            assignees: pr
+                .assignees
+                .unwrap_or_default()
+                .into_iter()
+                .map(|u| u.login)
+                .collect(),
+            reviewed_by: latest
+                .iter()
+                .filter_map(|r| r.user.as_ref().map(|u| u.login.clone()))
+                .collect(),
+            labels: pr.labels.into_iter().map(|l| l.name).collect(),
+            comments: comments
+                .into_iter()
+                .map(|c| super::types::PullRequestComment {
+                    editable: c.user.login.eq_ignore_ascii_case(&me),
+                    content: c.body,
+                    id: c.id.to_string(),
+                })
+                .collect(),
+            closed: pr.state == "closed",
+            merged: pr.merged,
+            review_decision,
+            checks,
+            mergeable,
+            reviewed_commit: reviews.last().and_then(|r| r.commit_id.clone()),
+            head_commit: pr.head.sha,
+            review_count: reviews.len(),
+        })
+    }
+
+    async fn open(&mut self) -> crate::error::Result<Vec<PullRequest>> {
+        #[derive(Serialize)]
+        struct Query {
+            state: &'static str,
+        }
+
+        self.all(&format!("{}/pulls", self.repo()), &Query { state: "open" })
+            .await
+    }
+
+    fn review_request(reviewers: Vec<String>) -> ReviewRequest {
+        let mut request = ReviewRequest {
+            reviewers: Vec::new(),
+            team_reviewers: Vec::new(),
+        };
+        for reviewer in reviewers.iter().map(|r| super::Reviewer::parse(r)) {
+            match reviewer {
+                super::Reviewer::User(login) => request.reviewers.push(login),
+                // Teams belong to the organization that owns the repository.
+                super::Reviewer::Team { slug, .. } => request.team_reviewers.push(slug),
+            }
+        }
+        request
+    }
+}
+
+impl super::GitHubAdapter for &mut Gitea {
+    type PRAdapter = super::types::PullRequest;
+
+    async fn pull_request(&mut self, number: u64) -> crate::error::Result<Self::PRAdapter> {
+        let pr: PullRequest = self.crab.get(self.pull(number), None::<&()>).await?;
+        self.load(pr).await
+    }
+
+    async fn pull_request_by_head<S>(&mut self, head: S) -> crate::error::Result<Self::PRAdapter>
+    where
+        S: Into<String>,
+    {
+        let head = head.into();
+        let mut prs: Vec<PullRequest> = self
+            .open()
+            .await?
+            .into_iter()
+            .filter(|pr| pr.head.name == head)
+            .collect();
+        if prs.len() > 1 {
+            return Err(crate::error::Error::new("Found more than one candidate PR"));
+        }
+        match prs.pop() {
+            Some(pr) => self.load(pr).await,
+            None => Err(crate::error::Error::new(format!(
+                "Couldn't find a PR for branch {}",
+                head
+            ))),
+        }
+    }
+
+    async fn pull_request_changed(&mut self, number: u64) -> crate::error::Result<bool> {
+        let pr: PullRequest = self.crab.get(self.pull(number), None::<&()>).await?;
+        let changed = self.updated.get(&number) != Some(&pr.updated_at);
+        self.updated.insert(number, pr.updated_at);
+        Ok(changed)
+    }
+
+    async fn open_pull_requests(&mut self) -> crate::error::Result<Vec<Self::PRAdapter>> {
+        let mut ret = Vec::new();
+        for pr in self.open().await? {
+            ret.push(self.load(pr).await?);
+        }
+        Ok(ret)
+    }
+
+    async fn open_pull_requests_by_base<S>(
+        &mut self,
+        base: S,
+    ) -> crate::error::Result<Vec<Self::PRAdapter>>
+    where
+        S: Into<String>,
+    {
+        // The API can't filter by base branch.
+        let base = base.into();
+        let mut ret = Vec::new();
+        for pr in self.open().await? {
+            if pr.base.name == base {
+                ret.push(self.load(pr).await?);
+            }
+        }
+        Ok(ret)
+    }
+
+    async fn new_pull_request<H, B, St, Sb>(
+        &mut self,
+        title: St,
+        body: Sb,
+        base_ref_name: B,
+        head_ref_name: H,
+        draft: bool,
+    ) -> crate::error::Result<Self::PRAdapter>
+    where
+        H: AsRef<str>,
+        B: AsRef<str>,
+        St: Into<String>,
+        Sb: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Create<'a> {
+            head: &'a str,
+            base: &'a str,
+            title: String,
+            body: String,
+        }
+
+        let pr: PullRequest = self
+            .crab
+            .post(
+                format!("{}/pulls", self.repo()),
+                Some(&Create {
+                    head: head_ref_name.as_ref(),
+                    base: base_ref_name.as_ref(),
+                    title: draft_title(&title.into(), draft),
+                    body: body.into(),
+                }),
+            )
+            .await?;
+        self.load(pr).await
+    }
+
+    async fn add_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let request = Gitea::review_request(reviewers.into_iter().map(|s| s.into()).collect());
+        let unknown = self.unknown_users(&request.reviewers).await?;
+        if !unknown.is_empty() {
+            return Err(super::traits::unknown_accounts(&unknown));
+        }
+        let response = self
+            .crab
+            ._post(
+                format!("{}/requested_reviewers", self.pull(pr.number)),
+                Some(&request),
+            )
+            .await?;
+        octocrab::map_github_error(response).await?;
+        Ok(())
+    }
+
+    async fn add_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let added: Vec<String> = assignees.into_iter().map(|s| s.into()).collect();
+        let unknown = self.unknown_users(&added).await?;
+        if !unknown.is_empty() {
+            return Err(super::traits::unknown_accounts(&unknown));
+        }
+        // The API only sets the whole list of assignees.
+        let mut assignees: Vec<String> = self
+            .current(pr.number)
+            .await?
+            .assignees
+            .unwrap_or_default()
+            .into_iter()
+            .map(|u| u.login)
+            .collect();
+        for login in added {
+            if !assignees.iter().any(|a| a.eq_ignore_ascii_case(&login)) {
+                assignees.push(login);
+            }
+        }
+        self.update(
+            pr.number,
+            &Update {
+                assignees: Some(assignees),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let request = Gitea::review_request(reviewers.into_iter().map(|s| s.into()).collect());
+        let response = self
+            .crab
+            ._delete(
+                format!("{}/requested_reviewers", self.pull(pr.number)),
+                Some(&request),
+            )
+            .await?;
+        octocrab::map_github_error(response).await?;
+        Ok(())
+    }
+
+    async fn remove_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let removed: Vec<String> = assignees.into_iter().map(|s| s.into()).collect();
+        let assignees = self
+            .current(pr.number)
+            .await?
+            .assignees
+            .unwrap_or_default()
+            .into_iter()
+            .map(|u| u.login)
+            .filter(|name| !removed.iter().any(|r| r.eq_ignore_ascii_case(name)))
+            .collect();
+        self.update(
+            pr.number,
+            &Update {
+                assignees: Some(assignees),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn add_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        #[derive(Serialize)]
+        struct Labels {
+            labels: Vec<u64>,
+        }
+
+        let labels: Vec<String> = labels.into_iter().map(|s| s.into()).collect();
+        let labels = self.label_ids(&labels).await?;
+        let response = self
+            .crab
+            ._post(
+                format!("{}/issues/{}/labels", self.repo(), pr.number),
+                Some(&Labels { labels }),
+            )
+            .await?;
+        octocrab::map_github_error(response).await?;
+        Ok(())
+    }
+
+    async fn remove_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let labels: Vec<String> = labels.into_iter().map(|s| s.into()).collect();
+        for id in self.label_ids(&labels).await? {
+            let response = self
+                .crab
+                ._delete(
+                    format!("{}/issues/{}/labels/{}", self.repo(), pr.number, id),
+                    None::<&()>,
+                )
+                .await?;
+            octocrab::map_github_error(response).await?;
+        }
+        Ok(())
+    }
+
+    async fn edit_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+        title: Option<String>,
+        body: Option<String>,
+    ) -> crate::error::Result<()> {
+        // A draft prefix added or removed since `pr` was read has to stay.
+        let title = match title {
+            Some(title) => Some(draft_title(
+                &title,
+                is_draft(&self.current(pr.number).await?.title),
+            )),
+            None => None,
+        };
+        self.update(
+            pr.number,
+            &Update {
+                title,
+                body,
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn set_draft(&mut self, pr: &Self::PRAdapter, draft: bool) -> crate::error::Result<()> {
+        self.update(
+            pr.number,
+            &Update {
+                title: Some(draft_title(&self.current(pr.number).await?.title, draft)),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn post_comment<C>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        content: C,
+    ) -> crate::error::Result<()>
+    where
+        C: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Body {
+            body: String,
+        }
+
+        let _: Comment = self
+            .crab
+            .post(
+                format!("{}/issues/{}/comments", self.repo(), pr.number),
+                Some(&Body {
+                    body: content.into(),
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_issue_comment<S, C>(
+        &mut self,
+        issue_comment: S,
+        content: C,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Body {
+            body: String,
+        }
+
+        let _: Comment = self
+            .crab
+            .patch(
+                format!("{}/issues/comments/{}", self.repo(), issue_comment.into()),
+                Some(&Body {
+                    body: content.into(),
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn rebase_pr<S>(&mut self, number: u64, new_base: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        self.update(
+            number,
+            &Update {
+                base: Some(new_base.into()),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn merge_pull_request<T, M>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        method: super::MergeMethod,
+        title: T,
+        message: M,
+    ) -> crate::error::Result<()>
+    where
+        T: Into<String>,
+        M: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Merge {
+            #[serde(rename = "Do")]
+            method: &'static str,
+            #[serde(rename = "MergeTitleField")]
+            title: String,
+            #[serde(rename = "MergeMessageField")]
+            message: String,
+            head_commit_id: String,
+        }
+
+        let merge = Merge {
+            method: match method {
+                super::MergeMethod::Merge => "merge",
+                super::MergeMethod::Squash => "squash",
+                super::MergeMethod::Rebase => "rebase",
+            },
+            title: title.into(),
+            message: message.into(),
+            head_commit_id: pr.head_commit.clone(),
+        };
+        let response = self
+            .crab
+            ._post(format!("{}/merge", self.pull(pr.number)), Some(&merge))
+            .await?;
+        octocrab::map_github_error(response).await?;
+        Ok(())
+    }
+
+    async fn close_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.update(
+            pr.number,
+            &Update {
+                state: Some("closed"),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn reopen_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.update(
+            pr.number,
+            &Update {
+                state: Some("open"),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_branch<S>(&mut self, branch: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        // Branch names keep their slashes in the path.
+        let branch: Vec<String> = branch
+            .into()
+            .split('/')
+            .map(super::gitlab::encode)
+            .collect();
+        let response = self
+            .crab
+            ._delete(
+                format!("{}/branches/{}", self.branch_repo(), branch.join("/")),
+                None::<&()>,
+            )
+            .await?;
+        octocrab::map_github_error(response).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draft_title, strip_draft};
+
+    #[test]
+    fn handles_draft_prefixes() {
+        assert_eq!(strip_draft("WIP: Fix it"), "Fix it");
+        assert_eq!(strip_draft("[wip] Fix it"), "Fix it");
+        assert_eq!(strip_draft("Wiping the cache"), "Wiping the cache");
+        assert_eq!(draft_title("Fix it", true), "WIP: Fix it");
+        assert_eq!(draft_title("WIP: Fix it", false), "Fix it");
+    }
+}
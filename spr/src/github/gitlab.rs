@@ -0,0 +1,933 @@
+//! Merge requests on GitLab, through its REST API. Octocrab does the HTTP, with the instance's
+//! `/api/v4` as base URI, so authentication and error handling work like they do for GitHub.
+//!
+//! A few things map differently:
+//! * Drafts are a `Draft:` prefix on the title, which is hidden from the title of the PR.
+//! * Notes take the place of comments. Their id is `<iid>/<note id>`, since notes can only be
+//!   edited through their merge request.
+//! * Approvals count as reviews. GitLab doesn't say which commit was approved, so there is no
+//!   reviewed commit.
+
+use serde::{Deserialize, Serialize};
+
+const DRAFT_PREFIX: &str = "Draft: ";
+/// Access level of the Developer role, the first one that can push.
+const DEVELOPER_ACCESS: u64 = 30;
+
+#[derive(Debug, Clone, Deserialize)]
+struct User {
+    id: u64,
+    username: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Pipeline {
+    status: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MergeRequest {
+    id: u64,
+    iid: u64,
+    title: String,
+    description: Option<String>,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    source_branch: String,
+    target_branch: String,
+    sha: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    reviewers: Vec<User>,
+    #[serde(default)]
+    assignees: Vec<User>,
+    #[serde(default)]
+    has_conflicts: bool,
+    detailed_merge_status: Option<String>,
+    head_pipeline: Option<Pipeline>,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Approver {
+    user: User,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Approvals {
+    #[serde(default)]
+    approved: bool,
+    #[serde(default)]
+    approvals_left: u64,
+    #[serde(default)]
+    approved_by: Vec<Approver>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Note {
+    id: u64,
+    body: String,
+    #[serde(default)]
+    system: bool,
+    author: User,
+}
+
+/// Fields of a merge request to change. Unset ones are left out of the request.
+#[derive(Debug, Default, Serialize)]
+struct Update {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reviewer_ids: Option<Vec<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    assignee_ids: Option<Vec<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    add_labels: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remove_labels: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_event: Option<&'static str>,
+}
+
+/// Escape `text` for use as a single path segment, like a project path or a branch name.
+pub(super) fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// The title without the prefixes GitLab uses to mark drafts.
+fn strip_draft(title: &str) -> &str {
+    lazy_regex::regex_find!(r#"(?i)^\s*(draft:|\[draft\]|\(draft\))\s*"#, title)
+        .map_or(title, |prefix| &title[prefix.len()..])
+}
+
+fn draft_title(title: &str, draft: bool) -> String {
+    match draft {
+        true => format!("{}{}", DRAFT_PREFIX, strip_draft(title)),
+        false => strip_draft(title).to_string(),
+    }
+}
+
+/// A note as a PR comment. Only our own notes can be edited.
+fn comment(iid: u64, note: Note, user: u64) -> super::types::PullRequestComment {
+    super::types::PullRequestComment {
+        editable: !note.system && note.author.id == user,
+        content: note.body,
+        id: format!("{}/{}", iid, note.id),
+    }
+}
+
+fn pull_request(
+    mr: MergeRequest,
+    approvals: Option<Approvals>,
+    comments: Vec<super::types::PullRequestComment>,
+) -> super::types::PullRequest {
+    let approvals = approvals.unwrap_or(Approvals {
+        approved: false,
+        approvals_left: 0,
+        approved_by: Vec::new(),
+    });
+    let reviewers: Vec<String> = mr.reviewers.into_iter().map(|u| u.username).collect();
+    let review_decision = if approvals.approved && !approvals.approved_by.is_empty() {
+        Some(super::ReviewDecision::Approved)
+    } else if approvals.approvals_left > 0 || !reviewers.is_empty() {
+        Some(super::ReviewDecision::ReviewRequired)
+    } else {
+        None
+    };
+    let checks = mr
+        .head_pipeline
+        .map(|pipeline| match pipeline.status.as_str() {
+            "success" => super::CheckState::Success,
+            "failed" | "canceled" => super::CheckState::Failure,
+            _ => super::CheckState::Pending,
+        });
+    let mergeable = match (mr.has_conflicts, mr.detailed_merge_status.as_deref()) {
+        (true, _) => super::Mergeable::Conflicting,
+        (false, None | Some("checking" | "unchecked" | "preparing")) => super::Mergeable::Unknown,
+        (false, Some(_)) => super::Mergeable::Mergeable,
+    };
+
+    super::types::PullRequest {
+        base: mr.target_branch,
+        head: mr.source_branch,
+        number: mr.iid,
+        node: mr.id.to_string(),
+        title: strip_draft(&mr.title).to_string(),
+        body: mr.description.unwrap_or_default(),
+        reviewers,
+        assignees: mr.assignees.into_iter().map(|u| u.username).collect(),
+        review_count: approvals.approved_by.len(),
+        reviewed_by: approvals
+            .approved_by
+            .into_iter()
+            .map(|a| a.user.username)
+            .collect(),
+        labels: mr.labels,
+        comments,
+        closed: mr.state != "opened",
+        merged: mr.state == "merged",
+        draft: mr.draft,
+        review_decision,
+        checks,
+        mergeable,
+        reviewed_commit: None,
+        head_commit: mr.sha.unwrap_or_default(),
+    }
+}
+
+/// Login of the user the token belongs to.
+pub async fn current_user(crab: &octocrab::Octocrab) -> crate::error::Result<String> {
+    let user: User = crab.get("/user", None::<&()>).await?;
+    Ok(user.username)
+}
+
+/// Whether the token's user can push to `owner/repo`, which takes at least the Developer role.
+pub async fn can_push(
+    crab: &octocrab::Octocrab,
+    owner: &str,
+    repo: &str,
+) -> crate::error::Result<bool> {
+    #[derive(Deserialize)]
+    struct Access {
+        access_level: u64,
+    }
+    #[derive(Deserialize)]
+    struct Permissions {
+        project_access: Option<Access>,
+        group_access: Option<Access>,
+    }
+    #[derive(Deserialize)]
+    struct Project {
+        permissions: Permissions,
+    }
+
+    let project: Project = crab
+        .get(
+            format!("/projects/{}", encode(&format!("{}/{}", owner, repo))),
+            None::<&()>,
+        )
+        .await?;
+    let level = [
+        project.permissions.project_access,
+        project.permissions.group_access,
+    ]
+    .into_iter()
+    .flatten()
+    .map(|access| access.access_level)
+    .max()
+    .unwrap_or(0);
+    Ok(level >= DEVELOPER_ACCESS)
+}
+
+#[derive(Clone)]
+pub struct GitLab {
+    config: crate::config::Config,
+    crab: octocrab::Octocrab,
+    /// Ids of users that were looked up already.
+    users: std::collections::HashMap<String, u64>,
+    /// Id of the user the token belongs to, to tell which notes are ours.
+    me: Option<u64>,
+    /// Last update of the MRs polled through `pull_request_changed`.
+    updated: std::collections::HashMap<u64, String>,
+}
+
+impl GitLab {
+    pub fn new(config: crate::config::Config, crab: octocrab::Octocrab) -> Self {
+        Self {
+            config,
+            crab,
+            users: std::collections::HashMap::new(),
+            me: None,
+            updated: std::collections::HashMap::new(),
+        }
+    }
+
+    fn project(&self) -> String {
+        format!(
+            "/projects/{}",
+            encode(&format!("{}/{}", self.config.owner, self.config.repo))
+        )
+    }
+
+    fn merge_request(&self, iid: u64) -> String {
+        format!("{}/merge_requests/{}", self.project(), iid)
+    }
+
+    /// All pages of a list of merge requests or notes.
+    async fn all<T, P>(&self, route: &str, parameters: &P) -> crate::error::Result<Vec<T>>
+    where
+        T: serde::de::DeserializeOwned,
+        P: Serialize,
+    {
+        #[derive(Serialize)]
+        struct Page<'a, P> {
+            #[serde(flatten)]
+            parameters: &'a P,
+            page: u64,
+            per_page: u64,
+        }
+
+        let mut ret = Vec::new();
+        for page in 1.. {
+            let items: Vec<T> = self
+                .crab
+                .get(
+                    route,
+                    Some(&Page {
+                        parameters,
+                        page,
+                        per_page: 100,
+                    }),
+                )
+                .await?;
+            let done = items.len() < 100;
+            ret.extend(items);
+            if done {
+                break;
+            }
+        }
+        Ok(ret)
+    }
+
+    async fn me(&mut self) -> crate::error::Result<u64> {
+        if let Some(me) = self.me {
+            return Ok(me);
+        }
+        let user: User = self.crab.get("/user", None::<&()>).await?;
+        self.me = Some(user.id);
+        Ok(user.id)
+    }
+
+    /// Id of the user `username`, or `None` if there is no such user.
+    async fn user_id(&mut self, username: &str) -> crate::error::Result<Option<u64>> {
+        #[derive(Serialize)]
+        struct Query<'a> {
+            username: &'a str,
+        }
+
+        if let Some(id) = self.users.get(&username.to_lowercase()) {
+            return Ok(Some(*id));
+        }
+        let users: Vec<User> = self.crab.get("/users", Some(&Query { username })).await?;
+        let id = users.first().map(|user| user.id);
+        if let Some(id) = id {
+            self.users.insert(username.to_lowercase(), id);
+        }
+        Ok(id)
+    }
+
+    /// Ids of `usernames`, or an error naming the ones that don't exist.
+    async fn user_ids<I>(&mut self, usernames: I) -> crate::error::Result<Vec<u64>>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut ids = Vec::new();
+        let mut unknown = Vec::new();
+        for username in usernames {
+            match super::Reviewer::parse(&username) {
+                super::Reviewer::User(login) => match self.user_id(&login).await? {
+                    Some(id) => ids.push(id),
+                    None => unknown.push(login),
+                },
+                // GitLab can only request reviews from users, not from groups.
+                team @ super::Reviewer::Team { .. } => unknown.push(team.to_string()),
+            }
+        }
+        match unknown.is_empty() {
+            true => Ok(ids),
+            false => Err(super::traits::unknown_accounts(&unknown)),
+        }
+    }
+
+    /// The merge request as it is now. Reviewers, assignees and the draft prefix can only be set
+    /// along with the rest of their field, so changes start from this rather than from a PR that
+    /// may have been read before an earlier change.
+    async fn current(&self, iid: u64) -> crate::error::Result<MergeRequest> {
+        Ok(self.crab.get(self.merge_request(iid), None::<&()>).await?)
+    }
+
+    async fn update(&self, iid: u64, update: &Update) -> crate::error::Result<MergeRequest> {
+        Ok(self.crab.put(self.merge_request(iid), Some(update)).await?)
+    }
+
+    async fn load(&mut self, mr: MergeRequest) -> crate::error::Result<super::types::PullRequest> {
+        #[derive(Serialize)]
+        struct Sort {
+            sort: &'static str,
+            order_by: &'static str,
+        }
+
+        let me = self.me().await?;
+        let approvals: Approvals = self
+            .crab
+            .get(
+                format!("{}/approvals", self.merge_request(mr.iid)),
+                None::<&()>,
+            )
+            .await?;
+        let notes: Vec<Note> = self
+            .all(
+                &format!("{}/notes", self.merge_request(mr.iid)),
+                &Sort {
+                    sort: "asc",
+                    order_by: "created_at",
+                },
+            )
+            .await?;
+        let comments = notes
+            .into_iter()
+            .map(|note| comment(mr.iid, note, me))
+            .collect();
+        Ok(pull_request(mr, Some(approvals), comments))
+    }
+
+    async fn merge_requests<P: Serialize>(
+        &mut self,
+        parameters: &P,
+    ) -> crate::error::Result<Vec<super::types::PullRequest>> {
+        let route = format!("{}/merge_requests", self.project());
+        let mut ret = Vec::new();
+        for mr in self.all::<MergeRequest, _>(&route, parameters).await? {
+            // Lists leave out the pipeline and merge status, so get each MR on its own.
+            let mr: MergeRequest = self
+                .crab
+                .get(self.merge_request(mr.iid), None::<&()>)
+                .await?;
+            ret.push(self.load(mr).await?);
+        }
+        Ok(ret)
+    }
+}
+
+impl super::GitHubAdapter for &mut GitLab {
+    type PRAdapter = super::types::PullRequest;
+
+    async fn pull_request(&mut self, number: u64) -> crate::error::Result<Self::PRAdapter> {
+        let mr: MergeRequest = self
+            .crab
+            .get(self.merge_request(number), None::<&()>)
+            .await?;
+        self.load(mr).await
+    }
+
+    async fn pull_request_by_head<S>(&mut self, head: S) -> crate::error::Result<Self::PRAdapter>
+    where
+        S: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Query {
+            source_branch: String,
+            state: &'static str,
+        }
+
+        let head = head.into();
+        let mut mrs = self
+            .merge_requests(&Query {
+                source_branch: head.clone(),
+                state: "opened",
+            })
+            .await?;
+        if mrs.len() > 1 {
+            return Err(crate::error::Error::new("Found more than one candidate MR"));
+        }
+        mrs.pop().ok_or_else(|| {
+            crate::error::Error::new(format!("Couldn't find an MR for branch {}", head))
+        })
+    }
+
+    async fn pull_request_changed(&mut self, number: u64) -> crate::error::Result<bool> {
+        let mr: MergeRequest = self
+            .crab
+            .get(self.merge_request(number), None::<&()>)
+            .await?;
+        let changed = self.updated.get(&number) != Some(&mr.updated_at);
+        self.updated.insert(number, mr.updated_at);
+        Ok(changed)
+    }
+
+    async fn open_pull_requests(&mut self) -> crate::error::Result<Vec<Self::PRAdapter>> {
+        #[derive(Serialize)]
+        struct Query {
+            state: &'static str,
+        }
+
+        self.merge_requests(&Query { state: "opened" }).await
+    }
+
+    async fn open_pull_requests_by_base<S>(
+        &mut self,
+        base: S,
+    ) -> crate::error::Result<Vec<Self::PRAdapter>>
+    where
+        S: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Query {
+            target_branch: String,
+            state: &'static str,
+        }
+
+        self.merge_requests(&Query {
+            target_branch: base.into(),
+            state: "opened",
+        })
+        .await
+    }
+
+    async fn new_pull_request<H, B, St, Sb>(
+        &mut self,
+        title: St,
+        body: Sb,
+        base_ref_name: B,
+        head_ref_name: H,
+        draft: bool,
+    ) -> crate::error::Result<Self::PRAdapter>
+    where
+        H: AsRef<str>,
+        B: AsRef<str>,
+        St: Into<String>,
+        Sb: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Create<'a> {
+            source_branch: &'a str,
+            target_branch: &'a str,
+            title: String,
+            description: String,
+        }
+
+        let mr: MergeRequest = self
+            .crab
+            .post(
+                format!("{}/merge_requests", self.project()),
+                Some(&Create {
+                    source_branch: head_ref_name.as_ref(),
+                    target_branch: base_ref_name.as_ref(),
+                    title: draft_title(&title.into(), draft),
+                    description: body.into(),
+                }),
+            )
+            .await?;
+        Ok(pull_request(mr, None, Vec::new()))
+    }
+
+    async fn add_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let added = self
+            .user_ids(reviewers.into_iter().map(|s| s.into()))
+            .await?;
+        // The API only sets the whole list of reviewers.
+        let current = self.current(pr.number).await?;
+        let mut ids: Vec<u64> = current.reviewers.iter().map(|u| u.id).collect();
+        ids.extend(
+            added
+                .into_iter()
+                .filter(|id| !ids.contains(id))
+                .collect::<Vec<_>>(),
+        );
+        self.update(
+            pr.number,
+            &Update {
+                reviewer_ids: Some(ids),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn add_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let added = self
+            .user_ids(assignees.into_iter().map(|s| s.into()))
+            .await?;
+        let current = self.current(pr.number).await?;
+        let mut ids: Vec<u64> = current.assignees.iter().map(|u| u.id).collect();
+        ids.extend(
+            added
+                .into_iter()
+                .filter(|id| !ids.contains(id))
+                .collect::<Vec<_>>(),
+        );
+        self.update(
+            pr.number,
+            &Update {
+                assignee_ids: Some(ids),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let removed: Vec<super::Reviewer> = reviewers
+            .into_iter()
+            .map(|s| super::Reviewer::parse(&s.into()))
+            .collect();
+        let ids = self
+            .current(pr.number)
+            .await?
+            .reviewers
+            .into_iter()
+            .filter(|user| !removed.iter().any(|r| r.is(&user.username)))
+            .map(|user| user.id)
+            .collect();
+        self.update(
+            pr.number,
+            &Update {
+                reviewer_ids: Some(ids),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let removed: Vec<String> = assignees.into_iter().map(|s| s.into()).collect();
+        let ids = self
+            .current(pr.number)
+            .await?
+            .assignees
+            .into_iter()
+            .filter(|user| {
+                !removed
+                    .iter()
+                    .any(|r| r.eq_ignore_ascii_case(&user.username))
+            })
+            .map(|user| user.id)
+            .collect();
+        self.update(
+            pr.number,
+            &Update {
+                assignee_ids: Some(ids),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn add_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let labels: Vec<String> = labels.into_iter().map(|s| s.into()).collect();
+        self.update(
+            pr.number,
+            &Update {
+                add_labels: Some(labels.join(",")),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let labels: Vec<String> = labels.into_iter().map(|s| s.into()).collect();
+        self.update(
+            pr.number,
+            &Update {
+                remove_labels: Some(labels.join(",")),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn edit_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+        title: Option<String>,
+        body: Option<String>,
+    ) -> crate::error::Result<()> {
+        // A draft prefix added or removed since `pr` was read has to stay.
+        let title = match title {
+            Some(title) => Some(draft_title(&title, self.current(pr.number).await?.draft)),
+            None => None,
+        };
+        self.update(
+            pr.number,
+            &Update {
+                title,
+                description: body,
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn set_draft(&mut self, pr: &Self::PRAdapter, draft: bool) -> crate::error::Result<()> {
+        self.update(
+            pr.number,
+            &Update {
+                title: Some(draft_title(&self.current(pr.number).await?.title, draft)),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn post_comment<C>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        content: C,
+    ) -> crate::error::Result<()>
+    where
+        C: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Body {
+            body: String,
+        }
+
+        let _: Note = self
+            .crab
+            .post(
+                format!("{}/notes", self.merge_request(pr.number)),
+                Some(&Body {
+                    body: content.into(),
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_issue_comment<S, C>(
+        &mut self,
+        issue_comment: S,
+        content: C,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Body {
+            body: String,
+        }
+
+        let id = issue_comment.into();
+        let Some((iid, note)) = id
+            .split_once('/')
+            .and_then(|(iid, note)| Some((iid.parse::<u64>().ok()?, note)))
+        else {
+            return Err(crate::error::Error::new(format!(
+                "Not the id of a GitLab note: {}",
+                id
+            )));
+        };
+        let _: Note = self
+            .crab
+            .put(
+                format!("{}/notes/{}", self.merge_request(iid), note),
+                Some(&Body {
+                    body: content.into(),
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn rebase_pr<S>(&mut self, number: u64, new_base: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        self.update(
+            number,
+            &Update {
+                target_branch: Some(new_base.into()),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn merge_pull_request<T, M>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        method: super::MergeMethod,
+        title: T,
+        message: M,
+    ) -> crate::error::Result<()>
+    where
+        T: Into<String>,
+        M: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Merge {
+            sha: String,
+            squash: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            merge_commit_message: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            squash_commit_message: Option<String>,
+        }
+
+        let message = format!("{}\n\n{}", title.into(), message.into())
+            .trim()
+            .to_string();
+        let merge = match method {
+            super::MergeMethod::Merge => Merge {
+                sha: pr.head_commit.clone(),
+                squash: false,
+                merge_commit_message: Some(message),
+                squash_commit_message: None,
+            },
+            super::MergeMethod::Squash => Merge {
+                sha: pr.head_commit.clone(),
+                squash: true,
+                merge_commit_message: None,
+                squash_commit_message: Some(message),
+            },
+            // Fast-forward merges are a setting of the project on GitLab, not of the request.
+            super::MergeMethod::Rebase => {
+                return Err(crate::error::Error::new(
+                    "GitLab can't rebase while merging. Use `merge` or `squash`, and set the \
+                     project's merge method to fast-forward to keep the history linear",
+                ));
+            }
+        };
+
+        let mr: MergeRequest = self
+            .crab
+            .put(
+                format!("{}/merge", self.merge_request(pr.number)),
+                Some(&merge),
+            )
+            .await?;
+        if mr.state != "merged" {
+            return Err(crate::error::Error::new(format!(
+                "GitLab did not merge MR !{}: it is {}",
+                pr.number, mr.state
+            )));
+        }
+        Ok(())
+    }
+
+    async fn close_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.update(
+            pr.number,
+            &Update {
+                state_event: Some("close"),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn reopen_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.update(
+            pr.number,
+            &Update {
+                state_event: Some("reopen"),
+                ..Update::default()
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_branch<S>(&mut self, branch: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        let response = self
+            .crab
+            ._delete(
+                format!(
+                    "{}/repository/branches/{}",
+                    self.project(),
+                    encode(&branch.into())
+                ),
+                None::<&()>,
+            )
+            .await?;
+        octocrab::map_github_error(response).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draft_title, encode, strip_draft};
+
+    #[test]
+    fn encodes_path_segments() {
+        assert_eq!(encode("group/sub/project"), "group%2Fsub%2Fproject");
+        assert_eq!(encode("spr/user/fix-it_1.2"), "spr%2Fuser%2Ffix-it_1.2");
+    }
+
+    #[test]
+    fn handles_draft_prefixes() {
+        assert_eq!(strip_draft("Draft: Fix it"), "Fix it");
+        assert_eq!(strip_draft("[Draft] Fix it"), "Fix it");
+        assert_eq!(strip_draft("(draft) Fix it"), "Fix it");
+        assert_eq!(strip_draft("Drafting the docs"), "Drafting the docs");
+        assert_eq!(draft_title("Draft: Fix it", true), "Draft: Fix it");
+        assert_eq!(draft_title("Draft: Fix it", false), "Fix it");
+    }
+}
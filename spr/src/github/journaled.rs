@@ -0,0 +1,367 @@
+use std::path::PathBuf;
+
+use crate::journal::{Journal, Mutation};
+
+use super::traits::find_pr_comment;
+use super::{GHPullRequest, GitHubAdapter, GithubPRComment};
+
+/// Wraps a GitHub adapter and records every mutation in the journal, so `undo` can revert it.
+pub struct Journaled<G> {
+    inner: G,
+    journal: Journal,
+    /// Git directory and remote to look up deleted branches in, so they can be restored.
+    remote: Option<(PathBuf, String)>,
+}
+
+impl<G> Journaled<G> {
+    pub fn new(inner: G, journal: Journal) -> Self {
+        Self {
+            inner,
+            journal,
+            remote: None,
+        }
+    }
+
+    /// Look up branches in the remote-tracking branches of `remote` before deleting them.
+    pub fn with_remote<P, S>(mut self, git_dir: P, remote: S) -> Self
+    where
+        P: Into<PathBuf>,
+        S: Into<String>,
+    {
+        self.remote = Some((git_dir.into(), remote.into()));
+        self
+    }
+
+    /// Where `branch` points to on the remote, as far as the last fetch knows.
+    fn remote_branch(&self, branch: &str) -> Option<String> {
+        let (git_dir, remote) = self.remote.as_ref()?;
+        let repo = git2::Repository::open(git_dir).ok()?;
+        let oid = repo
+            .refname_to_id(&format!("refs/remotes/{}/{}", remote, branch))
+            .ok()?;
+        Some(oid.to_string())
+    }
+}
+
+impl<G, PR> GitHubAdapter for &mut Journaled<G>
+where
+    G: GitHubAdapter<PRAdapter = PR>,
+    PR: GHPullRequest + Send,
+{
+    type PRAdapter = PR;
+
+    async fn pull_request(&mut self, number: u64) -> crate::error::Result<Self::PRAdapter> {
+        self.inner.pull_request(number).await
+    }
+
+    async fn pull_request_by_head<S>(&mut self, head: S) -> crate::error::Result<Self::PRAdapter>
+    where
+        S: Into<String>,
+    {
+        self.inner.pull_request_by_head(head).await
+    }
+
+    async fn pull_request_changed(&mut self, number: u64) -> crate::error::Result<bool> {
+        self.inner.pull_request_changed(number).await
+    }
+
+    async fn open_pull_requests(&mut self) -> crate::error::Result<Vec<Self::PRAdapter>> {
+        self.inner.open_pull_requests().await
+    }
+
+    async fn open_pull_requests_by_base<S>(
+        &mut self,
+        base: S,
+    ) -> crate::error::Result<Vec<Self::PRAdapter>>
+    where
+        S: Into<String>,
+    {
+        self.inner.open_pull_requests_by_base(base).await
+    }
+
+    async fn pull_requests<I>(
+        &mut self,
+        numbers: I,
+    ) -> crate::error::Result<Vec<Option<Self::PRAdapter>>>
+    where
+        I: IntoIterator<Item = Option<u64>>,
+    {
+        self.inner.pull_requests(numbers).await
+    }
+
+    async fn new_pull_request<H, B, St, Sb>(
+        &mut self,
+        title: St,
+        body: Sb,
+        base_ref_name: B,
+        head_ref_name: H,
+        draft: bool,
+    ) -> crate::error::Result<Self::PRAdapter>
+    where
+        H: AsRef<str>,
+        B: AsRef<str>,
+        St: Into<String>,
+        Sb: Into<String>,
+    {
+        let pr = self
+            .inner
+            .new_pull_request(title, body, base_ref_name, head_ref_name, draft)
+            .await?;
+        self.journal.record(Mutation::PullRequestCreated {
+            number: pr.pr_number(),
+        })?;
+        Ok(pr)
+    }
+
+    async fn add_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let reviewers: Vec<String> = reviewers.into_iter().map(|s| s.into()).collect();
+        self.inner
+            .add_reviewers(pr, reviewers.iter().cloned())
+            .await?;
+        self.journal.record(Mutation::ReviewersRequested {
+            number: pr.pr_number(),
+            reviewers,
+        })
+    }
+
+    async fn add_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let assignees: Vec<String> = assignees.into_iter().map(|s| s.into()).collect();
+        self.inner
+            .add_assignees(pr, assignees.iter().cloned())
+            .await?;
+        self.journal.record(Mutation::AssigneesAdded {
+            number: pr.pr_number(),
+            assignees,
+        })
+    }
+
+    async fn remove_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let reviewers: Vec<String> = reviewers.into_iter().map(|s| s.into()).collect();
+        self.inner
+            .remove_reviewers(pr, reviewers.iter().cloned())
+            .await?;
+        self.journal.record(Mutation::ReviewersRemoved {
+            number: pr.pr_number(),
+            reviewers,
+        })
+    }
+
+    async fn remove_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let assignees: Vec<String> = assignees.into_iter().map(|s| s.into()).collect();
+        self.inner
+            .remove_assignees(pr, assignees.iter().cloned())
+            .await?;
+        self.journal.record(Mutation::AssigneesRemoved {
+            number: pr.pr_number(),
+            assignees,
+        })
+    }
+
+    async fn add_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let labels: Vec<String> = labels.into_iter().map(|s| s.into()).collect();
+        self.inner.add_labels(pr, labels.iter().cloned()).await?;
+        self.journal.record(Mutation::LabelsAdded {
+            number: pr.pr_number(),
+            labels,
+        })
+    }
+
+    async fn remove_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let labels: Vec<String> = labels.into_iter().map(|s| s.into()).collect();
+        self.inner.remove_labels(pr, labels.iter().cloned()).await?;
+        self.journal.record(Mutation::LabelsRemoved {
+            number: pr.pr_number(),
+            labels,
+        })
+    }
+
+    async fn edit_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+        title: Option<String>,
+        body: Option<String>,
+    ) -> crate::error::Result<()> {
+        let old_title = title.as_ref().map(|_| pr.title().to_string());
+        let old_body = body.as_ref().map(|_| pr.body().to_string());
+        self.inner.edit_pull_request(pr, title, body).await?;
+        self.journal.record(Mutation::PullRequestEdited {
+            number: pr.pr_number(),
+            old_title,
+            old_body,
+        })
+    }
+
+    async fn set_draft(&mut self, pr: &Self::PRAdapter, draft: bool) -> crate::error::Result<()> {
+        self.inner.set_draft(pr, draft).await?;
+        self.journal.record(Mutation::DraftChanged {
+            number: pr.pr_number(),
+            draft,
+        })
+    }
+
+    async fn post_comment<C>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        content: C,
+    ) -> crate::error::Result<()>
+    where
+        C: Into<String>,
+    {
+        self.inner.post_comment(pr, content).await?;
+        self.journal.record(Mutation::CommentPosted {
+            number: pr.pr_number(),
+        })
+    }
+
+    async fn update_issue_comment<S, C>(
+        &mut self,
+        issue_comment: S,
+        content: C,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        // Without the PR at hand the old body isn't known.
+        let id = issue_comment.into();
+        self.inner.update_issue_comment(id.clone(), content).await?;
+        self.journal
+            .record(Mutation::CommentEdited { id, old_body: None })
+    }
+
+    async fn update_pr_comment<S>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        content: S,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        let content = content.into();
+        if !super::pr_comment_outdated(pr, &content) {
+            return Ok(());
+        }
+
+        let old = find_pr_comment(pr);
+        self.inner.update_pr_comment(pr, content).await?;
+        self.journal.record(match old {
+            Some(old) => Mutation::CommentEdited {
+                id: old.id().to_string(),
+                old_body: Some(old.body().to_string()),
+            },
+            None => Mutation::CommentPosted {
+                number: pr.pr_number(),
+            },
+        })
+    }
+
+    async fn rebase_pr<S>(&mut self, number: u64, new_base: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        let old_base = self
+            .inner
+            .pull_request(number)
+            .await?
+            .base_branch_name()
+            .to_string();
+        let new_base = new_base.into();
+        self.inner.rebase_pr(number, new_base.clone()).await?;
+        self.journal.record(Mutation::BaseChanged {
+            number,
+            old_base,
+            new_base,
+        })
+    }
+
+    async fn merge_pull_request<T, M>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        method: super::MergeMethod,
+        title: T,
+        message: M,
+    ) -> crate::error::Result<()>
+    where
+        T: Into<String>,
+        M: Into<String>,
+    {
+        self.inner
+            .merge_pull_request(pr, method, title, message)
+            .await?;
+        self.journal.record(Mutation::PullRequestMerged {
+            number: pr.pr_number(),
+        })
+    }
+
+    async fn close_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.inner.close_pull_request(pr).await?;
+        self.journal.record(Mutation::PullRequestClosed {
+            number: pr.pr_number(),
+        })
+    }
+
+    async fn reopen_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.inner.reopen_pull_request(pr).await?;
+        self.journal.record(Mutation::PullRequestReopened {
+            number: pr.pr_number(),
+        })
+    }
+
+    async fn delete_branch<S>(&mut self, branch: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        let branch = branch.into();
+        let old = self.remote_branch(&branch);
+        self.inner.delete_branch(branch.clone()).await?;
+        self.journal.record(Mutation::BranchDeleted { branch, old })
+    }
+}
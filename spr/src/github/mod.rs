@@ -3,12 +3,31 @@ pub mod fakes;
 
 mod traits;
 
+pub use traits::CheckState;
 pub use traits::GHPullRequest;
 pub use traits::GitHubAdapter;
 use traits::GithubPRComment;
+pub use traits::MergeMethod;
+pub use traits::Mergeable;
+pub use traits::PullRequestState;
+pub use traits::ReviewDecision;
+pub use traits::Reviewer;
+pub use traits::pr_comment_outdated;
 
 mod real;
 pub use real::GitHub;
 
+pub mod gitea;
+pub use gitea::Gitea;
+
+pub mod gitlab;
+pub use gitlab::GitLab;
+
+pub mod plugin;
+pub use plugin::Plugin;
+
+mod journaled;
+pub use journaled::Journaled;
+
 mod queries;
 mod types;
@@ -0,0 +1,876 @@
+//! PRs on any review system, through an external executable set in `spr.forge.command`. spr
+//! starts it once per command and talks JSON-RPC 2.0 with it over stdio, one message per line.
+//! The methods mirror the adapter traits, see `docs/reference/forge-plugins.md`.
+//!
+//! The plugin's stderr is passed through, so it can report progress or log there.
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Comment {
+    id: String,
+    body: String,
+    #[serde(default)]
+    editable: bool,
+}
+
+/// A PR as the plugin describes it. Anything but the branches, number and title may be left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PullRequest {
+    number: u64,
+    head: String,
+    base: String,
+    title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    state: Option<super::PullRequestState>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    reviewers: Vec<String>,
+    #[serde(default)]
+    assignees: Vec<String>,
+    #[serde(default)]
+    reviewed_by: Vec<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    comments: Vec<Comment>,
+    #[serde(default)]
+    review_decision: Option<super::ReviewDecision>,
+    #[serde(default)]
+    checks: Option<super::CheckState>,
+    #[serde(default)]
+    mergeable: Option<super::Mergeable>,
+    #[serde(default)]
+    reviewed_commit: Option<String>,
+    #[serde(default)]
+    head_commit: String,
+    #[serde(default)]
+    review_count: usize,
+}
+
+impl From<PullRequest> for super::types::PullRequest {
+    fn from(pr: PullRequest) -> Self {
+        let state = pr.state.unwrap_or(super::PullRequestState::Open);
+        Self {
+            base: pr.base,
+            head: pr.head,
+            number: pr.number,
+            node: pr.number.to_string(),
+            title: pr.title,
+            body: pr.body,
+            reviewers: pr.reviewers,
+            assignees: pr.assignees,
+            reviewed_by: pr.reviewed_by,
+            labels: pr.labels,
+            comments: pr
+                .comments
+                .into_iter()
+                .map(|c| super::types::PullRequestComment {
+                    content: c.body,
+                    id: c.id,
+                    editable: c.editable,
+                })
+                .collect(),
+            closed: state != super::PullRequestState::Open,
+            merged: state == super::PullRequestState::Merged,
+            draft: pr.draft,
+            review_decision: pr.review_decision,
+            checks: pr.checks,
+            mergeable: pr.mergeable.unwrap_or(super::Mergeable::Unknown),
+            reviewed_commit: pr.reviewed_commit,
+            head_commit: pr.head_commit,
+            review_count: pr.review_count,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Request<'a, P> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: P,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    id: Option<u64>,
+    #[serde(default)]
+    result: serde_json::Value,
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Initialized {
+    user: String,
+}
+
+/// Talks to a plugin through `reader` and `writer`.
+pub struct Plugin<R, W> {
+    reader: tokio::io::Lines<tokio::io::BufReader<R>>,
+    writer: W,
+    last_id: u64,
+    /// The plugin's process, killed when the adapter goes away.
+    _child: Option<tokio::process::Child>,
+}
+
+/// A plugin running as a child process.
+pub type Process = Plugin<tokio::process::ChildStdout, tokio::process::ChildStdin>;
+
+impl Process {
+    /// Start `command` in the current directory.
+    pub fn spawn(command: &str) -> crate::error::Result<Self> {
+        let mut child = tokio::process::Command::new(command)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| {
+                crate::error::Error::new(format!(
+                    "Failed to start forge plugin {}: {}",
+                    command, err
+                ))
+            })?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(crate::error::Error::new(
+                "Failed to connect to the forge plugin",
+            ));
+        };
+
+        let mut plugin = Plugin::new(stdout, stdin);
+        plugin._child = Some(child);
+        Ok(plugin)
+    }
+}
+
+impl<R, W> Plugin<R, W>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader: tokio::io::BufReader::new(reader).lines(),
+            writer,
+            last_id: 0,
+            _child: None,
+        }
+    }
+
+    async fn call<P, T>(&mut self, method: &str, params: P) -> crate::error::Result<T>
+    where
+        P: Serialize,
+        T: serde::de::DeserializeOwned,
+    {
+        self.last_id += 1;
+        let mut request = serde_json::to_string(&Request {
+            jsonrpc: "2.0",
+            id: self.last_id,
+            method,
+            params,
+        })?;
+        request.push('\n');
+        self.writer.write_all(request.as_bytes()).await?;
+        self.writer.flush().await?;
+
+        let line = self.reader.next_line().await?.ok_or_else(|| {
+            crate::error::Error::new(format!("The forge plugin exited during {}", method))
+        })?;
+        let response: Response = serde_json::from_str(&line)?;
+        if response.id != Some(self.last_id) {
+            return Err(crate::error::Error::new(format!(
+                "The forge plugin answered {} out of order",
+                method
+            )));
+        }
+        match response.error {
+            Some(error) => Err(crate::error::Error::new(error.message)),
+            None => Ok(serde_json::from_value(response.result)?),
+        }
+    }
+
+    /// Tell the plugin which repository spr works on. Returns the login of the current user.
+    pub async fn initialize(
+        &mut self,
+        owner: &str,
+        repo: &str,
+        remote: &str,
+    ) -> crate::error::Result<String> {
+        #[derive(Serialize)]
+        struct Params<'a> {
+            owner: &'a str,
+            repo: &'a str,
+            remote: &'a str,
+        }
+
+        let initialized: Initialized = self
+            .call(
+                "initialize",
+                Params {
+                    owner,
+                    repo,
+                    remote,
+                },
+            )
+            .await?;
+        Ok(initialized.user)
+    }
+}
+
+#[derive(Serialize)]
+struct Number {
+    number: u64,
+}
+
+/// Params of the methods that change a list of names on a PR, like its reviewers or labels.
+fn names<S, I>(number: u64, key: &str, names: I) -> serde_json::Value
+where
+    S: Into<String>,
+    I: IntoIterator<Item = S>,
+{
+    let names: Vec<String> = names.into_iter().map(|s| s.into()).collect();
+    let mut params = serde_json::json!({ "number": number });
+    params[key] = names.into();
+    params
+}
+
+impl<R, W> super::GitHubAdapter for &mut Plugin<R, W>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    type PRAdapter = super::types::PullRequest;
+
+    async fn pull_request(&mut self, number: u64) -> crate::error::Result<Self::PRAdapter> {
+        let pr: PullRequest = self.call("pull_request", Number { number }).await?;
+        Ok(pr.into())
+    }
+
+    async fn pull_request_by_head<S>(&mut self, head: S) -> crate::error::Result<Self::PRAdapter>
+    where
+        S: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Params {
+            head: String,
+        }
+
+        let pr: PullRequest = self
+            .call("pull_request_by_head", Params { head: head.into() })
+            .await?;
+        Ok(pr.into())
+    }
+
+    async fn pull_request_changed(&mut self, number: u64) -> crate::error::Result<bool> {
+        self.call("pull_request_changed", Number { number }).await
+    }
+
+    async fn open_pull_requests(&mut self) -> crate::error::Result<Vec<Self::PRAdapter>> {
+        #[derive(Serialize)]
+        struct Params {
+            base: Option<String>,
+        }
+
+        let prs: Vec<PullRequest> = self
+            .call("open_pull_requests", Params { base: None })
+            .await?;
+        Ok(prs.into_iter().map(|pr| pr.into()).collect())
+    }
+
+    async fn open_pull_requests_by_base<S>(
+        &mut self,
+        base: S,
+    ) -> crate::error::Result<Vec<Self::PRAdapter>>
+    where
+        S: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Params {
+            base: Option<String>,
+        }
+
+        let prs: Vec<PullRequest> = self
+            .call(
+                "open_pull_requests",
+                Params {
+                    base: Some(base.into()),
+                },
+            )
+            .await?;
+        Ok(prs.into_iter().map(|pr| pr.into()).collect())
+    }
+
+    async fn new_pull_request<H, B, St, Sb>(
+        &mut self,
+        title: St,
+        body: Sb,
+        base_ref_name: B,
+        head_ref_name: H,
+        draft: bool,
+    ) -> crate::error::Result<Self::PRAdapter>
+    where
+        H: AsRef<str>,
+        B: AsRef<str>,
+        St: Into<String>,
+        Sb: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Params<'a> {
+            title: String,
+            body: String,
+            base: &'a str,
+            head: &'a str,
+            draft: bool,
+        }
+
+        let pr: PullRequest = self
+            .call(
+                "new_pull_request",
+                Params {
+                    title: title.into(),
+                    body: body.into(),
+                    base: base_ref_name.as_ref(),
+                    head: head_ref_name.as_ref(),
+                    draft,
+                },
+            )
+            .await?;
+        Ok(pr.into())
+    }
+
+    async fn add_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        self.call("add_reviewers", names(pr.number, "reviewers", reviewers))
+            .await
+    }
+
+    async fn add_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        self.call("add_assignees", names(pr.number, "assignees", assignees))
+            .await
+    }
+
+    async fn remove_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        self.call("remove_reviewers", names(pr.number, "reviewers", reviewers))
+            .await
+    }
+
+    async fn remove_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        self.call("remove_assignees", names(pr.number, "assignees", assignees))
+            .await
+    }
+
+    async fn add_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        self.call("add_labels", names(pr.number, "labels", labels))
+            .await
+    }
+
+    async fn remove_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        self.call("remove_labels", names(pr.number, "labels", labels))
+            .await
+    }
+
+    async fn edit_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+        title: Option<String>,
+        body: Option<String>,
+    ) -> crate::error::Result<()> {
+        #[derive(Serialize)]
+        struct Params {
+            number: u64,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            title: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            body: Option<String>,
+        }
+
+        self.call(
+            "edit_pull_request",
+            Params {
+                number: pr.number,
+                title,
+                body,
+            },
+        )
+        .await
+    }
+
+    async fn set_draft(&mut self, pr: &Self::PRAdapter, draft: bool) -> crate::error::Result<()> {
+        #[derive(Serialize)]
+        struct Params {
+            number: u64,
+            draft: bool,
+        }
+
+        self.call(
+            "set_draft",
+            Params {
+                number: pr.number,
+                draft,
+            },
+        )
+        .await
+    }
+
+    async fn post_comment<C>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        content: C,
+    ) -> crate::error::Result<()>
+    where
+        C: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Params {
+            number: u64,
+            body: String,
+        }
+
+        self.call(
+            "post_comment",
+            Params {
+                number: pr.number,
+                body: content.into(),
+            },
+        )
+        .await
+    }
+
+    async fn update_issue_comment<S, C>(
+        &mut self,
+        issue_comment: S,
+        content: C,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Params {
+            id: String,
+            body: String,
+        }
+
+        self.call(
+            "update_comment",
+            Params {
+                id: issue_comment.into(),
+                body: content.into(),
+            },
+        )
+        .await
+    }
+
+    async fn rebase_pr<S>(&mut self, number: u64, new_base: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Params {
+            number: u64,
+            base: String,
+        }
+
+        self.call(
+            "rebase_pull_request",
+            Params {
+                number,
+                base: new_base.into(),
+            },
+        )
+        .await
+    }
+
+    async fn merge_pull_request<T, M>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        method: super::MergeMethod,
+        title: T,
+        message: M,
+    ) -> crate::error::Result<()>
+    where
+        T: Into<String>,
+        M: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Params {
+            number: u64,
+            method: super::MergeMethod,
+            title: String,
+            message: String,
+        }
+
+        self.call(
+            "merge_pull_request",
+            Params {
+                number: pr.number,
+                method,
+                title: title.into(),
+                message: message.into(),
+            },
+        )
+        .await
+    }
+
+    async fn close_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.call("close_pull_request", Number { number: pr.number })
+            .await
+    }
+
+    async fn reopen_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.call("reopen_pull_request", Number { number: pr.number })
+            .await
+    }
+
+    async fn delete_branch<S>(&mut self, branch: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        #[derive(Serialize)]
+        struct Params {
+            branch: String,
+        }
+
+        self.call(
+            "delete_branch",
+            Params {
+                branch: branch.into(),
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    use crate::github::fakes;
+    use crate::github::{GHPullRequest, GitHubAdapter, GithubPRComment};
+
+    impl From<fakes::PullRequest> for super::PullRequest {
+        fn from(pr: fakes::PullRequest) -> Self {
+            Self {
+                number: pr.number,
+                state: Some(pr.state()),
+                head: pr.head,
+                base: pr.base,
+                title: pr.title,
+                body: pr.body,
+                draft: pr.draft,
+                reviewers: pr.reviewers,
+                assignees: pr.assignees,
+                reviewed_by: pr.reviewed_by,
+                labels: pr.labels,
+                comments: pr
+                    .comments
+                    .into_iter()
+                    .map(|c| super::Comment {
+                        id: c.id,
+                        body: c.content,
+                        editable: c.editable,
+                    })
+                    .collect(),
+                review_decision: pr.review_decision,
+                checks: pr.checks,
+                mergeable: Some(pr.mergeable),
+                reviewed_commit: pr.reviewed_commit,
+                head_commit: pr.head_commit,
+                review_count: pr.review_count,
+            }
+        }
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    struct Params {
+        number: Option<u64>,
+        head: Option<String>,
+        base: Option<String>,
+        title: Option<String>,
+        body: Option<String>,
+        #[serde(default)]
+        draft: bool,
+        #[serde(default)]
+        reviewers: Vec<String>,
+        #[serde(default)]
+        assignees: Vec<String>,
+        #[serde(default)]
+        labels: Vec<String>,
+        id: Option<String>,
+        branch: Option<String>,
+        method: Option<crate::github::MergeMethod>,
+        message: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Request {
+        id: u64,
+        method: String,
+        #[serde(default)]
+        params: Params,
+    }
+
+    async fn handle(
+        mut gh: &mut fakes::GitHub,
+        method: &str,
+        p: Params,
+    ) -> crate::error::Result<serde_json::Value> {
+        let pr = match p.number {
+            Some(number) => Some(gh.pull_request(number).await?),
+            None => None,
+        };
+        let pr = || {
+            pr.clone()
+                .ok_or_else(|| crate::error::Error::new("Missing number"))
+        };
+        let to_value = |pr: fakes::PullRequest| serde_json::to_value(super::PullRequest::from(pr));
+
+        Ok(match method {
+            "initialize" => serde_json::json!({ "user": "plugin-user" }),
+            "pull_request" => to_value(pr()?)?,
+            "pull_request_by_head" => {
+                to_value(gh.pull_request_by_head(p.head.unwrap_or_default()).await?)?
+            }
+            "pull_request_changed" => {
+                serde_json::json!(gh.pull_request_changed(pr()?.number).await?)
+            }
+            "open_pull_requests" => {
+                let prs = match p.base {
+                    Some(base) => gh.open_pull_requests_by_base(base).await?,
+                    None => gh.open_pull_requests().await?,
+                };
+                serde_json::to_value(
+                    prs.into_iter()
+                        .map(super::PullRequest::from)
+                        .collect::<Vec<_>>(),
+                )?
+            }
+            "new_pull_request" => to_value(
+                gh.new_pull_request(
+                    p.title.unwrap_or_default(),
+                    p.body.unwrap_or_default(),
+                    p.base.unwrap_or_default(),
+                    p.head.unwrap_or_default(),
+                    p.draft,
+                )
+                .await?,
+            )?,
+            "add_reviewers" => serde_json::json!(gh.add_reviewers(&pr()?, p.reviewers).await?),
+            "remove_reviewers" => {
+                serde_json::json!(gh.remove_reviewers(&pr()?, p.reviewers).await?)
+            }
+            "add_assignees" => serde_json::json!(gh.add_assignees(&pr()?, p.assignees).await?),
+            "remove_assignees" => {
+                serde_json::json!(gh.remove_assignees(&pr()?, p.assignees).await?)
+            }
+            "add_labels" => serde_json::json!(gh.add_labels(&pr()?, p.labels).await?),
+            "remove_labels" => serde_json::json!(gh.remove_labels(&pr()?, p.labels).await?),
+            "edit_pull_request" => {
+                serde_json::json!(gh.edit_pull_request(&pr()?, p.title, p.body).await?)
+            }
+            "set_draft" => serde_json::json!(gh.set_draft(&pr()?, p.draft).await?),
+            "post_comment" => {
+                serde_json::json!(gh.post_comment(&pr()?, p.body.unwrap_or_default()).await?)
+            }
+            "update_comment" => serde_json::json!(
+                gh.update_issue_comment(p.id.unwrap_or_default(), p.body.unwrap_or_default())
+                    .await?
+            ),
+            "rebase_pull_request" => serde_json::json!(
+                gh.rebase_pr(pr()?.number, p.base.unwrap_or_default())
+                    .await?
+            ),
+            "merge_pull_request" => serde_json::json!(
+                gh.merge_pull_request(
+                    &pr()?,
+                    p.method.unwrap_or(crate::github::MergeMethod::Squash),
+                    p.title.unwrap_or_default(),
+                    p.message.unwrap_or_default(),
+                )
+                .await?
+            ),
+            "close_pull_request" => serde_json::json!(gh.close_pull_request(&pr()?).await?),
+            "reopen_pull_request" => serde_json::json!(gh.reopen_pull_request(&pr()?).await?),
+            "delete_branch" => {
+                serde_json::json!(gh.delete_branch(p.branch.unwrap_or_default()).await?)
+            }
+            _ => {
+                return Err(crate::error::Error::new(format!(
+                    "Unknown method {}",
+                    method
+                )));
+            }
+        })
+    }
+
+    /// The reference plugin: serves the protocol on `stream` from a fake GitHub, until spr hangs
+    /// up.
+    async fn serve(mut gh: fakes::GitHub, stream: tokio::io::DuplexStream) -> fakes::GitHub {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines = tokio::io::BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await.expect("Failed to read request") {
+            let request: Request = serde_json::from_str(&line).expect("Invalid request");
+            let response = match handle(&mut gh, &request.method, request.params).await {
+                Ok(result) => {
+                    serde_json::json!({ "jsonrpc": "2.0", "id": request.id, "result": result })
+                }
+                Err(err) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request.id,
+                    "error": { "code": 1, "message": err.messages().join(": ") },
+                }),
+            };
+            writer
+                .write_all(format!("{}\n", response).as_bytes())
+                .await
+                .expect("Failed to write response");
+        }
+        gh
+    }
+
+    /// Run `f` against the reference plugin serving `gh`, and return the fake afterwards.
+    async fn with_plugin<F>(gh: fakes::GitHub, f: F) -> fakes::GitHub
+    where
+        F: AsyncFnOnce(
+            &mut super::Plugin<
+                tokio::io::ReadHalf<tokio::io::DuplexStream>,
+                tokio::io::WriteHalf<tokio::io::DuplexStream>,
+            >,
+        ),
+    {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let client = async move {
+            let (reader, writer) = tokio::io::split(client);
+            let mut plugin = super::Plugin::new(reader, writer);
+            f(&mut plugin).await;
+        };
+        let (_, gh) = tokio::join!(client, serve(gh, server));
+        gh
+    }
+
+    #[tokio::test]
+    async fn drives_a_stack() {
+        let mut gh = fakes::GitHub::new();
+        gh.pull_requests
+            .insert(1, fakes::PullRequest::new("main", "spr/one", 1, "One", ""));
+        gh.teams = vec![String::from("org/team")];
+
+        let gh = with_plugin(gh, async |mut plugin| {
+            assert_eq!(
+                plugin
+                    .initialize("owner", "repo", "origin")
+                    .await
+                    .expect("Failed to initialize"),
+                "plugin-user"
+            );
+
+            let pr = plugin
+                .new_pull_request("Two", "Second", "spr/one", "spr/two", false)
+                .await
+                .expect("Failed to create PR");
+            assert_eq!(pr.pr_number(), 2);
+            plugin
+                .add_reviewers(&pr, ["alice", "org/team"])
+                .await
+                .expect("Failed to add reviewers");
+            plugin
+                .add_assignees(&pr, ["bob"])
+                .await
+                .expect("Failed to add assignees");
+            plugin
+                .update_pr_comment(&pr, "Stack")
+                .await
+                .expect("Failed to post comment");
+            let pr = plugin
+                .pull_request_by_head("spr/two")
+                .await
+                .expect("Failed to find PR");
+            plugin
+                .update_pr_comment(&pr, "Updated stack")
+                .await
+                .expect("Failed to update comment");
+            plugin
+                .rebase_pr(2, "main")
+                .await
+                .expect("Failed to rebase PR");
+        })
+        .await;
+
+        let pr = gh.pull_requests.get(&2).expect("PR 2 should exist");
+        assert_eq!(pr.base, "main");
+        assert_eq!(pr.reviewers, vec!["alice", "org/team"]);
+        assert_eq!(pr.assignees, vec!["bob"]);
+        assert_eq!(pr.comments.len(), 1);
+        assert!(pr.comments[0].body().starts_with("Updated stack"));
+    }
+
+    #[tokio::test]
+    async fn reports_plugin_errors() {
+        with_plugin(fakes::GitHub::new(), async |mut plugin| {
+            let err = plugin
+                .pull_request(7)
+                .await
+                .expect_err("Found a PR that doesn't exist");
+            assert_eq!(err.messages(), &vec![String::from("No such PR")]);
+        })
+        .await;
+    }
+}
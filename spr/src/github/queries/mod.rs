@@ -1,2 +1,3 @@
 pub mod mutations;
+pub mod teamid;
 pub mod userid;
@@ -7,10 +7,17 @@
 
 use graphql_client::GraphQLQuery;
 
+// Custom scalar used by the PR fragment. Commit ids are kept as their hex representation.
+type GitObjectID = String;
+
 #[derive(Clone)]
 pub struct GitHub {
     config: crate::config::Config,
     crab: octocrab::Octocrab,
+    /// Client for GraphQL queries. The same as `crab`, except on GitHub Enterprise Server.
+    graphql: octocrab::Octocrab,
+    /// ETags of the PRs polled through `pull_request_changed`.
+    etags: std::collections::HashMap<u64, String>,
 }
 
 #[derive(GraphQLQuery)]
@@ -31,6 +38,24 @@ pub struct OldComments;
 )]
 pub struct ByHead;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.docs.graphql",
+    query_path = "src/gql/update_issuecomment.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct ByBase;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.docs.graphql",
+    query_path = "src/gql/update_issuecomment.graphql",
+    variables_derives = "Clone, Debug",
+    response_derives = "Clone, Debug"
+)]
+pub struct OpenPullRequests;
+
 impl From<old_comments::OldCommentsRepositoryPullRequest> for super::types::PullRequest {
     fn from(pr: old_comments::PR) -> Self {
         let assignees = pr
@@ -38,16 +63,80 @@ impl From<old_comments::OldCommentsRepositoryPullRequest> for super::types::Pull
             .nodes
             .unwrap_or(Vec::new())
             .into_iter()
-            .filter_map(|node| node.map(|assignee| assignee.id))
+            .filter_map(|node| node.map(|assignee| assignee.login))
+            .collect();
+        let reviewed_by = pr
+            .latest_reviews
+            .and_then(|r| r.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|node| node?.author.map(|author| author.login))
+            .collect();
+        let labels = pr
+            .labels
+            .and_then(|l| l.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|node| node.map(|label| label.name))
             .collect();
         let reviewers = pr
             .review_requests
             .and_then(|r| r.nodes)
             .unwrap_or(Vec::new())
             .into_iter()
-            .filter_map(|node| node.map(|r| r.id))
+            .filter_map(|node| {
+                use old_comments::PrReviewRequestsNodesRequestedReviewer as Reviewer;
+                match node?.requested_reviewer? {
+                    Reviewer::User(user) => Some(user.login),
+                    Reviewer::Team(team) => Some(team.combined_slug),
+                    _ => None,
+                }
+            })
             .collect();
 
+        let review_decision = pr.review_decision.and_then(|decision| match decision {
+            old_comments::PullRequestReviewDecision::APPROVED => {
+                Some(super::ReviewDecision::Approved)
+            }
+            old_comments::PullRequestReviewDecision::CHANGES_REQUESTED => {
+                Some(super::ReviewDecision::ChangesRequested)
+            }
+            old_comments::PullRequestReviewDecision::REVIEW_REQUIRED => {
+                Some(super::ReviewDecision::ReviewRequired)
+            }
+            old_comments::PullRequestReviewDecision::Other(_) => None,
+        });
+
+        let checks = pr
+            .commits
+            .nodes
+            .and_then(|nodes| nodes.into_iter().flatten().next_back())
+            .and_then(|node| node.commit.status_check_rollup)
+            .and_then(|rollup| match rollup.state {
+                old_comments::StatusState::SUCCESS => Some(super::CheckState::Success),
+                old_comments::StatusState::PENDING | old_comments::StatusState::EXPECTED => {
+                    Some(super::CheckState::Pending)
+                }
+                old_comments::StatusState::ERROR | old_comments::StatusState::FAILURE => {
+                    Some(super::CheckState::Failure)
+                }
+                old_comments::StatusState::Other(_) => None,
+            });
+
+        let review_count = pr.reviews.as_ref().map_or(0, |r| r.total_count as usize);
+        let reviewed_commit = pr
+            .reviews
+            .and_then(|r| r.nodes)
+            .and_then(|nodes| nodes.into_iter().flatten().next_back())
+            .and_then(|review| review.commit)
+            .map(|commit| commit.oid);
+
+        let mergeable = match pr.mergeable {
+            old_comments::MergeableState::MERGEABLE => super::Mergeable::Mergeable,
+            old_comments::MergeableState::CONFLICTING => super::Mergeable::Conflicting,
+            _ => super::Mergeable::Unknown,
+        };
+
         let comments = pr
             .comments
             .nodes
@@ -70,10 +159,19 @@ impl From<old_comments::OldCommentsRepositoryPullRequest> for super::types::Pull
             body: pr.body,
             title: pr.title,
             closed: pr.closed,
+            merged: pr.merged,
             draft: pr.is_draft,
-            _reviewers: reviewers,
-            _assignees: assignees,
+            reviewers,
+            assignees,
+            reviewed_by,
+            labels,
             comments,
+            review_decision,
+            checks,
+            mergeable,
+            reviewed_commit,
+            head_commit: pr.head_ref_oid,
+            review_count,
         }
     }
 }
@@ -89,9 +187,117 @@ impl From<by_head::PR> for super::types::PullRequest {
     }
 }
 
+/// Response of queries for lists of PRs, like `ByBase` and `OpenPullRequests`. Their nodes are read straight into the
+/// `PR` fragment type of `OldComments`, so they share its conversion.
+#[derive(serde::Deserialize)]
+struct PullRequestsData {
+    repository: Option<PullRequestsRepository>,
+}
+
+#[derive(serde::Deserialize)]
+struct PullRequestsRepository {
+    #[serde(rename = "pullRequests")]
+    pull_requests: PullRequestConnection,
+}
+
+#[derive(serde::Deserialize)]
+struct PullRequestConnection {
+    /// Only asked for by paginated queries.
+    #[serde(rename = "pageInfo")]
+    page_info: Option<PageInfo>,
+    nodes: Option<Vec<Option<old_comments::PR>>>,
+}
+
+#[derive(serde::Deserialize)]
+struct PageInfo {
+    #[serde(rename = "hasNextPage")]
+    has_next_page: bool,
+    #[serde(rename = "endCursor")]
+    end_cursor: Option<String>,
+}
+
+/// Response of lookups that tell missing accounts from failures by GitHub's error `type`,
+/// which `graphql_client::Error` drops.
+#[derive(serde::Deserialize)]
+struct LookupResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<LookupError>>,
+}
+
+#[derive(serde::Deserialize)]
+struct LookupError {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    message: String,
+}
+
 impl GitHub {
     pub fn new(config: crate::config::Config, crab: octocrab::Octocrab) -> Self {
-        Self { config, crab }
+        Self {
+            config,
+            graphql: crab.clone(),
+            crab,
+            etags: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Send GraphQL queries through `graphql`, for when they are served from another base URL
+    /// than the REST API, like on GitHub Enterprise Server.
+    pub fn with_graphql(mut self, graphql: octocrab::Octocrab) -> Self {
+        self.graphql = graphql;
+        self
+    }
+}
+
+impl GitHub {
+    /// Node id of the user `login`, or `None` if there is no such user.
+    async fn user_id(&self, login: &str) -> crate::error::Result<Option<String>> {
+        let variables = super::queries::userid::user_id::Variables {
+            login: login.to_string(),
+        };
+        let resp: LookupResponse<super::queries::userid::user_id::ResponseData> = self
+            .graphql
+            .graphql(&super::queries::userid::UserId::build_query(variables))
+            .await?;
+        // Unknown logins come back as a NOT_FOUND error without data. Anything else, like a
+        // rate limit or a missing scope, is a failure rather than an unknown user.
+        if let Some(err) = resp
+            .errors
+            .iter()
+            .flatten()
+            .find(|err| err.kind.as_deref() != Some("NOT_FOUND"))
+        {
+            return Err(crate::error::Error::new(format!(
+                "Looking up {}: {}",
+                login, err.message
+            )));
+        }
+        Ok(resp.data.and_then(|data| data.user).map(|user| user.id))
+    }
+
+    /// Node id of the team `org/slug`, or `None` if it doesn't exist or isn't visible.
+    async fn team_id(&self, org: &str, slug: &str) -> crate::error::Result<Option<String>> {
+        let variables = super::queries::teamid::team_id::Variables {
+            org: org.to_string(),
+            slug: slug.to_string(),
+        };
+        let resp: LookupResponse<super::queries::teamid::team_id::ResponseData> = self
+            .graphql
+            .graphql(&super::queries::teamid::TeamId::build_query(variables))
+            .await?;
+        // Unknown teams are null without an error. Errors mean that `org` isn't an
+        // organization, or that the token may not read its teams.
+        if let Some(err) = resp.errors.iter().flatten().next() {
+            return Err(crate::error::Error::new(format!(
+                "Looking up {}/{}: {}",
+                org, slug, err.message
+            )));
+        }
+        Ok(resp
+            .data
+            .and_then(|data| data.organization)
+            .and_then(|org| org.team)
+            .map(|team| team.id))
     }
 }
 
@@ -106,7 +312,7 @@ impl super::GitHubAdapter for &mut GitHub {
         };
 
         let resp: graphql_client::Response<old_comments::ResponseData> = self
-            .crab
+            .graphql
             .graphql(&OldComments::build_query(variables))
             .await?;
         if let Some(errs) = resp.errors
@@ -137,8 +343,10 @@ impl super::GitHubAdapter for &mut GitHub {
             head: head.clone(),
         };
 
-        let resp: graphql_client::Response<by_head::ResponseData> =
-            self.crab.graphql(&ByHead::build_query(variables)).await?;
+        let resp: graphql_client::Response<by_head::ResponseData> = self
+            .graphql
+            .graphql(&ByHead::build_query(variables))
+            .await?;
         if let Some(errs) = resp.errors
             && !errs.is_empty()
         {
@@ -171,6 +379,120 @@ impl super::GitHubAdapter for &mut GitHub {
         }
     }
 
+    async fn pull_request_changed(&mut self, number: u64) -> crate::error::Result<bool> {
+        use reqwest::header::{ETAG, HeaderMap, IF_NONE_MATCH};
+
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = self.etags.get(&number) {
+            headers.insert(IF_NONE_MATCH, etag.parse()?);
+        }
+        let response = self
+            .crab
+            ._get_with_headers(
+                format!(
+                    "/repos/{}/{}/pulls/{}",
+                    self.config.owner, self.config.repo, number
+                ),
+                Some(headers),
+            )
+            .await?;
+
+        // GitHub doesn't count 304 answers against the rate limit.
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            return Err(crate::error::Error::new(format!(
+                "Polling PR #{} failed with {}",
+                number,
+                response.status()
+            )));
+        }
+        if let Some(etag) = response.headers().get(ETAG).and_then(|e| e.to_str().ok()) {
+            self.etags.insert(number, etag.to_string());
+        }
+        Ok(true)
+    }
+
+    async fn open_pull_requests(&mut self) -> crate::error::Result<Vec<Self::PRAdapter>> {
+        let mut ret = Vec::new();
+        let mut after = None;
+        loop {
+            let variables = open_pull_requests::Variables {
+                owner: self.config.owner.clone(),
+                name: self.config.repo.clone(),
+                after,
+            };
+
+            let resp: graphql_client::Response<PullRequestsData> = self
+                .graphql
+                .graphql(&OpenPullRequests::build_query(variables))
+                .await?;
+            if let Some(errs) = resp.errors
+                && !errs.is_empty()
+            {
+                return Err(crate::error::Error::new(format!("{:?}", errs)));
+            }
+
+            let pull_requests = resp
+                .data
+                .ok_or_else(|| crate::error::Error::new("No data on open PRs request"))?
+                .repository
+                .ok_or_else(|| crate::error::Error::new("No repository in open PRs request"))?
+                .pull_requests;
+            ret.extend(
+                pull_requests
+                    .nodes
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(super::types::PullRequest::from),
+            );
+
+            match pull_requests.page_info {
+                Some(page_info) if page_info.has_next_page => after = page_info.end_cursor,
+                _ => return Ok(ret),
+            }
+        }
+    }
+
+    async fn open_pull_requests_by_base<S>(
+        &mut self,
+        base: S,
+    ) -> crate::error::Result<Vec<Self::PRAdapter>>
+    where
+        S: Into<String>,
+    {
+        let variables = by_base::Variables {
+            owner: self.config.owner.clone(),
+            name: self.config.repo.clone(),
+            base: base.into(),
+        };
+
+        let resp: graphql_client::Response<PullRequestsData> = self
+            .graphql
+            .graphql(&ByBase::build_query(variables))
+            .await?;
+        if let Some(errs) = resp.errors
+            && !errs.is_empty()
+        {
+            return Err(crate::error::Error::new(format!("{:?}", errs)));
+        }
+
+        Ok(resp
+            .data
+            .ok_or_else(|| crate::error::Error::new("No data on by_base request"))?
+            .repository
+            .ok_or_else(|| crate::error::Error::new("No repository in by_base request"))?
+            .pull_requests
+            .nodes
+            .ok_or_else(|| crate::error::Error::new("No nodes in pull_requests for by_base"))?
+            .into_iter()
+            .flatten()
+            .map(super::types::PullRequest::from)
+            .collect())
+    }
+
     async fn pull_requests<I>(
         &mut self,
         numbers: I,
@@ -231,15 +553,25 @@ impl super::GitHubAdapter for &mut GitHub {
                 .node_id
                 .ok_or_else(|| crate::error::Error::new("No nodeID on new PR"))?,
             base: String::from(base_ref_name.as_ref()),
-            head: String::from(head_ref_name.as_ref()),
+            // Without the owner of the fork that `head_ref_name` may have.
+            head: octo_pr.head.ref_field.clone(),
             number: octo_pr.number,
             title: octo_pr.title.unwrap_or(String::new()),
             body: octo_pr.body.unwrap_or(String::new()),
-            _reviewers: Vec::new(),
-            _assignees: Vec::new(),
+            reviewers: Vec::new(),
+            assignees: Vec::new(),
+            reviewed_by: Vec::new(),
+            labels: Vec::new(),
             comments: Vec::new(),
             closed: false,
+            merged: false,
             draft,
+            review_decision: None,
+            checks: None,
+            mergeable: super::Mergeable::Unknown,
+            reviewed_commit: None,
+            head_commit: octo_pr.head.sha,
+            review_count: 0,
         })
     }
 
@@ -252,16 +584,38 @@ impl super::GitHubAdapter for &mut GitHub {
         S: Into<String>,
         I: IntoIterator<Item = S>,
     {
-        let reviewers = reviewers.into_iter().map(|s| s.into());
+        let mut users = Vec::new();
+        let mut teams = Vec::new();
+        let mut unknown = Vec::new();
+        for reviewer in reviewers
+            .into_iter()
+            .map(|s| super::Reviewer::parse(&s.into()))
+        {
+            let (id, ids) = match reviewer {
+                super::Reviewer::User(ref login) => (self.user_id(login).await?, &mut users),
+                super::Reviewer::Team { ref org, ref slug } => {
+                    (self.team_id(org, slug).await?, &mut teams)
+                }
+            };
+            match id {
+                Some(id) => ids.push(id),
+                None => unknown.push(reviewer.to_string()),
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(super::traits::unknown_accounts(&unknown));
+        }
+
         let variables = super::queries::mutations::request_reviews::Variables {
             pull_request_id: pr.node.clone(),
-            users: Some(reviewers.collect()),
+            users: Some(users),
+            teams: Some(teams),
         };
 
         let resp: graphql_client::Response<
             super::queries::mutations::request_reviews::ResponseData,
         > = self
-            .crab
+            .graphql
             .graphql(&super::queries::mutations::RequestReviews::build_query(
                 variables,
             ))
@@ -285,36 +639,15 @@ impl super::GitHubAdapter for &mut GitHub {
         I: IntoIterator<Item = S>,
     {
         let mut assignee_ids = Vec::new();
+        let mut unknown = Vec::new();
         for assignee_login in assignees.into_iter().map(|s| s.into()) {
-            let variables = super::queries::userid::user_id::Variables {
-                login: assignee_login.clone(),
-            };
-
-            let resp: graphql_client::Response<super::queries::userid::user_id::ResponseData> =
-                self.crab
-                    .graphql(&super::queries::userid::UserId::build_query(variables))
-                    .await?;
-            if let Some(errs) = resp.errors
-                && !errs.is_empty()
-            {
-                return Err(crate::error::Error::new(format!("{:?}", errs)));
+            match self.user_id(&assignee_login).await? {
+                Some(id) => assignee_ids.push(id),
+                None => unknown.push(assignee_login),
             }
-
-            let id = resp
-                .data
-                .ok_or_else(|| {
-                    crate::error::Error::new(format!(
-                        "No data on UserID request for {assignee_login}"
-                    ))
-                })?
-                .user
-                .ok_or_else(|| {
-                    crate::error::Error::new(
-                        "No user in data for UserId request for {assignee_login}",
-                    )
-                })?
-                .id;
-            assignee_ids.push(id);
+        }
+        if !unknown.is_empty() {
+            return Err(super::traits::unknown_accounts(&unknown));
         }
 
         let variables = super::queries::mutations::add_assignees::Variables {
@@ -323,7 +656,7 @@ impl super::GitHubAdapter for &mut GitHub {
         };
 
         let resp: graphql_client::Response<super::queries::mutations::add_assignees::ResponseData> =
-            self.crab
+            self.graphql
                 .graphql(&super::queries::mutations::AddAssignees::build_query(
                     variables,
                 ))
@@ -354,7 +687,7 @@ impl super::GitHubAdapter for &mut GitHub {
         let resp: graphql_client::Response<
             super::queries::mutations::update_issue_comment::ResponseData,
         > = self
-            .crab
+            .graphql
             .graphql(&super::queries::mutations::UpdateIssueComment::build_query(
                 variables,
             ))
@@ -367,6 +700,87 @@ impl super::GitHubAdapter for &mut GitHub {
         return Ok(());
     }
 
+    async fn remove_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let mut users = Vec::new();
+        let mut teams = Vec::new();
+        for reviewer in reviewers
+            .into_iter()
+            .map(|s| super::Reviewer::parse(&s.into()))
+        {
+            match reviewer {
+                super::Reviewer::User(login) => users.push(login),
+                // The REST API only wants the slug, the organization is the repository's.
+                super::Reviewer::Team { slug, .. } => teams.push(slug),
+            }
+        }
+        self.crab
+            .pulls(self.config.owner.clone(), self.config.repo.clone())
+            .remove_requested_reviewers(pr.number, users, teams)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let assignees: Vec<String> = assignees.into_iter().map(|s| s.into()).collect();
+        let assignees: Vec<&str> = assignees.iter().map(|s| s.as_str()).collect();
+        self.crab
+            .issues(self.config.owner.clone(), self.config.repo.clone())
+            .remove_assignees(pr.number, &assignees)
+            .await?;
+        Ok(())
+    }
+
+    async fn add_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let labels: Vec<String> = labels.into_iter().map(|s| s.into()).collect();
+        self.crab
+            .issues(self.config.owner.clone(), self.config.repo.clone())
+            .add_labels(pr.number, &labels)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let issues = self
+            .crab
+            .issues(self.config.owner.clone(), self.config.repo.clone());
+        for label in labels.into_iter().map(|s| s.into()) {
+            issues.remove_label(pr.number, label).await?;
+        }
+        Ok(())
+    }
+
     async fn post_comment<C>(
         &mut self,
         pr: &Self::PRAdapter,
@@ -381,7 +795,7 @@ impl super::GitHubAdapter for &mut GitHub {
         };
 
         let resp: graphql_client::Response<super::queries::mutations::add_comment::ResponseData> =
-            self.crab
+            self.graphql
                 .graphql(&super::queries::mutations::AddComment::build_query(
                     variables,
                 ))
@@ -394,6 +808,58 @@ impl super::GitHubAdapter for &mut GitHub {
         Ok(())
     }
 
+    async fn edit_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+        title: Option<String>,
+        body: Option<String>,
+    ) -> crate::error::Result<()> {
+        self.crab
+            .pulls(self.config.owner.clone(), self.config.repo.clone())
+            .update(pr.number)
+            .title::<String>(title)
+            .body::<String>(body)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn set_draft(&mut self, pr: &Self::PRAdapter, draft: bool) -> crate::error::Result<()> {
+        let errors = if draft {
+            let variables = super::queries::mutations::convert_to_draft::Variables {
+                pull_request_id: pr.node.clone(),
+            };
+            let resp: graphql_client::Response<
+                super::queries::mutations::convert_to_draft::ResponseData,
+            > = self
+                .graphql
+                .graphql(&super::queries::mutations::ConvertToDraft::build_query(
+                    variables,
+                ))
+                .await?;
+            resp.errors
+        } else {
+            let variables = super::queries::mutations::mark_ready_for_review::Variables {
+                pull_request_id: pr.node.clone(),
+            };
+            let resp: graphql_client::Response<
+                super::queries::mutations::mark_ready_for_review::ResponseData,
+            > = self
+                .graphql
+                .graphql(&super::queries::mutations::MarkReadyForReview::build_query(
+                    variables,
+                ))
+                .await?;
+            resp.errors
+        };
+        if let Some(errs) = errors
+            && !errs.is_empty()
+        {
+            return Err(crate::error::Error::new(format!("{:?}", errs)));
+        }
+        Ok(())
+    }
+
     async fn rebase_pr<S>(&mut self, number: u64, new_base: S) -> crate::error::Result<()>
     where
         S: Into<String>,
@@ -414,7 +880,7 @@ impl super::GitHubAdapter for &mut GitHub {
         let resp: graphql_client::Response<
             super::queries::mutations::update_pr_base::ResponseData,
         > = self
-            .crab
+            .graphql
             .graphql(&super::queries::mutations::UpdatePRBase::build_query(
                 variables,
             ))
@@ -426,4 +892,81 @@ impl super::GitHubAdapter for &mut GitHub {
         }
         Ok(())
     }
+
+    async fn merge_pull_request<T, M>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        method: super::MergeMethod,
+        title: T,
+        message: M,
+    ) -> crate::error::Result<()>
+    where
+        T: Into<String>,
+        M: Into<String>,
+    {
+        let pulls = self
+            .crab
+            .pulls(self.config.owner.clone(), self.config.repo.clone());
+        let builder = pulls.merge(pr.number);
+        // GitHub ignores title and message for rebase merges, so only send them when they are used.
+        let builder = match method {
+            super::MergeMethod::Merge => builder
+                .method(octocrab::params::pulls::MergeMethod::Merge)
+                .title(title)
+                .message(message),
+            super::MergeMethod::Squash => builder
+                .method(octocrab::params::pulls::MergeMethod::Squash)
+                .title(title)
+                .message(message),
+            super::MergeMethod::Rebase => {
+                builder.method(octocrab::params::pulls::MergeMethod::Rebase)
+            }
+        };
+
+        let merge = builder.send().await?;
+        if !merge.merged {
+            return Err(crate::error::Error::new(format!(
+                "GitHub did not merge PR #{}: {}",
+                pr.number,
+                merge.message.unwrap_or_default()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn close_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.crab
+            .pulls(self.config.owner.clone(), self.config.repo.clone())
+            .update(pr.number)
+            .state(octocrab::params::pulls::State::Closed)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn reopen_pull_request(&mut self, pr: &Self::PRAdapter) -> crate::error::Result<()> {
+        self.crab
+            .pulls(self.config.owner.clone(), self.config.repo.clone())
+            .update(pr.number)
+            .state(octocrab::params::pulls::State::Open)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_branch<S>(&mut self, branch: S) -> crate::error::Result<()>
+    where
+        S: Into<String>,
+    {
+        // PR branches are in the fork, if there is one.
+        let (owner, repo) = match self.config.fork {
+            Some(ref fork) => (fork.owner.clone(), fork.repo.clone()),
+            None => (self.config.owner.clone(), self.config.repo.clone()),
+        };
+        self.crab
+            .repos(owner, repo)
+            .delete_ref(&octocrab::params::repos::Reference::Branch(branch.into()))
+            .await?;
+        Ok(())
+    }
 }
@@ -1,5 +1,104 @@
 static COMMENT_MARKER: &str = "\n<!--creatd by jj-spr-->";
 
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMethod {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullRequestState {
+    Open,
+    Closed,
+    Merged,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+/// Rollup of all CI checks and commit statuses on the head commit of a PR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckState {
+    Pending,
+    Success,
+    Failure,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mergeable {
+    Mergeable,
+    Conflicting,
+    Unknown,
+}
+
+/// Someone to request a review from. Teams are written as `org/team`, optionally with a
+/// leading `@` like in CODEOWNERS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reviewer {
+    User(String),
+    Team { org: String, slug: String },
+}
+
+impl Reviewer {
+    pub fn parse(name: &str) -> Self {
+        let name = name.trim();
+        let name = name.strip_prefix('@').unwrap_or(name);
+        match name.split_once('/') {
+            Some((org, slug)) => Reviewer::Team {
+                org: org.to_string(),
+                slug: slug.to_string(),
+            },
+            None => Reviewer::User(name.to_string()),
+        }
+    }
+
+    /// Whether `name` is this user or team. GitHub ignores case in both.
+    pub fn is(&self, name: &str) -> bool {
+        self.to_string()
+            .eq_ignore_ascii_case(&Reviewer::parse(name).to_string())
+    }
+}
+
+impl std::fmt::Display for Reviewer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reviewer::User(login) => f.write_str(login),
+            Reviewer::Team { org, slug } => write!(f, "{}/{}", org, slug),
+        }
+    }
+}
+
+/// Error for reviewers or assignees that don't exist on GitHub.
+pub(super) fn unknown_accounts(names: &[String]) -> crate::error::Error {
+    crate::error::Error::new(format!(
+        "These users or teams don't exist or can't be seen: {}",
+        names.join(", ")
+    ))
+}
+
+pub(super) fn find_pr_comment<PR: GHPullRequest>(pr: &PR) -> Option<PR::PRComment> {
+    pr.comments()
+        .into_iter()
+        .find(|c| c.editable() && c.body().strip_suffix(COMMENT_MARKER).is_some())
+}
+
+/// Whether `update_pr_comment` with this content would change anything on the PR.
+pub fn pr_comment_outdated<PR: GHPullRequest>(pr: &PR, content: &str) -> bool {
+    find_pr_comment(pr).is_none_or(|old| old.body() != format!("{}{}", content, COMMENT_MARKER))
+}
+
 pub trait GithubPRComment {
     fn editable(&self) -> bool;
     fn body(&self) -> &str;
@@ -16,6 +115,21 @@ pub trait GHPullRequest {
     fn title(&self) -> &str;
     fn closed(&self) -> bool;
     fn comments(&self) -> Vec<Self::PRComment>;
+    fn state(&self) -> PullRequestState;
+    fn draft(&self) -> bool;
+    fn review_decision(&self) -> Option<ReviewDecision>;
+    fn requested_reviewers(&self) -> &[String];
+    /// Users who submitted a review. GitHub drops them from the requested reviewers.
+    fn reviewed_by(&self) -> &[String];
+    fn assignees(&self) -> &[String];
+    fn labels(&self) -> &[String];
+    fn checks(&self) -> Option<CheckState>;
+    fn mergeable(&self) -> Mergeable;
+    /// The commit the most recent review was left on, if there is any review.
+    fn reviewed_commit(&self) -> Option<&str>;
+    /// The commit the head branch points to on GitHub.
+    fn head_commit(&self) -> &str;
+    fn review_count(&self) -> usize;
 }
 
 pub trait GitHubAdapter {
@@ -33,6 +147,27 @@ pub trait GitHubAdapter {
     where
         S: Into<String>;
 
+    /// Whether the PR changed since the last time this was asked for it. The first call always
+    /// reports a change. Meant for polling, so it should use conditional requests that don't
+    /// count against the rate limit.
+    fn pull_request_changed(
+        &mut self,
+        number: u64,
+    ) -> impl std::future::Future<Output = crate::error::Result<bool>>;
+
+    /// All open PRs in the repository.
+    fn open_pull_requests(
+        &mut self,
+    ) -> impl std::future::Future<Output = crate::error::Result<Vec<Self::PRAdapter>>>;
+
+    /// All open PRs that are based on `base`.
+    fn open_pull_requests_by_base<S>(
+        &mut self,
+        base: S,
+    ) -> impl std::future::Future<Output = crate::error::Result<Vec<Self::PRAdapter>>>
+    where
+        S: Into<String>;
+
     fn new_pull_request<H, B, St, Sb>(
         &mut self,
         title: St,
@@ -85,6 +220,58 @@ pub trait GitHubAdapter {
         S: Into<String>,
         I: IntoIterator<Item = S>;
 
+    /// Withdraw review requests. Reviews that were already submitted stay.
+    fn remove_reviewers<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        reviewers: I,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>;
+
+    fn remove_assignees<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        assignees: I,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>;
+
+    fn add_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>;
+
+    fn remove_labels<S, I>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        labels: I,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>;
+
+    /// Change the title or body of a PR. `None` leaves that part as it is.
+    fn edit_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+        title: Option<String>,
+        body: Option<String>,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>;
+
+    /// Convert a PR to a draft, or mark it as ready for review.
+    fn set_draft(
+        &mut self,
+        pr: &Self::PRAdapter,
+        draft: bool,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>;
+
     fn post_comment<C>(
         &mut self,
         pr: &Self::PRAdapter,
@@ -111,13 +298,9 @@ pub trait GitHubAdapter {
         S: Into<String>,
     {
         async move {
-            let comments = pr.comments();
             let content = format!("{}{}", content.into(), COMMENT_MARKER);
 
-            if let Some(old) = comments
-                .into_iter()
-                .find(|c| c.editable() && c.body().strip_suffix(COMMENT_MARKER).is_some())
-            {
+            if let Some(old) = find_pr_comment(pr) {
                 if old.body() == content {
                     return Ok(());
                 }
@@ -136,4 +319,33 @@ pub trait GitHubAdapter {
     ) -> impl std::future::Future<Output = crate::error::Result<()>>
     where
         S: Into<String>;
+
+    fn merge_pull_request<T, M>(
+        &mut self,
+        pr: &Self::PRAdapter,
+        method: MergeMethod,
+        title: T,
+        message: M,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>
+    where
+        T: Into<String>,
+        M: Into<String>;
+
+    fn close_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>;
+
+    /// Reopen a PR that was closed without being merged.
+    fn reopen_pull_request(
+        &mut self,
+        pr: &Self::PRAdapter,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>;
+
+    fn delete_branch<S>(
+        &mut self,
+        branch: S,
+    ) -> impl std::future::Future<Output = crate::error::Result<()>>
+    where
+        S: Into<String>;
 }
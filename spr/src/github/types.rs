@@ -13,11 +13,21 @@ pub struct PullRequest {
     pub node: String,
     pub title: String,
     pub body: String,
-    pub _reviewers: Vec<String>,
-    pub _assignees: Vec<String>,
+    pub reviewers: Vec<String>,
+    pub assignees: Vec<String>,
+    /// Users who submitted a review.
+    pub reviewed_by: Vec<String>,
+    pub labels: Vec<String>,
     pub comments: Vec<PullRequestComment>,
     pub closed: bool,
+    pub merged: bool,
     pub draft: bool,
+    pub review_decision: Option<super::ReviewDecision>,
+    pub checks: Option<super::CheckState>,
+    pub mergeable: super::Mergeable,
+    pub reviewed_commit: Option<String>,
+    pub head_commit: String,
+    pub review_count: usize,
 }
 
 impl super::GithubPRComment for PullRequestComment {
@@ -64,4 +74,58 @@ impl super::GHPullRequest for PullRequest {
     fn comments(&self) -> Vec<Self::PRComment> {
         self.comments.clone()
     }
+
+    fn state(&self) -> super::PullRequestState {
+        if self.merged {
+            super::PullRequestState::Merged
+        } else if self.closed {
+            super::PullRequestState::Closed
+        } else {
+            super::PullRequestState::Open
+        }
+    }
+
+    fn draft(&self) -> bool {
+        self.draft
+    }
+
+    fn review_decision(&self) -> Option<super::ReviewDecision> {
+        self.review_decision
+    }
+
+    fn requested_reviewers(&self) -> &[String] {
+        self.reviewers.as_slice()
+    }
+
+    fn reviewed_by(&self) -> &[String] {
+        self.reviewed_by.as_slice()
+    }
+
+    fn assignees(&self) -> &[String] {
+        self.assignees.as_slice()
+    }
+
+    fn labels(&self) -> &[String] {
+        self.labels.as_slice()
+    }
+
+    fn checks(&self) -> Option<super::CheckState> {
+        self.checks
+    }
+
+    fn mergeable(&self) -> super::Mergeable {
+        self.mergeable
+    }
+
+    fn reviewed_commit(&self) -> Option<&str> {
+        self.reviewed_commit.as_deref()
+    }
+
+    fn head_commit(&self) -> &str {
+        self.head_commit.as_ref()
+    }
+
+    fn review_count(&self) -> usize {
+        self.review_count
+    }
 }
@@ -24,7 +24,20 @@ use serde::Deserialize;
 //r#""{\"parents\": " ++ json(parents.map(|c| c.change_id())) ++ ", \"bookmarks\": " ++ json(bookmarks.map(|b| b.name())) ++ ", \"description\": " ++ json(description) ++ ", \"change_id\": " ++ json(change_id) ++ "}""#,
 static REVISION_TEMPLATE: &'static str = r#""{\"parents\": [" ++ parents.map(|c| json(c.change_id())).join(",") ++ "], \"bookmarks\": [" ++ bookmarks.map(|b| json(b.name())).join(",") ++ "], \"description\": " ++ json(description) ++ ", \"change_id\": " ++ json(change_id) ++ " }\n""#;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Trailer spr adds to the commits it creates, naming the revision they were derived from.
+pub const CHANGE_ID_TRAILER: &str = "Change-Id";
+
+/// Change ids named by [`CHANGE_ID_TRAILER`] lines in `message`. Squash merges tend to indent
+/// or bullet the messages they combine, so any prefix is accepted. Only jj's change id alphabet
+/// (`k`-`z`) matches, so Gerrit's `Change-Id: I…` trailers are ignored.
+pub fn change_id_trailers(message: &str) -> impl Iterator<Item = ChangeId> + '_ {
+    let regex = lazy_regex::regex!(r#"(?m)^\W*Change-Id:\s*([k-z]+)\s*$"#);
+    regex
+        .captures_iter(message)
+        .map(|caps| ChangeId::from(caps.get(1).unwrap().as_str()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ChangeId {
     id: String,
 }
@@ -65,34 +78,13 @@ struct RawRevision {
     description: String,
 }
 
-fn parse_pull_request_field(text: &str) -> Option<u64> {
-    if text.is_empty() {
-        return None;
-    }
-
-    let regex = lazy_regex::regex!(r#"^\s*#?\s*(\d+)\s*$"#);
-    let m = regex.captures(text);
-    if let Some(caps) = m {
-        return Some(caps.get(1).unwrap().as_str().parse().unwrap());
-    }
-
-    let regex = lazy_regex::regex!(
-        r#"^\s*https?://github.com/([\w\-\.]+)/([\w\-\.]+)/pull/(\d+)([/?#].*)?\s*$"#
-    );
-    let m = regex.captures(text);
-    if let Some(caps) = m {
-        return Some(caps.get(3).unwrap().as_str().parse().unwrap());
-    }
-
-    None
-}
-
-impl From<RawRevision> for Revision {
-    fn from(value: RawRevision) -> Self {
+impl RawRevision {
+    fn into_revision(self, config: &Config) -> Revision {
+        let value = self;
         let message = parse_message(value.description.as_ref(), MessageSection::Title);
         let pull_request_number = message
             .get(&MessageSection::PullRequest)
-            .and_then(|url| parse_pull_request_field(url));
+            .and_then(|url| config.parse_pull_request_field(url));
         let title = String::from(
             message
                 .get(&MessageSection::Title)
@@ -234,10 +226,18 @@ impl RevSet {
         RevSet(format!("::({})", self.0))
     }
 
+    pub fn descendants(&self) -> Self {
+        RevSet(format!("({})::", self.0))
+    }
+
     pub fn parent(&self) -> Self {
         RevSet(format!("({})-", self.0))
     }
 
+    pub fn children(&self) -> Self {
+        RevSet(format!("({})+", self.0))
+    }
+
     pub fn heads(&self) -> Self {
         RevSet(format!("heads({})", self.0))
     }
@@ -317,7 +317,7 @@ impl Jujutsu {
         self.prepare_commit(config, commit_oid)
     }
 
-    pub fn read_revision_range(&self, range: &RevSet) -> Result<Vec<Revision>> {
+    pub fn read_revision_range(&self, config: &Config, range: &RevSet) -> Result<Vec<Revision>> {
         let output = self.run_ro_captured_with_args([
             "log",
             "--no-graph",
@@ -332,15 +332,15 @@ impl Jujutsu {
         for line in output.lines() {
             let raw: RawRevision = serde_json::from_str(line.trim())
                 .context(String::from("Decode revision in range"))?;
-            ret.push(Revision::from(raw))
+            ret.push(raw.into_revision(config))
         }
 
         Ok(ret)
     }
 
-    pub fn read_revision(&self, id: ChangeId) -> Result<Revision> {
+    pub fn read_revision(&self, config: &Config, id: ChangeId) -> Result<Revision> {
         if let Some(r) = self
-            .read_revision_range(&RevSet::from(&id).unique())?
+            .read_revision_range(config, &RevSet::from(&id).unique())?
             .into_iter()
             .next()
         {
@@ -445,6 +445,12 @@ impl Jujutsu {
         parent_oids: &[Oid],
     ) -> Result<ChangeId> {
         let original_commit = RevSet::from(&self.git_repo.find_commit(original_commit_oid)?);
+        let message = format!(
+            "{}\n\n{}: {}",
+            message.trim_end(),
+            CHANGE_ID_TRAILER,
+            self.get_change_id_for_commit(original_commit_oid)?
+        );
 
         let parents: std::result::Result<Vec<RevSet>, _> = parent_oids
             .iter()
@@ -455,7 +461,7 @@ impl Jujutsu {
                     .map(|c| RevSet::from(&c))
             })
             .collect();
-        self.new_revision(parents?, Some(message), true)
+        self.new_revision(parents?, Some(&message), true)
             .context(String::from("Create new for derived commit"))?;
 
         let change = self
@@ -616,6 +622,47 @@ impl Jujutsu {
         Ok(output.lines().map(|l| ChangeId::from(l.trim())).collect())
     }
 
+    /// Change and commit ids of the revisions in `revset`.
+    pub fn revset_to_commits(&self, revset: &RevSet) -> Result<Vec<(ChangeId, Oid)>> {
+        let output = self.run_ro_captured_with_args([
+            "log",
+            "--no-graph",
+            "--reversed",
+            "-r",
+            revset.as_ref(),
+            "--template",
+            "change_id ++ \" \" ++ commit_id ++ \"\\n\"",
+        ])?;
+
+        output
+            .lines()
+            .filter_map(|l| l.trim().split_once(' '))
+            .map(|(change, commit)| {
+                let oid = Oid::from_str(commit).map_err(|e| {
+                    Error::new(format!("Failed to parse commit ID '{}': {}", commit, e))
+                })?;
+                Ok((ChangeId::from(change), oid))
+            })
+            .collect()
+    }
+
+    /// Patch-id of the changes `commit_oid` makes on top of its first parent. `None` if it
+    /// doesn't change anything.
+    pub fn patch_id(&self, commit_oid: Oid) -> Result<Option<Oid>> {
+        let commit = self.git_repo.find_commit(commit_oid)?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff =
+            self.git_repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        if diff.deltas().len() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(diff.patchid(None)?))
+    }
+
     pub fn revset_to_change_id(&self, revset: &RevSet) -> Result<ChangeId> {
         let ids = self.revset_to_change_ids(&revset.unique())?;
         if let Some(id) = ids.first() {
@@ -753,6 +800,17 @@ impl Jujutsu {
         self.run_captured_with_args(["git", "fetch"]).map(|_| {})
     }
 
+    /// Fetch like `run_git_fetch`, and from the fork PR branches are pushed to, if there is one.
+    pub fn run_git_fetch_for(&mut self, config: &Config) -> Result<()> {
+        self.run_git_fetch()?;
+        match config.fork {
+            Some(ref fork) => self
+                .run_captured_with_args(["git", "fetch", "--remote", fork.remote.as_str()])
+                .map(|_| {}),
+            None => Ok(()),
+        }
+    }
+
     pub fn new_revision<M: AsRef<str>, I>(
         &mut self,
         parents: I,
@@ -845,6 +903,29 @@ impl Jujutsu {
             .map(|_| {})
     }
 
+    /// Id of the latest jj operation, after snapshotting the working copy.
+    pub fn current_operation(&mut self) -> Result<String> {
+        self.run_captured_with_args(["operation", "log", "--no-graph", "--limit", "1", "-T", "id"])
+            .map(|id| id.trim().to_string())
+    }
+
+    pub fn restore_operation<S: AsRef<str>>(&mut self, operation: S) -> Result<()> {
+        self.run_captured_with_args(["operation", "restore", operation.as_ref()])
+            .map(|_| {})
+    }
+
+    /// Paths of the files `change` touches, relative to the workspace root.
+    pub fn changed_files(&self, change: &ChangeId) -> Result<Vec<String>> {
+        let output = self.run_ro_captured_with_args([
+            "diff",
+            "--name-only",
+            "-r",
+            RevSet::from(change).unique().as_ref(),
+        ])?;
+
+        Ok(output.lines().map(String::from).collect())
+    }
+
     pub fn is_empty(&self, change: &ChangeId) -> Result<bool> {
         let output = self.run_ro_captured_with_args([
             "log",
@@ -864,7 +945,7 @@ impl Jujutsu {
     }
 }
 
-fn get_jj_bin() -> PathBuf {
+pub(crate) fn get_jj_bin() -> PathBuf {
     std::env::var_os("JJ").map_or_else(|| "jj".into(), |v| v.into())
 }
 
@@ -984,14 +1065,14 @@ mod tests {
             &[commit2.as_str(), commit3.as_str()],
         );
 
-        let c1 = jj.read_revision(ChangeId::from(commit2));
+        let c1 = jj.read_revision(&testing::config::basic(), ChangeId::from(commit2));
         assert!(c1.is_ok(), "Failed to read @ revision: {:?}", c1.err());
         assert!(
             c1.unwrap().parent_ids.len() == 1,
             "Got more than one parent of c1",
         );
 
-        let c2 = jj.read_revision(ChangeId::from(commit4));
+        let c2 = jj.read_revision(&testing::config::basic(), ChangeId::from(commit4));
         assert!(c2.is_ok(), "Failed to read @ revision: {:?}", c2.err());
         assert!(
             c2.unwrap().parent_ids.len() == 2,
@@ -1152,6 +1233,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn change_id_trailers_skip_gerrit_ids() {
+        let message = "Squashed (#1)\n\n\
+            * Change-Id: zyxwvutsrqponmlkzyxwvutsrqponmlk\n\
+            Change-Id: I8473b95934b5732ac55d26311a706c9c2bde9940\n";
+        assert_eq!(
+            change_id_trailers(message).collect::<Vec<_>>(),
+            vec![ChangeId::from("zyxwvutsrqponmlkzyxwvutsrqponmlk")]
+        );
+    }
+
     #[test]
     fn jj_from_workspace() {
         let (temp_dr, jj, _) = testing::setup::repo_with_origin();
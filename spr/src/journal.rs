@@ -0,0 +1,241 @@
+//! Journal of the changes spr commands made locally and on GitHub.
+//! Every command opens an entry before it runs and closes it when it is done, so `undo` can
+//! revert the latest one.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, ResultExt};
+
+/// Only keep this many entries around. Older ones can't be undone anyway.
+const MAX_ENTRIES: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mutation", rename_all = "snake_case")]
+pub enum Mutation {
+    PullRequestCreated {
+        number: u64,
+    },
+    BaseChanged {
+        number: u64,
+        old_base: String,
+        new_base: String,
+    },
+    CommentPosted {
+        number: u64,
+    },
+    CommentEdited {
+        id: String,
+        old_body: Option<String>,
+    },
+    ReviewersRequested {
+        number: u64,
+        reviewers: Vec<String>,
+    },
+    ReviewersRemoved {
+        number: u64,
+        reviewers: Vec<String>,
+    },
+    AssigneesAdded {
+        number: u64,
+        assignees: Vec<String>,
+    },
+    AssigneesRemoved {
+        number: u64,
+        assignees: Vec<String>,
+    },
+    LabelsAdded {
+        number: u64,
+        labels: Vec<String>,
+    },
+    LabelsRemoved {
+        number: u64,
+        labels: Vec<String>,
+    },
+    /// Only the parts that were changed have their old value.
+    PullRequestEdited {
+        number: u64,
+        old_title: Option<String>,
+        old_body: Option<String>,
+    },
+    DraftChanged {
+        number: u64,
+        draft: bool,
+    },
+    PullRequestMerged {
+        number: u64,
+    },
+    PullRequestClosed {
+        number: u64,
+    },
+    PullRequestReopened {
+        number: u64,
+    },
+    BranchDeleted {
+        branch: String,
+        /// Where the branch pointed to, if it is known.
+        old: Option<String>,
+    },
+    BranchPushed {
+        branch: String,
+        old: Option<String>,
+        new: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub command: String,
+    pub op_before: String,
+    /// Not set while the command is still running, or if it never finished.
+    pub op_after: Option<String>,
+    pub mutations: Vec<Mutation>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Entries {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(jj: &crate::jj::Jujutsu) -> Self {
+        Self {
+            path: jj.git_repo.path().join("spr-journal.json"),
+        }
+    }
+
+    fn load(&self) -> Result<Entries> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .context(format!("Parse journal {}", self.path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Entries::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save(&self, entries: &Entries) -> Result<()> {
+        std::fs::write(&self.path, serde_json::to_string_pretty(entries)?)
+            .context(format!("Write journal {}", self.path.display()))
+    }
+
+    /// Open a new entry for `command`, starting at jj operation `op_before`.
+    pub fn begin<S: Into<String>>(&self, command: S, op_before: String) -> Result<()> {
+        let mut entries = self.load()?;
+        entries.entries.push(Entry {
+            command: command.into(),
+            op_before,
+            op_after: None,
+            mutations: Vec::new(),
+        });
+        let excess = entries.entries.len().saturating_sub(MAX_ENTRIES);
+        entries.entries.drain(..excess);
+        self.save(&entries)
+    }
+
+    /// Add a mutation to the open entry. Does nothing if no command is being journaled.
+    pub fn record(&self, mutation: Mutation) -> Result<()> {
+        let mut entries = self.load()?;
+        match entries.entries.last_mut() {
+            Some(entry) if entry.op_after.is_none() => entry.mutations.push(mutation),
+            _ => return Ok(()),
+        }
+        self.save(&entries)
+    }
+
+    /// Close the open entry. Entries that didn't change anything are dropped.
+    pub fn finish(&self, op_after: String) -> Result<()> {
+        let mut entries = self.load()?;
+        let Some(entry) = entries.entries.last_mut() else {
+            return Ok(());
+        };
+        if entry.op_after.is_some() {
+            return Ok(());
+        }
+        if entry.mutations.is_empty() && entry.op_before == op_after {
+            entries.entries.pop();
+        } else {
+            entry.op_after = Some(op_after);
+        }
+        self.save(&entries)
+    }
+
+    pub fn last(&self) -> Result<Option<Entry>> {
+        Ok(self.load()?.entries.pop())
+    }
+
+    /// Replace the mutations of the latest entry, for when only some of them were reverted.
+    pub fn retain_last(&self, mutations: Vec<Mutation>) -> Result<()> {
+        let mut entries = self.load()?;
+        if let Some(entry) = entries.entries.last_mut() {
+            entry.mutations = mutations;
+        }
+        self.save(&entries)
+    }
+
+    pub fn pop(&self) -> Result<()> {
+        let mut entries = self.load()?;
+        entries.entries.pop();
+        self.save(&entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Journal, Mutation};
+    use crate::testing;
+
+    #[test]
+    fn records_only_open_entries() {
+        let (_temp_dir, jj, _) = testing::setup::repo_with_origin();
+        let journal = Journal::new(&jj);
+
+        journal
+            .record(Mutation::PullRequestCreated { number: 1 })
+            .expect("Recording without an entry shouldn't fail");
+        assert_eq!(journal.last().expect("Journal should load"), None);
+
+        journal
+            .begin("push", String::from("before"))
+            .expect("Begin shouldn't fail");
+        journal
+            .record(Mutation::PullRequestCreated { number: 2 })
+            .expect("Record shouldn't fail");
+        journal
+            .finish(String::from("after"))
+            .expect("Finish shouldn't fail");
+        journal
+            .record(Mutation::PullRequestCreated { number: 3 })
+            .expect("Recording after finish shouldn't fail");
+
+        let entry = journal
+            .last()
+            .expect("Journal should load")
+            .expect("Entry should exist");
+        assert_eq!(entry.command, "push");
+        assert_eq!(entry.op_after.as_deref(), Some("after"));
+        assert_eq!(
+            entry.mutations,
+            vec![Mutation::PullRequestCreated { number: 2 }]
+        );
+    }
+
+    #[test]
+    fn drops_empty_entries() {
+        let (_temp_dir, jj, _) = testing::setup::repo_with_origin();
+        let journal = Journal::new(&jj);
+
+        journal
+            .begin("status", String::from("op"))
+            .expect("Begin shouldn't fail");
+        journal
+            .finish(String::from("op"))
+            .expect("Finish shouldn't fail");
+        assert_eq!(journal.last().expect("Journal should load"), None);
+    }
+}
@@ -0,0 +1,98 @@
+//! Labels for PRs: the ones in the `Labels` section, and the ones derived by the rules in
+//! `spr.push.labels`.
+
+use crate::config::push::LabelRule;
+
+/// Labels in a comma separated `Labels` section.
+pub fn parse(section: &str) -> Vec<String> {
+    section
+        .split(',')
+        .map(|label| label.trim())
+        .filter(|label| !label.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// The conventional commit type of `title`, like `fix` in `fix(parser)!: Handle empty input`.
+fn commit_type(title: &str) -> Option<&str> {
+    lazy_regex::regex_captures!(r#"^\s*(\w+)(?:\([^)]*\))?!?:"#, title).map(|(_, t)| t)
+}
+
+fn applies(rule: &LabelRule, title: &str, paths: &[String]) -> bool {
+    let type_matches = rule
+        .commit_type
+        .as_ref()
+        .is_none_or(|wanted| commit_type(title).is_some_and(|t| t.eq_ignore_ascii_case(wanted)));
+    let paths_match = rule.paths.is_empty()
+        || rule
+            .paths
+            .iter()
+            .filter_map(|pattern| crate::codeowners::pattern_regex(pattern))
+            .any(|pattern| paths.iter().any(|path| pattern.is_match(path)));
+    type_matches && paths_match
+}
+
+/// Whether any of `rules` looks at the changed files, so they have to be looked up.
+pub fn need_paths(rules: &[LabelRule]) -> bool {
+    rules.iter().any(|rule| !rule.paths.is_empty())
+}
+
+/// Labels of all `rules` that apply to a revision with `title`, touching `paths`.
+pub fn derive(rules: &[LabelRule], title: &str, paths: &[String]) -> Vec<String> {
+    let mut ret: Vec<String> = Vec::new();
+    for rule in rules.iter().filter(|rule| applies(rule, title, paths)) {
+        if !ret.contains(&rule.label) {
+            ret.push(rule.label.clone());
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::push::LabelRule;
+
+    #[test]
+    fn derives_labels_from_rules() {
+        let rules = [
+            LabelRule {
+                label: String::from("bug"),
+                commit_type: Some(String::from("fix")),
+                paths: Vec::new(),
+            },
+            LabelRule {
+                label: String::from("docs"),
+                commit_type: None,
+                paths: vec![String::from("docs/"), String::from("*.md")],
+            },
+            LabelRule {
+                label: String::from("parser-bug"),
+                commit_type: Some(String::from("fix")),
+                paths: vec![String::from("/src/parser/")],
+            },
+        ];
+        let paths = |paths: &[&str]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            super::derive(
+                &rules,
+                "fix(parser)!: Handle empty input",
+                &paths(&["src/parser/mod.rs"])
+            ),
+            ["bug", "parser-bug"]
+        );
+        assert_eq!(
+            super::derive(&rules, "Fix: typo", &paths(&["README.md"])),
+            ["bug", "docs"]
+        );
+        assert_eq!(
+            super::derive(&rules, "feat: Add parser", &paths(&["src/parser/mod.rs"])),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn parses_sections() {
+        assert_eq!(super::parse("bug, area/parser,, "), ["bug", "area/parser"]);
+    }
+}
@@ -5,13 +5,19 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+pub mod codeowners;
 pub mod commands;
 pub mod config;
 pub mod error;
 pub mod github;
 pub mod jj;
+pub mod journal;
+pub mod labels;
 pub mod message;
 pub mod output;
+pub mod plan;
+pub mod snapshot;
+pub mod template;
 pub mod tree;
 pub mod utils;
 
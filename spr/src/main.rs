@@ -12,8 +12,9 @@
 use clap::{Parser, Subcommand};
 use jj_spr::{
     commands,
-    config::{self, get_auth_token},
+    config::{self, forge::Forge, get_auth_token, get_gitea_auth_token, get_gitlab_auth_token},
     error::{Error, Result, ResultExt},
+    github::{GHPullRequest, GitHubAdapter},
 };
 use reqwest::{self, header};
 
@@ -29,6 +30,16 @@ pub struct Cli {
     #[clap(long)]
     github_auth_token: Option<String>,
 
+    /// GitLab personal access token (if not given taken from jj config
+    /// spr.gitlabAuthToken or the GITLAB_TOKEN environment variable)
+    #[clap(long)]
+    gitlab_auth_token: Option<String>,
+
+    /// Forgejo or Gitea access token (if not given taken from jj config
+    /// spr.giteaAuthToken or the GITEA_TOKEN environment variable)
+    #[clap(long)]
+    gitea_auth_token: Option<String>,
+
     /// GitHub repository ('org/name', if not given taken from config
     /// spr.githubRepository)
     #[clap(long)]
@@ -50,6 +61,9 @@ enum Commands {
     /// repository
     Init,
 
+    /// Check the configuration, GitHub token and repository state for problems
+    Doctor,
+
     /// Create a new or update an existing Pull Request on GitHub
     Push(commands::push::PushOptions),
 
@@ -64,6 +78,53 @@ enum Commands {
 
     /// Remove the PR tracking information form a revision. E.g. to have a "clean" change after adopt.
     Detach(commands::detach::DetachOptions),
+
+    /// Merge the lowest open Pull Request of a stack and restack the rest onto trunk
+    Land(commands::land::LandOptions),
+
+    /// Show the stack with the state, reviews and checks of its Pull Requests
+    Status(commands::status::StatusOptions),
+
+    /// Mark draft Pull Requests as ready for review
+    Ready(commands::ready::ReadyOptions),
+
+    /// Close Pull Requests, delete their branches and detach the revisions from them
+    Close(commands::close::CloseOptions),
+
+    /// Show what changed in a revision since it was last pushed or reviewed
+    Diff(commands::diff::DiffOptions),
+
+    /// Delete remote spr branches that no open Pull Request or local revision uses anymore
+    Gc(commands::gc::GcOptions),
+
+    /// Revert the last spr command, locally and on GitHub as far as possible
+    Undo(commands::undo::UndoOptions),
+
+    /// Keep polling the Pull Requests of a stack and report what happens to them
+    Watch(commands::watch::WatchOptions),
+}
+
+impl Commands {
+    /// Name recorded in the undo journal. `None` for commands that aren't journaled.
+    fn journal_name(&self) -> Option<&'static str> {
+        match self {
+            Commands::Push(_) => Some("push"),
+            Commands::Sync(_) => Some("sync"),
+            Commands::Fetch(_) => Some("fetch"),
+            Commands::Adopt(_) => Some("adopt"),
+            Commands::Detach(_) => Some("detach"),
+            Commands::Land(_) => Some("land"),
+            Commands::Ready(_) => Some("ready"),
+            Commands::Close(_) => Some("close"),
+            Commands::Gc(_) => Some("gc"),
+            Commands::Init
+            | Commands::Doctor
+            | Commands::Status(_)
+            | Commands::Diff(_)
+            | Commands::Undo(_)
+            | Commands::Watch(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -78,6 +139,14 @@ pub async fn spr() -> Result<()> {
     if let Commands::Init = cli.command {
         return commands::init::init().await;
     }
+    if let Commands::Doctor = cli.command {
+        return commands::doctor::doctor(
+            cli.github_auth_token,
+            cli.gitlab_auth_token,
+            cli.gitea_auth_token,
+        )
+        .await;
+    }
 
     // Discover the Jujutsu repository and get the colocated Git repo
     let current_dir =
@@ -86,59 +155,176 @@ pub async fn spr() -> Result<()> {
         .context("could not initialize Jujutsu backend".to_owned())?;
     let git_config = jj.git_repo.config()?;
 
-    let github_auth_token = match cli.github_auth_token {
-        Some(v) => v,
-        None => get_auth_token(&git_config)
-            .ok_or_else(|| Error::new("GitHub auth token must be configured".to_string()))?,
-    };
+    let remote_name = config::remote_from_jj(&jj)?;
+    let (forge, host) = config::forge_from_jj(&jj, &remote_name)?;
+    match forge {
+        Forge::GitHub => {
+            let github_auth_token = match cli.github_auth_token {
+                Some(v) => v,
+                None => get_auth_token(&git_config).ok_or_else(|| {
+                    Error::new("GitHub auth token must be configured".to_string())
+                })?,
+            };
+
+            let api_url = config::github_api_url(&jj, &host);
+            let client = |base_uri: String| {
+                octocrab::OctocrabBuilder::default()
+                    .personal_token(github_auth_token.clone())
+                    .base_uri(base_uri)
+                    .context(String::from("Creating GH client"))?
+                    .build()
+                    .context(String::from("Creating GH client"))
+            };
+            let crab = client(api_url.clone())?;
+            let graphql = client(config::github_graphql_url(&api_url))?;
 
-    let crab = octocrab::OctocrabBuilder::default()
-        .personal_token(github_auth_token.clone())
-        .build()
-        .context(String::from("Creating GH client"))?;
-
-    let mut headers = header::HeaderMap::new();
-    headers.insert(
-        header::ACCEPT,
-        "application/json"
-            .parse()
-            .expect("Shouldn't have issues parsing literal"),
-    );
-    headers.insert(
-        header::USER_AGENT,
-        format!("spr/{}", env!("CARGO_PKG_VERSION"))
-            .try_into()
-            .expect("Shouldn't have issues parsing literal"),
-    );
-    headers.insert(
-        header::AUTHORIZATION,
-        format!("Bearer {}", github_auth_token).parse()?,
-    );
-
-    let config = config::from_jj(&jj, async || {
-        let user = crab
-            .current()
-            .user()
+            let mut headers = header::HeaderMap::new();
+            headers.insert(
+                header::ACCEPT,
+                "application/json"
+                    .parse()
+                    .expect("Shouldn't have issues parsing literal"),
+            );
+            headers.insert(
+                header::USER_AGENT,
+                format!("spr/{}", env!("CARGO_PKG_VERSION"))
+                    .try_into()
+                    .expect("Shouldn't have issues parsing literal"),
+            );
+            headers.insert(
+                header::AUTHORIZATION,
+                format!("Bearer {}", github_auth_token).parse()?,
+            );
+
+            let config = config::from_jj(&jj, async || {
+                let user = crab
+                    .current()
+                    .user()
+                    .await
+                    .context(String::from("Get current user from github"))?;
+                Ok(user.login)
+            })
             .await
-            .context(String::from("Get current user from github"))?;
-        Ok(user.login)
-    })
-    .await
-    .context(String::from("Read configuration"))?;
-    let mut gh = jj_spr::github::GitHub::new(config.clone(), crab);
-
-    match cli.command {
-        Commands::Fetch(opts) => commands::fetch::fetch(opts, &mut jj, &mut gh, &config).await?,
-        Commands::Adopt(opts) => commands::adopt::adopt(opts, &mut jj, &mut gh, &config).await?,
-        Commands::Push(opts) => commands::push::push(&mut jj, &mut gh, &config, opts).await?,
-        Commands::Sync(opts) => commands::sync::sync(&mut jj, &mut gh, &config, opts).await?,
-        Commands::Detach(opts) => commands::detach::detach(&mut jj, &config, opts).await?,
-        // The following commands are executed above and return from this
-        // function before it reaches this match.
-        Commands::Init => (),
+            .context(String::from("Read configuration"))?;
+            let mut gh = jj_spr::github::GitHub::new(config.clone(), crab).with_graphql(graphql);
+            run(cli.command, &mut jj, &mut gh, &config).await
+        }
+        Forge::GitLab => {
+            let gitlab_auth_token = match cli.gitlab_auth_token {
+                Some(v) => v,
+                None => get_gitlab_auth_token(&git_config).ok_or_else(|| {
+                    Error::new("GitLab auth token must be configured".to_string())
+                })?,
+            };
+
+            let crab = octocrab::OctocrabBuilder::default()
+                .personal_token(gitlab_auth_token)
+                .base_uri(config::gitlab_api_url(&jj, &host))
+                .context(String::from("Creating GitLab client"))?
+                .build()
+                .context(String::from("Creating GitLab client"))?;
+
+            let config = config::from_jj(&jj, async || {
+                jj_spr::github::gitlab::current_user(&crab)
+                    .await
+                    .context(String::from("Get current user from GitLab"))
+            })
+            .await
+            .context(String::from("Read configuration"))?;
+            let mut gl = jj_spr::github::GitLab::new(config.clone(), crab);
+            run(cli.command, &mut jj, &mut gl, &config).await
+        }
+        Forge::Gitea => {
+            let gitea_auth_token = match cli.gitea_auth_token {
+                Some(v) => v,
+                None => get_gitea_auth_token(&git_config)
+                    .ok_or_else(|| Error::new("Gitea auth token must be configured".to_string()))?,
+            };
+
+            let crab = octocrab::OctocrabBuilder::default()
+                .personal_token(gitea_auth_token)
+                .base_uri(config::gitea_api_url(&jj, &host))
+                .context(String::from("Creating Gitea client"))?
+                .build()
+                .context(String::from("Creating Gitea client"))?;
+
+            let config = config::from_jj(&jj, async || {
+                jj_spr::github::gitea::current_user(&crab)
+                    .await
+                    .context(String::from("Get current user from Gitea"))
+            })
+            .await
+            .context(String::from("Read configuration"))?;
+            let mut gt = jj_spr::github::Gitea::new(config.clone(), crab);
+            run(cli.command, &mut jj, &mut gt, &config).await
+        }
+        Forge::Plugin { ref command } => {
+            let mut plugin = jj_spr::github::plugin::Process::spawn(command)?;
+            let (repo, owner) = config::repo_and_owner_from_jj(&jj, &remote_name, forge.clone())?;
+            let user = plugin
+                .initialize(&owner, &repo, &remote_name)
+                .await
+                .context(String::from("Initialize forge plugin"))?;
+
+            let config = config::from_jj(&jj, async || Ok(user))
+                .await
+                .context(String::from("Read configuration"))?;
+            run(cli.command, &mut jj, &mut plugin, &config).await
+        }
+    }
+}
+
+/// Run one of the commands that talk to the forge.
+async fn run<G, PR>(
+    command: Commands,
+    jj: &mut jj_spr::jj::Jujutsu,
+    gh: &mut G,
+    config: &config::Config,
+) -> Result<()>
+where
+    PR: GHPullRequest + Send,
+    for<'a> &'a mut G: GitHubAdapter<PRAdapter = PR>,
+{
+    if let Commands::Undo(opts) = command {
+        return commands::undo::undo(jj, gh, config, opts).await;
+    }
+
+    let journal = jj_spr::journal::Journal::new(jj);
+    let journal_name = command.journal_name();
+    if let Some(name) = journal_name {
+        journal.begin(name, jj.current_operation()?)?;
+    }
+    let mut gh = jj_spr::github::Journaled::new(gh, journal.clone())
+        .with_remote(jj.git_repo.path(), config.push_remote());
+
+    let result = match command {
+        Commands::Fetch(opts) => commands::fetch::fetch(opts, jj, &mut gh, config).await,
+        Commands::Adopt(opts) => commands::adopt::adopt(opts, jj, &mut gh, config).await,
+        Commands::Push(opts) => commands::push::push(jj, &mut gh, config, opts).await,
+        Commands::Sync(opts) => commands::sync::sync(jj, &mut gh, config, opts).await,
+        Commands::Detach(opts) => commands::detach::detach(jj, config, opts).await,
+        Commands::Land(opts) => commands::land::land(jj, &mut gh, config, opts).await,
+        Commands::Status(opts) => commands::status::status(jj, &mut gh, config, opts).await,
+        Commands::Ready(opts) => commands::ready::ready(jj, &mut gh, config, opts).await,
+        Commands::Close(opts) => commands::close::close(jj, &mut gh, config, opts).await,
+        Commands::Diff(opts) => commands::diff::diff(jj, &mut gh, config, opts).await,
+        Commands::Gc(opts) => commands::gc::gc(jj, &mut gh, config, opts).await,
+        Commands::Watch(opts) => {
+            // Spelled out, since the bound on `G` is a candidate for the watched adapter as well.
+            commands::watch::watch::<jj_spr::github::Journaled<_>, _>(jj, &mut gh, config, opts)
+                .await
+        }
+        // The following commands are executed before and return from `spr`
+        // before it reaches this function.
+        Commands::Init | Commands::Doctor | Commands::Undo(_) => Ok(()),
     };
 
-    Ok::<_, Error>(())
+    // Close the entry even if the command failed half way, so its changes can still be undone.
+    if journal_name.is_some() {
+        journal.finish(jj.current_operation()?)?;
+    }
+
+    result
 }
 
 #[tokio::main]
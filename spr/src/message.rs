@@ -12,11 +12,17 @@ pub type MessageSectionsMap = std::collections::BTreeMap<MessageSection, String>
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub enum MessageSection {
     Assignees,
+    Draft,
+    Labels,
     LastCommit,
     PullRequest,
     ReviewedBy,
     Reviewers,
     Summary,
+    Synced,
+    SyncedLabels,
+    SyncedReviewers,
+    Template,
     Title,
 }
 
@@ -25,11 +31,17 @@ pub fn message_section_label(section: &MessageSection) -> &'static str {
 
     match section {
         Assignees => "Assignees",
+        Draft => "Draft",
+        Labels => "Labels",
         LastCommit => "Last Commit",
         PullRequest => "Pull Request",
         ReviewedBy => "Reviewed By",
         Reviewers => "Reviewers",
         Summary => "Summary",
+        Synced => "Synced",
+        SyncedLabels => "Synced Labels",
+        SyncedReviewers => "Synced Reviewers",
+        Template => "Template",
         Title => "Title",
     }
 }
@@ -39,12 +51,19 @@ pub fn message_section_by_label(label: &str) -> Option<MessageSection> {
 
     match &label.to_ascii_lowercase()[..] {
         "assignees" => Some(Assignees),
+        "draft" => Some(Draft),
+        "label" => Some(Labels),
+        "labels" => Some(Labels),
         "last commit" => Some(LastCommit),
         "pull request" => Some(PullRequest),
         "reviewed by" => Some(ReviewedBy),
         "reviewer" => Some(Reviewers),
         "reviewers" => Some(Reviewers),
         "summary" => Some(Summary),
+        "synced" => Some(Synced),
+        "synced labels" => Some(SyncedLabels),
+        "synced reviewers" => Some(SyncedReviewers),
+        "template" => Some(Template),
         "title" => Some(Title),
         _ => None,
     }
@@ -155,9 +174,15 @@ pub fn build_commit_message(section_texts: &MessageSectionsMap) -> String {
             MessageSection::Summary,
             MessageSection::Reviewers,
             MessageSection::Assignees,
+            MessageSection::Labels,
+            MessageSection::Draft,
+            MessageSection::Template,
             MessageSection::ReviewedBy,
             MessageSection::PullRequest,
             MessageSection::LastCommit,
+            MessageSection::Synced,
+            MessageSection::SyncedLabels,
+            MessageSection::SyncedReviewers,
         ],
     )
 }
@@ -178,6 +203,18 @@ pub fn build_github_body_for_merging(section_texts: &MessageSectionsMap) -> Stri
     )
 }
 
+/// Value of a `Draft` section.
+pub fn parse_draft(value: &str) -> Result<bool> {
+    match &value.trim().to_ascii_lowercase()[..] {
+        "yes" | "true" => Ok(true),
+        "no" | "false" => Ok(false),
+        _ => Err(Error::new(format!(
+            "Draft has to be yes or no, not '{}'",
+            value.trim()
+        ))),
+    }
+}
+
 pub fn validate_commit_message(
     config: &crate::config::Config,
     message: &MessageSectionsMap,
@@ -190,6 +227,12 @@ pub fn validate_commit_message(
         crate::output::output(&config.icons.error, "Commit message does not have a title!")?;
         return Err(Error::empty());
     }
+    if let Some(Err(err)) = message.get(&MessageSection::Draft).map(|d| parse_draft(d)) {
+        for message in err.messages() {
+            crate::output::output(&config.icons.error, message)?;
+        }
+        return Err(Error::empty());
+    }
 
     Ok(())
 }
@@ -0,0 +1,263 @@
+//! Plans describe what a command would do, without doing it.
+//! Commands build a plan instead of touching jj or GitHub when run with `--dry-run`.
+
+use serde::Serialize;
+
+use crate::error::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum PlanFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    Fetch,
+    Fix {
+        revisions: String,
+    },
+    PushBranch {
+        revision: String,
+        branch: String,
+    },
+    CreatePullRequest {
+        revision: String,
+        title: String,
+        head: String,
+        base: String,
+        draft: bool,
+    },
+    RetargetPullRequest {
+        number: u64,
+        from: String,
+        to: String,
+    },
+    UpdateComment {
+        number: u64,
+    },
+    SetDraft {
+        number: u64,
+        draft: bool,
+    },
+    UpdateLabels {
+        number: u64,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    EditPullRequest {
+        number: u64,
+        title: bool,
+        summary: bool,
+    },
+    UpdateReviewers {
+        number: u64,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    UpdateAssignees {
+        number: u64,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    UpdateDescription {
+        revision: String,
+    },
+    MergeCode {
+        revision: String,
+        commit: String,
+    },
+    Rebase {
+        revisions: String,
+        onto: String,
+    },
+    Abandon {
+        revision: String,
+        /// Not set for revisions that landed without a PR spr knows about.
+        number: Option<u64>,
+    },
+    DeleteBranch {
+        branch: String,
+    },
+    Detach {
+        revision: String,
+        number: u64,
+    },
+    ReopenPullRequest {
+        number: u64,
+    },
+}
+
+fn write_changes(
+    f: &mut std::fmt::Formatter<'_>,
+    what: &str,
+    number: u64,
+    added: &[String],
+    removed: &[String],
+) -> std::fmt::Result {
+    write!(f, "Update {} of #{}", what, number)?;
+    if !added.is_empty() {
+        write!(f, ", add {}", added.join(", "))?;
+    }
+    if !removed.is_empty() {
+        write!(f, ", remove {}", removed.join(", "))?;
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Fetch => write!(f, "Fetch from the remote"),
+            Action::Fix { revisions } => write!(f, "Run `jj fix` on {}", revisions),
+            Action::PushBranch { revision, branch } => {
+                write!(f, "Push {} to branch {}", revision, branch)
+            }
+            Action::CreatePullRequest {
+                revision,
+                title,
+                head,
+                base,
+                draft,
+            } => write!(
+                f,
+                "Create {}pull request '{}' for {} ({} -> {})",
+                if *draft { "draft " } else { "" },
+                title,
+                revision,
+                head,
+                base
+            ),
+            Action::RetargetPullRequest { number, from, to } => {
+                write!(f, "Retarget #{} from {} to {}", number, from, to)
+            }
+            Action::UpdateComment { number } => write!(f, "Update stack comment on #{}", number),
+            Action::SetDraft {
+                number,
+                draft: true,
+            } => write!(f, "Convert #{} to a draft", number),
+            Action::SetDraft {
+                number,
+                draft: false,
+            } => write!(f, "Mark #{} ready for review", number),
+            Action::UpdateLabels {
+                number,
+                added,
+                removed,
+            } => write_changes(f, "labels", *number, added, removed),
+            Action::EditPullRequest {
+                number,
+                title,
+                summary,
+            } => write!(
+                f,
+                "Update {} of #{}",
+                match (title, summary) {
+                    (true, true) => "title and summary",
+                    (true, false) => "title",
+                    (false, _) => "summary",
+                },
+                number
+            ),
+            Action::UpdateReviewers {
+                number,
+                added,
+                removed,
+            } => write_changes(f, "reviewers", *number, added, removed),
+            Action::UpdateAssignees {
+                number,
+                added,
+                removed,
+            } => write_changes(f, "assignees", *number, added, removed),
+            Action::UpdateDescription { revision } => {
+                write!(f, "Update description of {}", revision)
+            }
+            Action::MergeCode { revision, commit } => {
+                write!(
+                    f,
+                    "Merge upstream changes up to {} into {}",
+                    commit, revision
+                )
+            }
+            Action::Rebase { revisions, onto } => write!(f, "Rebase {} onto {}", revisions, onto),
+            Action::Abandon {
+                revision,
+                number: Some(number),
+            } => write!(f, "Abandon {} (#{})", revision, number),
+            Action::Abandon {
+                revision,
+                number: None,
+            } => write!(f, "Abandon {} (already in trunk)", revision),
+            Action::DeleteBranch { branch } => write!(f, "Delete remote branch {}", branch),
+            Action::Detach { revision, number } => {
+                write!(f, "Detach {} from #{}", revision, number)
+            }
+            Action::ReopenPullRequest { number } => write!(f, "Reopen #{}", number),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Plan {
+    actions: Vec<Action>,
+}
+
+impl Plan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, action: Action) {
+        self.actions.push(action);
+    }
+
+    pub fn actions(&self) -> &[Action] {
+        self.actions.as_slice()
+    }
+
+    pub fn print(&self, config: &crate::config::Config, format: PlanFormat) -> Result<()> {
+        match format {
+            PlanFormat::Json => {
+                console::Term::stdout().write_line(&serde_json::to_string_pretty(self)?)?;
+            }
+            PlanFormat::Text if self.actions.is_empty() => {
+                crate::output::output(&config.icons.sleeping, "Nothing would be done")?;
+            }
+            PlanFormat::Text => {
+                for action in self.actions.iter() {
+                    crate::output::output(&config.icons.info, action.to_string())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, Plan};
+
+    #[test]
+    fn serializes_tagged_actions() {
+        let mut plan = Plan::new();
+        plan.push(Action::Fetch);
+        plan.push(Action::RetargetPullRequest {
+            number: 3,
+            from: String::from("spr/test/base"),
+            to: String::from("main"),
+        });
+
+        let json: serde_json::Value = serde_json::to_value(&plan).expect("Plan should serialize");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "actions": [
+                    {"action": "fetch"},
+                    {"action": "retarget_pull_request", "number": 3, "from": "spr/test/base", "to": "main"},
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Snapshots of the title and summary as they were the last time a revision and its PR agreed
+//! on them. They are kept as short hashes in the `Synced` section, so `push` and `fetch` can
+//! tell which side changed since, and only flag a conflict when both did.
+
+use git2::{ObjectType, Oid};
+
+use crate::{
+    error::Result,
+    message::{MessageSection, MessageSectionsMap},
+};
+
+const HASH_LENGTH: usize = 12;
+
+/// How a field compares between the revision and its PR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Merge {
+    InSync,
+    /// Only the revision changed since the last sync.
+    Local,
+    /// Only the PR changed since the last sync.
+    Remote,
+    /// Both changed, and not in the same way.
+    Conflict,
+    /// They differ, and there is no snapshot to tell which side changed. Both `push` and
+    /// `fetch` take the PR's copy then, like an edit on GitHub.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Changes {
+    pub title: Merge,
+    pub summary: Merge,
+}
+
+impl Changes {
+    /// Names of the fields that changed on both sides.
+    pub fn conflicts(&self) -> impl Iterator<Item = &'static str> {
+        [("title", self.title), ("summary", self.summary)]
+            .into_iter()
+            .filter(|(_, merge)| *merge == Merge::Conflict)
+            .map(|(name, _)| name)
+    }
+}
+
+/// GitHub turns line endings into `\r\n` when a body is edited on the website.
+fn normalize(text: &str) -> String {
+    text.replace("\r\n", "\n").trim().to_string()
+}
+
+fn hash(text: &str) -> Result<String> {
+    let oid = Oid::hash_object(ObjectType::Blob, normalize(text).as_bytes())?;
+    Ok(oid.to_string()[..HASH_LENGTH].to_string())
+}
+
+fn merge(base: Option<&str>, local: &str, remote: &str) -> Result<Merge> {
+    if normalize(local) == normalize(remote) {
+        return Ok(Merge::InSync);
+    }
+    let Some(base) = base else {
+        return Ok(Merge::Unknown);
+    };
+    Ok(match (hash(local)? != base, hash(remote)? != base) {
+        (true, false) => Merge::Local,
+        (false, true) => Merge::Remote,
+        _ => Merge::Conflict,
+    })
+}
+
+/// The hashes in the `Synced` section of `message`, title first.
+fn read(message: &MessageSectionsMap) -> Option<(&str, &str)> {
+    let mut hashes = message.get(&MessageSection::Synced)?.split_whitespace();
+    Some((hashes.next()?, hashes.next()?))
+}
+
+fn local(message: &MessageSectionsMap, section: MessageSection) -> &str {
+    message.get(&section).map_or("", |s| s.as_str())
+}
+
+/// The PR's copy of the title and summary, in the form they take in a description.
+pub fn remote<PR: crate::github::GHPullRequest>(pr: &PR) -> (String, String) {
+    (
+        normalize(pr.title()),
+        normalize(crate::template::strip(pr.body())),
+    )
+}
+
+/// Compare the title and summary of `message` with the PR's.
+pub fn compare(message: &MessageSectionsMap, title: &str, summary: &str) -> Result<Changes> {
+    let base = read(message);
+    Ok(Changes {
+        title: merge(
+            base.map(|(title, _)| title),
+            local(message, MessageSection::Title),
+            title,
+        )?,
+        summary: merge(
+            base.map(|(_, summary)| summary),
+            local(message, MessageSection::Summary),
+            summary,
+        )?,
+    })
+}
+
+/// Record the fields that now match the PR's `title` and `summary` in the `Synced` section.
+/// The others keep their last synced state, so the difference shows up again next time.
+/// Without one, the revision's copy is recorded, so the PR's reads as the edit.
+pub fn update(message: &mut MessageSectionsMap, title: &str, summary: &str) -> Result<()> {
+    let base = read(message);
+    let next = |base: Option<&str>, local: &str, remote: &str| -> Result<String> {
+        match base {
+            _ if normalize(local) == normalize(remote) => hash(remote),
+            Some(base) => Ok(base.to_string()),
+            None => hash(local),
+        }
+    };
+    let title = next(
+        base.map(|(title, _)| title),
+        local(message, MessageSection::Title),
+        title,
+    )?;
+    let summary = next(
+        base.map(|(_, summary)| summary),
+        local(message, MessageSection::Summary),
+        summary,
+    )?;
+    message.insert(MessageSection::Synced, format!("{} {}", title, summary));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Changes, Merge, compare, update};
+    use crate::message::{MessageSection, MessageSectionsMap};
+
+    fn message(title: &str, summary: &str) -> MessageSectionsMap {
+        [
+            (MessageSection::Title, title.to_string()),
+            (MessageSection::Summary, summary.to_string()),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn tells_sides_apart() {
+        let mut synced = message("Title", "Summary");
+        update(&mut synced, "Title", "Summary").expect("Hashing shouldn't fail");
+
+        let mut local = synced.clone();
+        local.insert(MessageSection::Title, String::from("New title"));
+        assert_eq!(
+            compare(&local, "Title", "Summary\r\n").expect("Hashing shouldn't fail"),
+            Changes {
+                title: Merge::Local,
+                summary: Merge::InSync,
+            }
+        );
+        assert_eq!(
+            compare(&synced, "Title", "Edited on GitHub").expect("Hashing shouldn't fail"),
+            Changes {
+                title: Merge::InSync,
+                summary: Merge::Remote,
+            }
+        );
+        assert_eq!(
+            compare(&local, "Other title", "Summary").expect("Hashing shouldn't fail"),
+            Changes {
+                title: Merge::Conflict,
+                summary: Merge::InSync,
+            }
+        );
+        assert_eq!(
+            compare(&message("Title", "Summary"), "Other title", "Summary")
+                .expect("Hashing shouldn't fail"),
+            Changes {
+                title: Merge::Unknown,
+                summary: Merge::InSync,
+            }
+        );
+    }
+
+    #[test]
+    fn takes_remote_without_snapshot() {
+        let mut legacy = message("Title", "Summary");
+        update(&mut legacy, "Other title", "Summary").expect("Hashing shouldn't fail");
+
+        assert_eq!(
+            compare(&legacy, "Other title", "Summary").expect("Hashing shouldn't fail"),
+            Changes {
+                title: Merge::Remote,
+                summary: Merge::InSync,
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_base_of_unresolved_fields() {
+        let mut synced = message("Title", "Summary");
+        update(&mut synced, "Title", "Summary").expect("Hashing shouldn't fail");
+        let mut changed = synced.clone();
+        changed.insert(MessageSection::Title, String::from("New title"));
+
+        // The title is still different on the PR, so it has to stay a local change.
+        update(&mut changed, "Title", "Summary").expect("Hashing shouldn't fail");
+        assert_eq!(
+            changed.get(&MessageSection::Synced),
+            synced.get(&MessageSection::Synced)
+        );
+        assert_eq!(
+            compare(&changed, "Title", "Summary")
+                .expect("Hashing shouldn't fail")
+                .title,
+            Merge::Local
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Pull request templates, to give new PRs the body the repository asks for.
+//! The summary goes between two markers, so `fetch` can take it out of the body again, even
+//! after the rest of the template was filled in on GitHub.
+
+use git2::Oid;
+
+use crate::error::{Error, Result};
+
+/// Where GitHub looks for templates. Names are case insensitive.
+const DIRECTORIES: [&str; 3] = [".github", "", "docs"];
+const DEFAULT_FILE: &str = "pull_request_template.md";
+const NAMED_DIRECTORY: &str = "pull_request_template";
+
+const SUMMARY_BEGIN: &str = "<!-- spr:summary -->";
+const SUMMARY_END: &str = "<!-- spr:summary-end -->";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    content: String,
+}
+
+/// Look up `path` in `tree`, ignoring case like GitHub does.
+fn find_blob(repo: &git2::Repository, tree: &git2::Tree, path: &[&str]) -> Result<Option<Oid>> {
+    let Some((name, rest)) = path.split_first() else {
+        return Ok(None);
+    };
+    let Some(entry) = tree
+        .iter()
+        .find(|entry| entry.name().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+    else {
+        return Ok(None);
+    };
+    match entry.kind() {
+        Some(git2::ObjectType::Blob) if rest.is_empty() => Ok(Some(entry.id())),
+        Some(git2::ObjectType::Tree) if !rest.is_empty() => {
+            find_blob(repo, &repo.find_tree(entry.id())?, rest)
+        }
+        _ => Ok(None),
+    }
+}
+
+impl Template {
+    pub fn new<S: Into<String>>(content: S) -> Self {
+        Self {
+            content: content.into(),
+        }
+    }
+
+    /// Read the template from the tree of `commit`. Without a `name` this is the default
+    /// template, if the repository has one. Named templates live in a `PULL_REQUEST_TEMPLATE`
+    /// directory, and it is an error if there is none by that name.
+    pub fn from_commit(
+        repo: &git2::Repository,
+        commit: Oid,
+        name: Option<&str>,
+    ) -> Result<Option<Self>> {
+        let tree = repo.find_commit(commit)?.tree()?;
+        let file = name.map(|name| match name.contains('.') {
+            true => name.to_string(),
+            false => format!("{}.md", name),
+        });
+        for directory in DIRECTORIES {
+            let path: Vec<&str> = match file {
+                Some(ref file) => vec![directory, NAMED_DIRECTORY, file],
+                None => vec![directory, DEFAULT_FILE],
+            };
+            let path: Vec<&str> = path.into_iter().filter(|c| !c.is_empty()).collect();
+            if let Some(blob) = find_blob(repo, &tree, &path)? {
+                let blob = repo.find_blob(blob)?;
+                return Ok(Some(Self::new(String::from_utf8_lossy(blob.content()))));
+            }
+        }
+
+        match name {
+            Some(name) => Err(Error::new(format!(
+                "Pull request template '{}' doesn't exist",
+                name
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// The PR body for `summary`. It replaces `placeholder`, or goes on top if the template
+    /// doesn't have one.
+    pub fn fill(&self, placeholder: &str, summary: &str) -> String {
+        let summary = format!("{}\n{}\n{}", SUMMARY_BEGIN, summary.trim(), SUMMARY_END);
+        if !placeholder.is_empty() && self.content.contains(placeholder) {
+            self.content.replacen(placeholder, &summary, 1)
+        } else {
+            format!("{}\n\n{}", summary, self.content)
+        }
+    }
+}
+
+/// The summary in a PR body made by [`Template::fill`]. Other bodies are returned as they are.
+pub fn strip(body: &str) -> &str {
+    let Some((_, rest)) = body.split_once(SUMMARY_BEGIN) else {
+        return body;
+    };
+    match rest.split_once(SUMMARY_END) {
+        Some((summary, _)) => summary.trim(),
+        None => body,
+    }
+}
+
+/// `body` with a new summary. Only the part made by [`Template::fill`] is replaced, other bodies
+/// are replaced entirely.
+pub fn replace(body: &str, summary: &str) -> String {
+    if let Some((before, rest)) = body.split_once(SUMMARY_BEGIN)
+        && let Some((_, after)) = rest.split_once(SUMMARY_END)
+    {
+        return format!(
+            "{}{}\n{}\n{}{}",
+            before,
+            SUMMARY_BEGIN,
+            summary.trim(),
+            SUMMARY_END,
+            after
+        );
+    }
+    summary.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Template, replace, strip};
+
+    #[test]
+    fn fills_placeholder() {
+        let template =
+            Template::new("## Summary\n\n<!-- summary -->\n\n## Checklist\n- [ ] Tests\n");
+        let body = template.fill("<!-- summary -->", "Fix the thing\n");
+        assert_eq!(
+            body,
+            "## Summary\n\n<!-- spr:summary -->\nFix the thing\n<!-- spr:summary-end -->\n\n## Checklist\n- [ ] Tests\n"
+        );
+        assert_eq!(strip(&body), "Fix the thing");
+        // Ticking boxes on GitHub doesn't get in the way.
+        assert_eq!(strip(&body.replace("[ ]", "[x]")), "Fix the thing");
+
+        let body = replace(&body.replace("[ ]", "[x]"), "Fix it properly");
+        assert_eq!(strip(&body), "Fix it properly");
+        assert!(body.ends_with("## Checklist\n- [x] Tests\n"));
+    }
+
+    #[test]
+    fn puts_summary_on_top_without_placeholder() {
+        let template = Template::new("## Checklist\n- [ ] Tests\n");
+        let body = template.fill("<!-- summary -->", "Fix the thing");
+        assert!(body.starts_with("<!-- spr:summary -->\nFix the thing\n"));
+        assert!(body.ends_with("## Checklist\n- [ ] Tests\n"));
+        assert_eq!(strip(&body), "Fix the thing");
+    }
+
+    #[test]
+    fn leaves_other_bodies_alone() {
+        assert_eq!(strip("Just a summary"), "Just a summary");
+        assert_eq!(replace("Just a summary", "Another one"), "Another one");
+        assert_eq!(
+            strip("<!-- spr:summary -->\nNo end"),
+            "<!-- spr:summary -->\nNo end"
+        );
+    }
+}
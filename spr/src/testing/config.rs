@@ -10,5 +10,7 @@ pub fn basic() -> crate::config::Config {
         crate::config::drawing::Drawing::default(),
         crate::config::icons::Icons::default(),
         crate::config::push::PushConfig::default(),
+        crate::config::land::LandConfig::default(),
+        crate::config::watch::WatchConfig::default(),
     )
 }
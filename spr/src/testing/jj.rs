@@ -0,0 +1,15 @@
+use crate::jj::{ChangeId, RevSet};
+
+/// Write `message` to `path` in the working copy and commit it. Returns the new revision.
+pub fn create_jujutsu_commit(jj: &mut crate::jj::Jujutsu, message: &str, path: &str) -> ChangeId {
+    let file_path = jj
+        .git_repo
+        .workdir()
+        .expect("Failed to extract workdir from JJ handle")
+        .join(path);
+    std::fs::write(&file_path, message).expect("Failed to write test file");
+
+    jj.commit(message).expect("Failed to commit revision");
+    jj.revset_to_change_id(&RevSet::current().parent())
+        .expect("Failed to get changeid of '@-'")
+}
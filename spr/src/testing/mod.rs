@@ -7,4 +7,5 @@
 
 pub mod config;
 pub mod git;
+pub mod jj;
 pub mod setup;
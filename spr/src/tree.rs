@@ -70,6 +70,43 @@ impl<T> Tree<T> {
             self.children.iter().map(|t| t.width()).sum::<usize>()
         }
     }
+
+    /// Render the tree into lines, one per node, using `line` for the node itself.
+    /// Forks are drawn as unicode-art with the characters from `drawing`.
+    pub fn draw<F>(&self, drawing: &crate::config::drawing::Drawing, line: &F) -> Vec<String>
+    where
+        F: Fn(&T) -> String,
+    {
+        // The node itself doesn't need indents.
+        // It is indented by the parent if necessary
+        let mut lines = vec![line(&self.value)];
+
+        match self.children.as_slice() {
+            [] => {}
+            [next] => {
+                lines.extend(next.draw(drawing, line));
+            }
+            // We have more than one child branch.
+            // We need to actually build an unicode-art tree
+            children => {
+                let mut child_lines = Vec::new();
+                for child in children {
+                    let indent: String =
+                        std::iter::repeat_n(drawing.space.clone(), child.width() * 2 - 1).collect();
+                    let prefix = std::iter::once(drawing.fork.clone())
+                        .chain(std::iter::repeat(drawing.cont.clone()));
+                    let new_lines = child.draw(drawing, line);
+                    let old_lines = std::iter::zip(prefix, child_lines)
+                        .map(|(i, l)| format!("{i}{indent}{l}",));
+                    child_lines = old_lines.chain(new_lines).collect();
+                }
+
+                lines.extend(child_lines);
+            }
+        }
+
+        lines
+    }
 }
 
 pub struct Forest<T>(Vec<Tree<T>>);
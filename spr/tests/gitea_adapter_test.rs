@@ -0,0 +1,141 @@
+/*
+ * Integration tests for the Forgejo and Gitea adapter, run against the mock Gitea server
+ */
+
+mod mock_gitea;
+
+use jj_spr::github::{GHPullRequest, GitHubAdapter, Gitea};
+use mock_gitea::MockGitea;
+
+fn adapter(mock: &MockGitea) -> Gitea {
+    let config = jj_spr::config::Config::new(
+        String::from("acme"),
+        String::from("widgets"),
+        String::from("origin"),
+        String::from("main"),
+        String::from("spr/alice/"),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
+    Gitea::new(config, client(mock))
+}
+
+fn client(mock: &MockGitea) -> octocrab::Octocrab {
+    octocrab::OctocrabBuilder::default()
+        .personal_token(String::from("test-token"))
+        .base_uri(mock.api_url())
+        .expect("Failed to set base URI")
+        .build()
+        .expect("Failed to build octocrab client")
+}
+
+async fn mock() -> MockGitea {
+    let mock = MockGitea::start("acme", "widgets", "alice").await;
+    mock.state().users = vec![String::from("bob"), String::from("carol")];
+    mock
+}
+
+// Push adds and removes with the PR it read before either change.
+#[tokio::test]
+async fn adds_and_removes_from_the_same_snapshot() {
+    let mock = mock().await;
+    let number = mock.open_pull_request("main", "feature", "Feature");
+    {
+        let mut state = mock.state();
+        let pr = state.pull_requests.get_mut(&number).unwrap();
+        pr.reviewers = vec![String::from("bob")];
+        pr.assignees = vec![String::from("bob")];
+    }
+    let mut gt = adapter(&mock);
+    let mut gt = &mut gt;
+    let pr = gt
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+
+    gt.add_reviewers(&pr, ["carol"])
+        .await
+        .expect("Adding reviewers shouldn't fail");
+    gt.remove_reviewers(&pr, ["bob"])
+        .await
+        .expect("Removing reviewers shouldn't fail");
+    gt.add_assignees(&pr, ["carol"])
+        .await
+        .expect("Adding assignees shouldn't fail");
+    gt.remove_assignees(&pr, ["bob"])
+        .await
+        .expect("Removing assignees shouldn't fail");
+
+    let pr = mock.pull_request(number);
+    assert_eq!(pr.reviewers, ["carol"]);
+    assert_eq!(pr.assignees, ["carol"]);
+}
+
+#[tokio::test]
+async fn editing_the_title_keeps_a_new_draft_state() {
+    let mock = mock().await;
+    let number = mock.open_pull_request("main", "feature", "Old title");
+    let mut gt = adapter(&mock);
+    let mut gt = &mut gt;
+    let pr = gt
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+    assert!(!pr.draft());
+
+    gt.set_draft(&pr, true)
+        .await
+        .expect("Converting to draft shouldn't fail");
+    gt.edit_pull_request(&pr, Some(String::from("New title")), None)
+        .await
+        .expect("Editing PR shouldn't fail");
+    assert_eq!(mock.pull_request(number).title, "WIP: New title");
+
+    let pr = gt
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+    assert!(pr.draft());
+    assert_eq!(pr.title(), "New title");
+    gt.set_draft(&pr, false)
+        .await
+        .expect("Marking ready shouldn't fail");
+    gt.edit_pull_request(&pr, Some(String::from("Newer title")), None)
+        .await
+        .expect("Editing PR shouldn't fail");
+    assert_eq!(mock.pull_request(number).title, "Newer title");
+}
+
+#[tokio::test]
+async fn checks_push_access() {
+    let mock = mock().await;
+    let crab = client(&mock);
+
+    assert!(
+        !jj_spr::github::gitea::can_push(&crab, "acme", "widgets")
+            .await
+            .expect("Reading the repository shouldn't fail"),
+        "Readers can't push"
+    );
+    mock.state().can_push = true;
+    assert!(
+        jj_spr::github::gitea::can_push(&crab, "acme", "widgets")
+            .await
+            .expect("Reading the repository shouldn't fail"),
+        "Writers can push"
+    );
+    assert!(
+        jj_spr::github::gitea::can_push(&crab, "acme", "gadgets")
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        jj_spr::github::gitea::current_user(&crab)
+            .await
+            .expect("Reading the user shouldn't fail"),
+        "alice"
+    );
+}
@@ -0,0 +1,483 @@
+/*
+ * Integration tests for the real GitHub adapter, run against the mock GitHub server
+ */
+
+mod mock_github;
+
+use jj_spr::github::{
+    CheckState, GHPullRequest, GitHub, GitHubAdapter, MergeMethod, PullRequestState, ReviewDecision,
+};
+use mock_github::{Fault, MockGitHub, Review};
+
+fn client(base_uri: String) -> octocrab::Octocrab {
+    octocrab::OctocrabBuilder::default()
+        .personal_token(String::from("test-token"))
+        .base_uri(base_uri)
+        .expect("Failed to set base URI")
+        .build()
+        .expect("Failed to build octocrab client")
+}
+
+// Built like in main, with GraphQL next to the REST API as on GitHub Enterprise Server.
+fn adapter(mock: &MockGitHub) -> GitHub {
+    let config = jj_spr::config::Config::new(
+        String::from("acme"),
+        String::from("widgets"),
+        String::from("origin"),
+        String::from("main"),
+        String::from("spr/alice/"),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
+    let api_url = mock.api_url();
+    GitHub::new(config, client(api_url.clone()))
+        .with_graphql(client(jj_spr::config::github_graphql_url(&api_url)))
+}
+
+async fn mock() -> MockGitHub {
+    let mock = MockGitHub::start("acme", "widgets", "alice").await;
+    {
+        let mut state = mock.state();
+        state.users = vec![String::from("bob"), String::from("carol")];
+        state.teams = vec![String::from("acme/core")];
+    }
+    mock
+}
+
+#[tokio::test]
+async fn creates_and_reads_pull_requests() {
+    let mock = mock().await;
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+
+    let created = gh
+        .new_pull_request(
+            "Add widget",
+            "Widgets are great",
+            "main",
+            "spr/alice/add-widget",
+            true,
+        )
+        .await
+        .expect("Creating PR shouldn't fail");
+    assert_eq!(created.pr_number(), 1);
+    assert_eq!(created.head_branch_name(), "spr/alice/add-widget");
+    assert!(created.draft());
+
+    let pr = gh.pull_request(1).await.expect("Reading PR shouldn't fail");
+    assert_eq!(pr.title(), "Add widget");
+    assert_eq!(pr.body(), "Widgets are great");
+    assert_eq!(pr.base_branch_name(), "main");
+    assert_eq!(pr.head_branch_name(), "spr/alice/add-widget");
+    assert_eq!(pr.state(), PullRequestState::Open);
+    assert!(pr.draft());
+
+    gh.pull_request(2)
+        .await
+        .expect_err("Reading a missing PR should fail");
+}
+
+#[tokio::test]
+async fn reads_review_state_and_checks() {
+    let mock = mock().await;
+    let number = mock.open_pull_request("main", "feature", "Feature");
+    {
+        let mut state = mock.state();
+        let pr = state
+            .pull_requests
+            .get_mut(&number)
+            .expect("PR should exist");
+        pr.head_commit = String::from("abc123");
+        pr.reviews.push(Review {
+            author: String::from("bob"),
+            approved: true,
+            commit: String::from("abc123"),
+        });
+        pr.reviewers.push(String::from("acme/core"));
+        pr.checks = Some(String::from("FAILURE"));
+    }
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+
+    let pr = gh
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+    assert_eq!(pr.review_decision(), Some(ReviewDecision::Approved));
+    assert_eq!(pr.reviewed_by(), ["bob"]);
+    assert_eq!(pr.requested_reviewers(), ["acme/core"]);
+    assert_eq!(pr.reviewed_commit(), Some("abc123"));
+    assert_eq!(pr.head_commit(), "abc123");
+    assert_eq!(pr.review_count(), 1);
+    assert_eq!(pr.checks(), Some(CheckState::Failure));
+}
+
+#[tokio::test]
+async fn finds_pull_requests_by_head_base_and_state() {
+    let mock = mock().await;
+    let bottom = mock.open_pull_request("main", "spr/alice/bottom", "Bottom");
+    let top = mock.open_pull_request("spr/alice/bottom", "spr/alice/top", "Top");
+    let closed = mock.open_pull_request("main", "spr/alice/closed", "Closed");
+    mock.state()
+        .pull_requests
+        .get_mut(&closed)
+        .expect("PR should exist")
+        .closed = true;
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+
+    let pr = gh
+        .pull_request_by_head("spr/alice/top")
+        .await
+        .expect("Finding PR by head shouldn't fail");
+    assert_eq!(pr.pr_number(), top);
+    gh.pull_request_by_head("spr/alice/missing")
+        .await
+        .expect_err("Finding a missing head should fail");
+
+    let on_bottom = gh
+        .open_pull_requests_by_base("spr/alice/bottom")
+        .await
+        .expect("Finding PRs by base shouldn't fail");
+    assert_eq!(
+        on_bottom
+            .iter()
+            .map(|pr| pr.pr_number())
+            .collect::<Vec<_>>(),
+        [top]
+    );
+
+    let prs = gh
+        .pull_requests([Some(top), None, Some(bottom)])
+        .await
+        .expect("Reading several PRs shouldn't fail");
+    assert_eq!(
+        prs.iter()
+            .map(|pr| pr.as_ref().map(|pr| pr.pr_number()))
+            .collect::<Vec<_>>(),
+        [Some(top), None, Some(bottom)]
+    );
+}
+
+#[tokio::test]
+async fn pages_through_open_pull_requests() {
+    let mock = mock().await;
+    for i in 0..5 {
+        mock.open_pull_request("main", &format!("branch-{}", i), "PR");
+    }
+    mock.state().page_size = Some(2);
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+
+    let open = gh
+        .open_pull_requests()
+        .await
+        .expect("Listing open PRs shouldn't fail");
+    assert_eq!(
+        open.iter().map(|pr| pr.pr_number()).collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5]
+    );
+    assert_eq!(
+        mock.requests()
+            .iter()
+            .filter(|name| *name == "OpenPullRequests")
+            .count(),
+        3
+    );
+}
+
+#[tokio::test]
+async fn updates_pull_requests() {
+    let mock = mock().await;
+    let number = mock.open_pull_request("spr/alice/parent", "spr/alice/child", "Old title");
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+    let pr = gh
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+
+    gh.edit_pull_request(&pr, Some(String::from("New title")), None)
+        .await
+        .expect("Editing PR shouldn't fail");
+    gh.set_draft(&pr, true)
+        .await
+        .expect("Converting to draft shouldn't fail");
+    gh.rebase_pr(number, "main")
+        .await
+        .expect("Changing the base shouldn't fail");
+    gh.add_labels(&pr, ["bug", "ui"])
+        .await
+        .expect("Adding labels shouldn't fail");
+    gh.remove_labels(&pr, ["bug"])
+        .await
+        .expect("Removing labels shouldn't fail");
+
+    let updated = mock.pull_request(number);
+    assert_eq!(updated.title, "New title");
+    assert!(updated.draft);
+    assert_eq!(updated.base, "main");
+    assert_eq!(updated.labels, ["ui"]);
+
+    gh.set_draft(&pr, false)
+        .await
+        .expect("Marking ready shouldn't fail");
+    assert!(!mock.pull_request(number).draft);
+}
+
+#[tokio::test]
+async fn manages_reviewers_and_assignees() {
+    let mock = mock().await;
+    let number = mock.open_pull_request("main", "feature", "Feature");
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+    let pr = gh
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+
+    gh.add_reviewers(&pr, ["bob", "@acme/core"])
+        .await
+        .expect("Requesting reviews shouldn't fail");
+    gh.add_assignees(&pr, ["carol"])
+        .await
+        .expect("Adding assignees shouldn't fail");
+    let pr = gh
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+    assert_eq!(pr.requested_reviewers(), ["bob", "acme/core"]);
+    assert_eq!(pr.assignees(), ["carol"]);
+
+    let err = gh
+        .add_reviewers(&pr, ["mallory", "acme/nobody"])
+        .await
+        .expect_err("Requesting reviews from unknown accounts should fail");
+    assert!(
+        err.to_string().contains("mallory, acme/nobody"),
+        "Unknown accounts weren't named: {}",
+        err
+    );
+    gh.add_assignees(&pr, ["mallory"])
+        .await
+        .expect_err("Assigning an unknown user should fail");
+    let err = gh
+        .add_reviewers(&pr, ["@bob/core"])
+        .await
+        .expect_err("Requesting reviews from a team of a user should fail");
+    assert!(
+        err.to_string()
+            .contains("Could not resolve to an Organization"),
+        "GitHub's error wasn't reported: {}",
+        err
+    );
+
+    gh.remove_reviewers(&pr, ["bob", "acme/core"])
+        .await
+        .expect("Removing reviewers shouldn't fail");
+    gh.remove_assignees(&pr, ["carol"])
+        .await
+        .expect("Removing assignees shouldn't fail");
+    let pr = mock.pull_request(number);
+    assert!(
+        pr.reviewers.is_empty(),
+        "Reviewers left: {:?}",
+        pr.reviewers
+    );
+    assert!(
+        pr.assignees.is_empty(),
+        "Assignees left: {:?}",
+        pr.assignees
+    );
+}
+
+#[tokio::test]
+async fn posts_and_updates_the_stack_comment() {
+    let mock = mock().await;
+    let number = mock.open_pull_request("main", "feature", "Feature");
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+
+    let pr = gh
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+    gh.update_pr_comment(&pr, "Stack v1")
+        .await
+        .expect("Posting comment shouldn't fail");
+    let pr = gh
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+    assert!(!jj_spr::github::pr_comment_outdated(&pr, "Stack v1"));
+
+    gh.update_pr_comment(&pr, "Stack v2")
+        .await
+        .expect("Updating comment shouldn't fail");
+    let comments = mock.pull_request(number).comments;
+    assert_eq!(comments.len(), 1, "Comment was posted again");
+    assert!(comments[0].body.starts_with("Stack v2"));
+    assert!(
+        mock.requests()
+            .contains(&String::from("UpdateIssueComment")),
+        "Comment wasn't updated in place"
+    );
+}
+
+#[tokio::test]
+async fn merges_closes_and_deletes() {
+    let mock = mock().await;
+    let merged = mock.open_pull_request("main", "spr/alice/merged", "Merged");
+    let closed = mock.open_pull_request("main", "spr/alice/closed", "Closed");
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+
+    let pr = gh
+        .pull_request(merged)
+        .await
+        .expect("Reading PR shouldn't fail");
+    gh.merge_pull_request(&pr, MergeMethod::Squash, "Merged (#1)", "Body")
+        .await
+        .expect("Merging shouldn't fail");
+    let pr = gh
+        .pull_request(merged)
+        .await
+        .expect("Reading PR shouldn't fail");
+    assert_eq!(pr.state(), PullRequestState::Merged);
+    assert_eq!(
+        mock.pull_request(merged).merge_method.as_deref(),
+        Some("squash")
+    );
+    gh.merge_pull_request(&pr, MergeMethod::Squash, "Again", "Body")
+        .await
+        .expect_err("Merging a merged PR should fail");
+
+    let pr = gh
+        .pull_request(closed)
+        .await
+        .expect("Reading PR shouldn't fail");
+    gh.close_pull_request(&pr)
+        .await
+        .expect("Closing shouldn't fail");
+    assert_eq!(
+        gh.pull_request(closed)
+            .await
+            .expect("Reading PR shouldn't fail")
+            .state(),
+        PullRequestState::Closed
+    );
+    gh.reopen_pull_request(&pr)
+        .await
+        .expect("Reopening shouldn't fail");
+    assert!(!mock.pull_request(closed).closed);
+
+    gh.delete_branch("spr/alice/merged")
+        .await
+        .expect("Deleting branch shouldn't fail");
+    assert_eq!(mock.state().deleted_branches, ["spr/alice/merged"]);
+    assert!(
+        mock.requests().contains(&String::from(
+            "DELETE /api/v3/repos/acme/widgets/git/refs/heads/spr/alice/merged"
+        )),
+        "Branch wasn't deleted in the repository: {:?}",
+        mock.requests()
+    );
+}
+
+#[tokio::test]
+async fn polls_with_etags() {
+    let mock = mock().await;
+    let number = mock.open_pull_request("main", "feature", "Feature");
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+
+    assert!(
+        gh.pull_request_changed(number)
+            .await
+            .expect("Polling shouldn't fail"),
+        "First poll should report a change"
+    );
+    assert!(
+        !gh.pull_request_changed(number)
+            .await
+            .expect("Polling shouldn't fail"),
+        "Unchanged PR was reported as changed"
+    );
+
+    let pr = gh
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+    gh.add_labels(&pr, ["bug"])
+        .await
+        .expect("Adding labels shouldn't fail");
+    assert!(
+        gh.pull_request_changed(number)
+            .await
+            .expect("Polling shouldn't fail"),
+        "Changed PR wasn't reported"
+    );
+}
+
+#[tokio::test]
+async fn reports_api_errors() {
+    let mock = mock().await;
+    let number = mock.open_pull_request("main", "feature", "Feature");
+    let mut gh = adapter(&mock);
+    let mut gh = &mut gh;
+    let pr = gh
+        .pull_request(number)
+        .await
+        .expect("Reading PR shouldn't fail");
+
+    mock.fail(
+        "OldComments",
+        Fault::GraphQL(String::from("Something went wrong")),
+    );
+    let err = gh
+        .pull_request(number)
+        .await
+        .expect_err("GraphQL errors should fail the request");
+    assert!(
+        err.to_string().contains("Something went wrong"),
+        "GraphQL error wasn't reported: {}",
+        err
+    );
+
+    mock.fail(
+        "UserId",
+        Fault::GraphQL(String::from("API rate limit exceeded")),
+    );
+    let err = gh
+        .add_assignees(&pr, ["bob"])
+        .await
+        .expect_err("Failed lookups should fail the request");
+    assert!(
+        err.to_string().contains("API rate limit exceeded"),
+        "Failed lookup was reported as an unknown user: {}",
+        err
+    );
+
+    mock.fail(
+        "PATCH /api/v3/repos/acme/widgets/pulls/1",
+        Fault::Status(422),
+    );
+    gh.edit_pull_request(&pr, Some(String::from("New title")), None)
+        .await
+        .expect_err("REST errors should fail the request");
+    assert_eq!(mock.pull_request(number).title, "Feature");
+
+    mock.fail("UpdatePRBase", Fault::Status(502));
+    gh.rebase_pr(number, "other")
+        .await
+        .expect_err("HTTP errors on GraphQL should fail the request");
+
+    // Faults only apply once.
+    gh.rebase_pr(number, "other")
+        .await
+        .expect("Retried request shouldn't fail");
+    assert_eq!(mock.pull_request(number).base, "other");
+}
@@ -0,0 +1,142 @@
+/*
+ * Integration tests for the GitLab adapter, run against the mock GitLab server
+ */
+
+mod mock_gitlab;
+
+use jj_spr::github::{GHPullRequest, GitHubAdapter, GitLab};
+use mock_gitlab::MockGitLab;
+
+fn adapter(mock: &MockGitLab) -> GitLab {
+    let config = jj_spr::config::Config::new(
+        String::from("acme"),
+        String::from("widgets"),
+        String::from("origin"),
+        String::from("main"),
+        String::from("spr/alice/"),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
+    GitLab::new(config, client(mock))
+}
+
+fn client(mock: &MockGitLab) -> octocrab::Octocrab {
+    octocrab::OctocrabBuilder::default()
+        .personal_token(String::from("test-token"))
+        .base_uri(mock.api_url())
+        .expect("Failed to set base URI")
+        .build()
+        .expect("Failed to build octocrab client")
+}
+
+async fn mock() -> MockGitLab {
+    let mock = MockGitLab::start("acme/widgets", "alice").await;
+    mock.state().users = vec![String::from("bob"), String::from("carol")];
+    mock
+}
+
+// Push adds and removes with the PR it read before either change.
+#[tokio::test]
+async fn adds_and_removes_from_the_same_snapshot() {
+    let mock = mock().await;
+    let iid = mock.open_merge_request("main", "feature", "Feature");
+    {
+        let mut state = mock.state();
+        let mr = state.merge_requests.get_mut(&iid).unwrap();
+        mr.reviewers = vec![String::from("bob")];
+        mr.assignees = vec![String::from("bob")];
+    }
+    let mut gl = adapter(&mock);
+    let mut gl = &mut gl;
+    let pr = gl
+        .pull_request(iid)
+        .await
+        .expect("Reading MR shouldn't fail");
+
+    gl.add_reviewers(&pr, ["carol"])
+        .await
+        .expect("Adding reviewers shouldn't fail");
+    gl.remove_reviewers(&pr, ["bob"])
+        .await
+        .expect("Removing reviewers shouldn't fail");
+    gl.add_assignees(&pr, ["carol"])
+        .await
+        .expect("Adding assignees shouldn't fail");
+    gl.remove_assignees(&pr, ["bob"])
+        .await
+        .expect("Removing assignees shouldn't fail");
+
+    let mr = mock.merge_request(iid);
+    assert_eq!(mr.reviewers, ["carol"]);
+    assert_eq!(mr.assignees, ["carol"]);
+}
+
+#[tokio::test]
+async fn editing_the_title_keeps_a_new_draft_state() {
+    let mock = mock().await;
+    let iid = mock.open_merge_request("main", "feature", "Old title");
+    let mut gl = adapter(&mock);
+    let mut gl = &mut gl;
+    let pr = gl
+        .pull_request(iid)
+        .await
+        .expect("Reading MR shouldn't fail");
+    assert!(!pr.draft());
+
+    gl.set_draft(&pr, true)
+        .await
+        .expect("Converting to draft shouldn't fail");
+    gl.edit_pull_request(&pr, Some(String::from("New title")), None)
+        .await
+        .expect("Editing MR shouldn't fail");
+    assert_eq!(mock.merge_request(iid).title, "Draft: New title");
+
+    let pr = gl
+        .pull_request(iid)
+        .await
+        .expect("Reading MR shouldn't fail");
+    assert!(pr.draft());
+    assert_eq!(pr.title(), "New title");
+    gl.set_draft(&pr, false)
+        .await
+        .expect("Marking ready shouldn't fail");
+    gl.edit_pull_request(&pr, Some(String::from("Newer title")), None)
+        .await
+        .expect("Editing MR shouldn't fail");
+    assert_eq!(mock.merge_request(iid).title, "Newer title");
+}
+
+#[tokio::test]
+async fn checks_push_access() {
+    let mock = mock().await;
+    let crab = client(&mock);
+
+    mock.state().access_level = 20;
+    assert!(
+        !jj_spr::github::gitlab::can_push(&crab, "acme", "widgets")
+            .await
+            .expect("Reading the project shouldn't fail"),
+        "Reporters can't push"
+    );
+    mock.state().access_level = 30;
+    assert!(
+        jj_spr::github::gitlab::can_push(&crab, "acme", "widgets")
+            .await
+            .expect("Reading the project shouldn't fail"),
+        "Developers can push"
+    );
+    assert!(
+        jj_spr::github::gitlab::can_push(&crab, "acme", "gadgets")
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        jj_spr::github::gitlab::current_user(&crab)
+            .await
+            .expect("Reading the user shouldn't fail"),
+        "alice"
+    );
+}
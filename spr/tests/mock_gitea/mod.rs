@@ -0,0 +1,286 @@
+/*
+ * An in-process stand-in for the Forgejo and Gitea API, serving the pull request endpoints the
+ * `Gitea` adapter uses from in-memory state. Like Gitea, the list of assignees is only ever
+ * replaced as a whole.
+ */
+
+#![allow(dead_code)]
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+
+const TIMESTAMP: &str = "2026-01-01T00:00:00Z";
+const SHA: &str = "0000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Default)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    pub base: String,
+    pub head: String,
+    /// Logins of the requested reviewers.
+    pub reviewers: Vec<String>,
+    /// Logins of the assignees.
+    pub assignees: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    pub owner: String,
+    pub repo: String,
+    /// The user the token belongs to.
+    pub login: String,
+    /// Other users that exist.
+    pub users: Vec<String>,
+    /// Whether the token's user can push to the repository.
+    pub can_push: bool,
+    pub pull_requests: BTreeMap<u64, PullRequest>,
+    /// Method and path of the requests received so far.
+    pub requests: Vec<String>,
+}
+
+pub struct MockGitea {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for MockGitea {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl MockGitea {
+    /// Serve the repository `owner/repo` for `login` on a free local port.
+    pub async fn start(owner: &str, repo: &str, login: &str) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            login: login.to_string(),
+            ..Default::default()
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock Gitea");
+        let address = listener
+            .local_addr()
+            .expect("Failed to get address of mock Gitea");
+
+        let shared = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = shared.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| handle(state.clone(), request));
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        Self {
+            address,
+            state,
+            server,
+        }
+    }
+
+    /// Base URL of the API, for `spr.giteaApiUrl`.
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api/v1", self.address)
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("Mock Gitea state is poisoned")
+    }
+
+    pub fn pull_request(&self, number: u64) -> PullRequest {
+        self.state()
+            .pull_requests
+            .get(&number)
+            .cloned()
+            .unwrap_or_else(|| panic!("Mock Gitea has no PR #{}", number))
+    }
+
+    /// Open a PR directly, like someone else did on Gitea.
+    pub fn open_pull_request(&self, base: &str, head: &str, title: &str) -> u64 {
+        let mut state = self.state();
+        let number = state.pull_requests.len() as u64 + 1;
+        state.pull_requests.insert(
+            number,
+            PullRequest {
+                number,
+                title: title.to_string(),
+                base: base.to_string(),
+                head: head.to_string(),
+                ..Default::default()
+            },
+        );
+        number
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let body = request
+        .into_body()
+        .collect()
+        .await
+        .map(|body| body.to_bytes())
+        .unwrap_or_default();
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    let mut state = state.lock().expect("Mock Gitea state is poisoned");
+    state.requests.push(format!("{} {}", method, path));
+    let Some(route) = path.strip_prefix("/api/v1/") else {
+        return Ok(error(StatusCode::NOT_FOUND, "Not Found"));
+    };
+    let route: Vec<&str> = route.split('/').collect();
+    Ok(state.rest(&method, &route, &body))
+}
+
+fn respond(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .expect("Failed to build response")
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+    respond(status, json!({"message": message}))
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(String::from))
+        .collect()
+}
+
+fn users(logins: &[String]) -> Value {
+    Value::Array(logins.iter().map(|login| json!({"login": login})).collect())
+}
+
+impl State {
+    fn exists(&self, login: &str) -> bool {
+        self.login == login || self.users.iter().any(|user| user == login)
+    }
+
+    fn pull_request_json(&self, pr: &PullRequest) -> Value {
+        json!({
+            "id": 1000 + pr.number,
+            "number": pr.number,
+            "title": pr.title,
+            "body": pr.body,
+            "state": "open",
+            "merged": false,
+            "mergeable": true,
+            "head": {"ref": pr.head, "sha": SHA},
+            "base": {"ref": pr.base, "sha": SHA},
+            "labels": [],
+            "assignees": users(&pr.assignees),
+            "requested_reviewers": users(&pr.reviewers),
+            "requested_reviewers_teams": [],
+            "updated_at": TIMESTAMP,
+        })
+    }
+
+    fn rest(&mut self, method: &Method, route: &[&str], body: &Value) -> Response<Full<Bytes>> {
+        match (method, route) {
+            (&Method::GET, ["user"]) => respond(StatusCode::OK, json!({"login": self.login})),
+            (&Method::GET, ["users", login]) if self.exists(login) => {
+                respond(StatusCode::OK, json!({"login": login}))
+            }
+            (&Method::GET, ["repos", owner, repo])
+                if *owner == self.owner && *repo == self.repo =>
+            {
+                respond(
+                    StatusCode::OK,
+                    json!({
+                        "full_name": format!("{}/{}", self.owner, self.repo),
+                        "permissions": {"admin": false, "push": self.can_push, "pull": true},
+                    }),
+                )
+            }
+            (&Method::GET, ["repos", owner, repo, "commits", _, "status"])
+                if *owner == self.owner && *repo == self.repo =>
+            {
+                error(StatusCode::NOT_FOUND, "Not Found")
+            }
+            (&Method::GET, ["repos", owner, repo, "issues", _, "comments"])
+                if *owner == self.owner && *repo == self.repo =>
+            {
+                respond(StatusCode::OK, json!([]))
+            }
+            (_, ["repos", owner, repo, "pulls", number, rest @ ..])
+                if *owner == self.owner && *repo == self.repo =>
+            {
+                let Some(mut pr) = number
+                    .parse()
+                    .ok()
+                    .and_then(|number: u64| self.pull_requests.get(&number).cloned())
+                else {
+                    return error(StatusCode::NOT_FOUND, "Not Found");
+                };
+                match (method, rest) {
+                    (&Method::GET, []) => respond(StatusCode::OK, self.pull_request_json(&pr)),
+                    (&Method::GET, ["reviews"]) => respond(StatusCode::OK, json!([])),
+                    (&Method::PATCH, []) => {
+                        if let Some(title) = body["title"].as_str() {
+                            pr.title = title.to_string();
+                        }
+                        if let Some(text) = body["body"].as_str() {
+                            pr.body = text.to_string();
+                        }
+                        if !body["assignees"].is_null() {
+                            pr.assignees = strings(&body["assignees"]);
+                        }
+                        self.pull_requests.insert(pr.number, pr.clone());
+                        respond(StatusCode::CREATED, self.pull_request_json(&pr))
+                    }
+                    (&Method::POST, ["requested_reviewers"]) => {
+                        for login in strings(&body["reviewers"]) {
+                            if !pr.reviewers.contains(&login) {
+                                pr.reviewers.push(login);
+                            }
+                        }
+                        self.pull_requests.insert(pr.number, pr);
+                        respond(StatusCode::CREATED, json!([]))
+                    }
+                    (&Method::DELETE, ["requested_reviewers"]) => {
+                        let removed = strings(&body["reviewers"]);
+                        pr.reviewers.retain(|login| !removed.contains(login));
+                        self.pull_requests.insert(pr.number, pr);
+                        respond(StatusCode::NO_CONTENT, Value::Null)
+                    }
+                    _ => error(StatusCode::NOT_FOUND, "Not Found"),
+                }
+            }
+            _ => error(StatusCode::NOT_FOUND, "Not Found"),
+        }
+    }
+}
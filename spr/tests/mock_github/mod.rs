@@ -0,0 +1,1018 @@
+/*
+ * An in-process stand-in for the GitHub API, so the real `GitHub` adapter and the whole CLI can
+ * be tested without network. It serves the REST and GraphQL endpoints spr uses from in-memory
+ * state, records every request and fails requests on demand.
+ *
+ * Like GitHub Enterprise Server, REST is served under `/api/v3` and GraphQL at `/api/graphql`.
+ */
+
+#![allow(dead_code)]
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    HeaderMap, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
+    header::{ETAG, IF_NONE_MATCH},
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+
+const TIMESTAMP: &str = "2026-01-01T00:00:00Z";
+
+#[derive(Debug, Clone, Default)]
+pub struct Comment {
+    pub id: String,
+    pub body: String,
+    pub author: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Review {
+    pub author: String,
+    pub approved: bool,
+    /// Commit the review was left on.
+    pub commit: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    pub base: String,
+    pub head: String,
+    /// Owner of the fork the head is in, for PRs opened with an `owner:branch` head.
+    pub fork: Option<String>,
+    pub draft: bool,
+    pub closed: bool,
+    pub merged: bool,
+    /// `merge`, `squash` or `rebase`, once merged.
+    pub merge_method: Option<String>,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    /// Requested reviewers, logins or teams as `org/team`.
+    pub reviewers: Vec<String>,
+    pub reviews: Vec<Review>,
+    pub comments: Vec<Comment>,
+    /// State of the checks, like `SUCCESS` or `PENDING`.
+    pub checks: Option<String>,
+    /// Head commit, if there is no git repository to look it up in.
+    pub head_commit: String,
+    /// Bumped on every change, for ETags.
+    version: u64,
+}
+
+/// A request the server received.
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: String,
+    pub path: String,
+    /// Name of the GraphQL operation.
+    pub operation: Option<String>,
+    pub body: Value,
+}
+
+impl Recorded {
+    /// The GraphQL operation, or method and path of REST requests, like `PATCH /repos/o/r/pulls/1`.
+    pub fn name(&self) -> String {
+        match self.operation {
+            Some(ref operation) => operation.clone(),
+            None => format!("{} {}", self.method, self.path),
+        }
+    }
+}
+
+/// How to fail a request.
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// Answer with this HTTP status and a GitHub error message.
+    Status(u16),
+    /// Answer a GraphQL request with this error and no data.
+    GraphQL(String),
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    pub owner: String,
+    pub repo: String,
+    /// The user the token belongs to.
+    pub login: String,
+    /// Other users that exist.
+    pub users: Vec<String>,
+    /// Teams that exist, as `org/team`.
+    pub teams: Vec<String>,
+    pub pull_requests: BTreeMap<u64, PullRequest>,
+    pub deleted_branches: Vec<String>,
+    pub requests: Vec<Recorded>,
+    /// The repository the remote points to. Head commits are looked up and PRs merged in it.
+    pub git: Option<PathBuf>,
+    /// Page size of open PRs, instead of what the query asks for.
+    pub page_size: Option<usize>,
+    faults: Vec<(String, Fault)>,
+    comments: u64,
+}
+
+pub struct MockGitHub {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for MockGitHub {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl MockGitHub {
+    /// Serve the repository `owner/repo` for `login` on a free local port.
+    pub async fn start(owner: &str, repo: &str, login: &str) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            login: login.to_string(),
+            ..Default::default()
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock GitHub");
+        let address = listener
+            .local_addr()
+            .expect("Failed to get address of mock GitHub");
+
+        let shared = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = shared.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| handle(state.clone(), request));
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        Self {
+            address,
+            state,
+            server,
+        }
+    }
+
+    /// Base URL of the REST API, for `spr.githubApiUrl`.
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api/v3", self.address)
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("Mock GitHub state is poisoned")
+    }
+
+    /// Fail the next request with this name, see `Recorded::name`.
+    pub fn fail<S: Into<String>>(&self, name: S, fault: Fault) {
+        self.state().faults.push((name.into(), fault));
+    }
+
+    /// Names of the requests received so far.
+    pub fn requests(&self) -> Vec<String> {
+        self.state().requests.iter().map(Recorded::name).collect()
+    }
+
+    pub fn pull_request(&self, number: u64) -> PullRequest {
+        self.state()
+            .pull_requests
+            .get(&number)
+            .cloned()
+            .unwrap_or_else(|| panic!("Mock GitHub has no PR #{}", number))
+    }
+
+    /// Open a PR directly, like someone else did on GitHub.
+    pub fn open_pull_request(&self, base: &str, head: &str, title: &str) -> u64 {
+        let mut state = self.state();
+        let number = state.pull_requests.len() as u64 + 1;
+        state.pull_requests.insert(
+            number,
+            PullRequest {
+                number,
+                title: title.to_string(),
+                base: base.to_string(),
+                head: head.to_string(),
+                ..Default::default()
+            },
+        );
+        number
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let headers = request.headers().clone();
+    let body = request
+        .into_body()
+        .collect()
+        .await
+        .map(|body| body.to_bytes())
+        .unwrap_or_default();
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    let operation = match path.as_str() {
+        "/api/graphql" => body["operationName"].as_str().map(String::from),
+        _ => None,
+    };
+
+    let mut state = state.lock().expect("Mock GitHub state is poisoned");
+    let recorded = Recorded {
+        method: method.clone(),
+        path: path.clone(),
+        operation: operation.clone(),
+        body: body.clone(),
+    };
+    let name = recorded.name();
+    state.requests.push(recorded);
+
+    if let Some(index) = state.faults.iter().position(|(n, _)| *n == name) {
+        let (_, fault) = state.faults.remove(index);
+        return Ok(match fault {
+            Fault::Status(status) => error(
+                StatusCode::from_u16(status).expect("Invalid status of fault"),
+                "Injected failure",
+            ),
+            Fault::GraphQL(message) => respond(
+                StatusCode::OK,
+                json!({"data": null, "errors": [{"message": message}]}),
+            ),
+        });
+    }
+
+    Ok(match operation {
+        Some(operation) => state.graphql(&operation, &body["variables"]),
+        None if path == "/api/graphql" => error(StatusCode::BAD_REQUEST, "No operation name"),
+        None => match path.strip_prefix("/api/v3/") {
+            Some(route) => {
+                let route: Vec<String> = route.split('/').map(percent_decode).collect();
+                let route: Vec<&str> = route.iter().map(String::as_str).collect();
+                state.rest(&method, &route, &headers, &body)
+            }
+            None => error(StatusCode::NOT_FOUND, "Not Found"),
+        },
+    })
+}
+
+fn respond(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .expect("Failed to build response")
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+    respond(
+        status,
+        json!({"message": message, "documentation_url": "https://docs.github.com/rest"}),
+    )
+}
+
+fn empty(status: StatusCode) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(status)
+        .body(Full::new(Bytes::new()))
+        .expect("Failed to build response")
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match (byte, tail.get(..2)) {
+            (b'%', Some(hex)) => {
+                let hex = std::str::from_utf8(hex).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(decoded) => {
+                        bytes.push(decoded);
+                        rest = &tail[2..];
+                        continue;
+                    }
+                    Err(_) => bytes.push(byte),
+                }
+            }
+            _ => bytes.push(byte),
+        }
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(String::from))
+        .collect()
+}
+
+fn author(login: &str) -> Value {
+    let url = format!("https://api.github.com/users/{}", login);
+    json!({
+        "login": login,
+        "id": 1,
+        "node_id": format!("U_{}", login),
+        "avatar_url": "https://avatars.githubusercontent.com/u/1",
+        "gravatar_id": "",
+        "url": url,
+        "html_url": format!("https://github.com/{}", login),
+        "followers_url": format!("{}/followers", url),
+        "following_url": format!("{}/following", url),
+        "gists_url": format!("{}/gists", url),
+        "starred_url": format!("{}/starred", url),
+        "subscriptions_url": format!("{}/subscriptions", url),
+        "organizations_url": format!("{}/orgs", url),
+        "repos_url": format!("{}/repos", url),
+        "events_url": format!("{}/events", url),
+        "received_events_url": format!("{}/received_events", url),
+        "type": "User",
+        "site_admin": false,
+    })
+}
+
+impl State {
+    fn repository(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+
+    fn exists(&self, login: &str) -> bool {
+        self.login == login || self.users.iter().any(|user| user == login)
+    }
+
+    fn git(&self) -> Option<git2::Repository> {
+        self.git
+            .as_ref()
+            .map(|path| git2::Repository::open(path).expect("Failed to open git repository"))
+    }
+
+    fn commit_of(&self, branch: &str) -> Option<String> {
+        let repo = self.git()?;
+        let oid = repo.refname_to_id(&format!("refs/heads/{}", branch)).ok()?;
+        Some(oid.to_string())
+    }
+
+    fn head_commit(&self, pr: &PullRequest) -> String {
+        match pr.fork {
+            None => self.commit_of(&pr.head),
+            Some(_) => None,
+        }
+        .unwrap_or_else(|| pr.head_commit.clone())
+    }
+
+    fn by_node(&mut self, id: &str) -> Option<&mut PullRequest> {
+        let number: u64 = id.strip_prefix("PR_")?.parse().ok()?;
+        self.pull_requests.get_mut(&number)
+    }
+
+    fn label(&self, name: &str) -> Value {
+        json!({
+            "id": 1,
+            "node_id": format!("LA_{}", name),
+            "url": format!("https://api.github.com/repos/{}/labels/{}", self.repository(), name),
+            "name": name,
+            "color": "ededed",
+            "default": false,
+        })
+    }
+
+    fn rest_pull_request(&self, pr: &PullRequest) -> Value {
+        let url = format!(
+            "https://api.github.com/repos/{}/pulls/{}",
+            self.repository(),
+            pr.number
+        );
+        json!({
+            "url": url,
+            "id": pr.number,
+            "node_id": format!("PR_{}", pr.number),
+            "html_url": format!("https://github.com/{}/pull/{}", self.repository(), pr.number),
+            "number": pr.number,
+            "state": if pr.closed { "closed" } else { "open" },
+            "title": pr.title,
+            "body": pr.body,
+            "draft": pr.draft,
+            "merged": pr.merged,
+            "labels": pr.labels.iter().map(|l| self.label(l)).collect::<Vec<_>>(),
+            "head": {
+                "label": format!("{}:{}", pr.fork.as_deref().unwrap_or(&self.owner), pr.head),
+                "ref": pr.head,
+                "sha": self.head_commit(pr),
+            },
+            "base": {
+                "label": format!("{}:{}", self.owner, pr.base),
+                "ref": pr.base,
+                "sha": self.commit_of(&pr.base).unwrap_or_default(),
+            },
+        })
+    }
+
+    fn issue(&self, pr: &PullRequest) -> Value {
+        let url = format!(
+            "https://api.github.com/repos/{}/issues/{}",
+            self.repository(),
+            pr.number
+        );
+        json!({
+            "id": pr.number,
+            "node_id": format!("PR_{}", pr.number),
+            "url": url,
+            "repository_url": format!("https://api.github.com/repos/{}", self.repository()),
+            "labels_url": format!("{}/labels{{/name}}", url),
+            "comments_url": format!("{}/comments", url),
+            "events_url": format!("{}/events", url),
+            "html_url": format!("https://github.com/{}/pull/{}", self.repository(), pr.number),
+            "number": pr.number,
+            "state": if pr.closed { "closed" } else { "open" },
+            "state_reason": null,
+            "title": pr.title,
+            "body": pr.body,
+            "user": author(&self.login),
+            "labels": pr.labels.iter().map(|l| self.label(l)).collect::<Vec<_>>(),
+            "assignee": pr.assignees.first().map(|a| author(a)),
+            "assignees": pr.assignees.iter().map(|a| author(a)).collect::<Vec<_>>(),
+            "locked": false,
+            "comments": pr.comments.len(),
+            "created_at": TIMESTAMP,
+            "updated_at": TIMESTAMP,
+        })
+    }
+
+    fn graphql_pull_request(&self, pr: &PullRequest) -> Value {
+        let reviewers: Vec<Value> = pr
+            .reviewers
+            .iter()
+            .map(|reviewer| match reviewer.contains('/') {
+                true => {
+                    json!({"requestedReviewer": {"__typename": "Team", "combinedSlug": reviewer}})
+                }
+                false => json!({"requestedReviewer": {"__typename": "User", "login": reviewer}}),
+            })
+            .collect();
+        let decision = if pr.reviews.iter().any(|review| review.approved) {
+            Value::from("APPROVED")
+        } else if !pr.reviewers.is_empty() {
+            Value::from("REVIEW_REQUIRED")
+        } else {
+            Value::Null
+        };
+        json!({
+            "id": format!("PR_{}", pr.number),
+            "number": pr.number,
+            "body": pr.body,
+            "title": pr.title,
+            "baseRefName": pr.base,
+            "headRefName": pr.head,
+            "headRefOid": self.head_commit(pr),
+            "closed": pr.closed,
+            "merged": pr.merged,
+            "isDraft": pr.draft,
+            "comments": {"nodes": pr.comments.iter().map(|comment| json!({
+                "body": comment.body,
+                "id": comment.id,
+                "viewerCanUpdate": comment.author == self.login,
+            })).collect::<Vec<_>>()},
+            "assignees": {"nodes": pr.assignees.iter().map(|login| json!({
+                "id": format!("U_{}", login),
+                "login": login,
+            })).collect::<Vec<_>>()},
+            "labels": {"nodes": pr.labels.iter().map(|name| json!({"name": name})).collect::<Vec<_>>()},
+            "reviewRequests": {"nodes": reviewers},
+            "reviewDecision": decision,
+            "latestReviews": {"nodes": pr.reviews.iter().map(|review| json!({
+                "author": {"__typename": "User", "login": review.author},
+            })).collect::<Vec<_>>()},
+            "reviews": {
+                "totalCount": pr.reviews.len(),
+                "nodes": pr.reviews.last().map(|review| json!({"commit": {"oid": review.commit}})).into_iter().collect::<Vec<_>>(),
+            },
+            "mergeable": if pr.closed { "UNKNOWN" } else { "MERGEABLE" },
+            "commits": {"nodes": [{"commit": {
+                "statusCheckRollup": pr.checks.as_ref().map(|state| json!({"state": state})),
+            }}]},
+        })
+    }
+
+    fn graphql(&mut self, operation: &str, variables: &Value) -> Response<Full<Bytes>> {
+        let text = |name: &str| variables[name].as_str().unwrap_or_default().to_string();
+        if matches!(
+            operation,
+            "OldComments" | "ByHead" | "ByBase" | "OpenPullRequests"
+        ) && (text("owner") != self.owner || text("name") != self.repo)
+        {
+            return graphql_error(
+                json!({"repository": null}),
+                "NOT_FOUND",
+                &format!(
+                    "Could not resolve to a Repository with the name '{}/{}'.",
+                    text("owner"),
+                    text("name")
+                ),
+            );
+        }
+
+        let data = match operation {
+            "OldComments" => {
+                let number = variables["number"].as_u64().unwrap_or_default();
+                match self.pull_requests.get(&number) {
+                    Some(pr) => {
+                        json!({"repository": {"pullRequest": self.graphql_pull_request(pr)}})
+                    }
+                    None => {
+                        return graphql_error(
+                            json!({"repository": {"pullRequest": null}}),
+                            "NOT_FOUND",
+                            &format!(
+                                "Could not resolve to a PullRequest with the number of {}.",
+                                number
+                            ),
+                        );
+                    }
+                }
+            }
+            "ByHead" => {
+                let head = text("head");
+                let nodes: Vec<Value> = self
+                    .pull_requests
+                    .values()
+                    .filter(|pr| pr.head == head)
+                    .map(|pr| self.graphql_pull_request(pr))
+                    .collect();
+                json!({"repository": {"pullRequests": {"nodes": nodes}}})
+            }
+            "ByBase" => {
+                let base = text("base");
+                let nodes: Vec<Value> = self
+                    .pull_requests
+                    .values()
+                    .filter(|pr| pr.base == base && !pr.closed)
+                    .map(|pr| self.graphql_pull_request(pr))
+                    .collect();
+                json!({"repository": {"pullRequests": {"nodes": nodes}}})
+            }
+            "OpenPullRequests" => {
+                let open: Vec<&PullRequest> = self
+                    .pull_requests
+                    .values()
+                    .filter(|pr| !pr.closed)
+                    .collect();
+                let start: usize = variables["after"]
+                    .as_str()
+                    .and_then(|cursor| cursor.parse().ok())
+                    .unwrap_or(0);
+                let end = (start + self.page_size.unwrap_or(50)).min(open.len());
+                let nodes: Vec<Value> = open[start.min(end)..end]
+                    .iter()
+                    .map(|pr| self.graphql_pull_request(pr))
+                    .collect();
+                json!({"repository": {"pullRequests": {
+                    "pageInfo": {"hasNextPage": end < open.len(), "endCursor": end.to_string()},
+                    "nodes": nodes,
+                }}})
+            }
+            "UserId" => {
+                let login = text("login");
+                if !self.exists(&login) {
+                    return graphql_error(
+                        json!({"user": null}),
+                        "NOT_FOUND",
+                        &format!("Could not resolve to a User with the login of '{}'.", login),
+                    );
+                }
+                json!({"user": {"id": format!("U_{}", login)}})
+            }
+            "TeamId" => {
+                let org = text("org");
+                if org != self.owner
+                    && !self
+                        .teams
+                        .iter()
+                        .any(|team| team.starts_with(&format!("{}/", org)))
+                {
+                    return graphql_error(
+                        json!({"organization": null}),
+                        "NOT_FOUND",
+                        &format!(
+                            "Could not resolve to an Organization with the login of '{}'.",
+                            org
+                        ),
+                    );
+                }
+                let team = format!("{}/{}", org, text("slug"));
+                let found = self.teams.contains(&team);
+                json!({"organization": {"team": found.then(|| json!({"id": format!("T_{}", team)}))}})
+            }
+            "AddComment" => {
+                self.comments += 1;
+                let comment = Comment {
+                    id: format!("IC_{}", self.comments),
+                    body: text("body"),
+                    author: self.login.clone(),
+                };
+                let Some(pr) = self.by_node(&text("pull_request_id")) else {
+                    return not_found_node(&text("pull_request_id"));
+                };
+                pr.comments.push(comment);
+                pr.version += 1;
+                json!({"addComment": {"clientMutationId": null}})
+            }
+            "UpdateIssueComment" => {
+                let id = text("comment_id");
+                let login = self.login.clone();
+                let Some(pr) = self
+                    .pull_requests
+                    .values_mut()
+                    .find(|pr| pr.comments.iter().any(|comment| comment.id == id))
+                else {
+                    return not_found_node(&id);
+                };
+                let comment = pr
+                    .comments
+                    .iter_mut()
+                    .find(|comment| comment.id == id)
+                    .expect("Comment was just found");
+                if comment.author != login {
+                    return graphql_error(
+                        json!({"updateIssueComment": null}),
+                        "FORBIDDEN",
+                        "Resource not accessible by integration",
+                    );
+                }
+                comment.body = text("body");
+                pr.version += 1;
+                json!({"updateIssueComment": {"clientMutationId": null}})
+            }
+            "UpdatePRBase" => {
+                let branch = text("branch");
+                let Some(pr) = self.by_node(&text("pull_request_id")) else {
+                    return not_found_node(&text("pull_request_id"));
+                };
+                pr.base = branch;
+                pr.version += 1;
+                json!({"updatePullRequest": {"clientMutationId": null}})
+            }
+            "RequestReviews" => {
+                let users = strings(&variables["users"]);
+                let teams = strings(&variables["teams"]);
+                let Some(pr) = self.by_node(&text("pull_request_id")) else {
+                    return not_found_node(&text("pull_request_id"));
+                };
+                for id in users.iter().chain(teams.iter()) {
+                    let reviewer = id
+                        .strip_prefix("U_")
+                        .or_else(|| id.strip_prefix("T_"))
+                        .unwrap_or(id)
+                        .to_string();
+                    if !pr.reviewers.contains(&reviewer) {
+                        pr.reviewers.push(reviewer);
+                    }
+                }
+                pr.version += 1;
+                json!({"requestReviews": {"clientMutationId": null}})
+            }
+            "AddAssignees" => {
+                let assignees = strings(&variables["assignees"]);
+                let Some(pr) = self.by_node(&text("assignable_id")) else {
+                    return not_found_node(&text("assignable_id"));
+                };
+                for id in assignees {
+                    let login = id.strip_prefix("U_").unwrap_or(&id).to_string();
+                    if !pr.assignees.contains(&login) {
+                        pr.assignees.push(login);
+                    }
+                }
+                pr.version += 1;
+                json!({"addAssigneesToAssignable": {"clientMutationId": null}})
+            }
+            "ConvertToDraft" | "MarkReadyForReview" => {
+                let Some(pr) = self.by_node(&text("pull_request_id")) else {
+                    return not_found_node(&text("pull_request_id"));
+                };
+                pr.draft = operation == "ConvertToDraft";
+                pr.version += 1;
+                match pr.draft {
+                    true => json!({"convertPullRequestToDraft": {"clientMutationId": null}}),
+                    false => json!({"markPullRequestReadyForReview": {"clientMutationId": null}}),
+                }
+            }
+            _ => {
+                return graphql_error(
+                    Value::Null,
+                    "UNKNOWN",
+                    &format!("Mock GitHub doesn't know operation {}", operation),
+                );
+            }
+        };
+        respond(StatusCode::OK, json!({"data": data}))
+    }
+
+    fn rest(
+        &mut self,
+        method: &str,
+        route: &[&str],
+        headers: &HeaderMap,
+        body: &Value,
+    ) -> Response<Full<Bytes>> {
+        match (method, route) {
+            ("GET", ["user"]) => respond(StatusCode::OK, author(&self.login)),
+            ("DELETE", ["repos", _, _, "git", "refs", "heads", branch @ ..]) => {
+                let branch = branch.join("/");
+                if let Some(repo) = self.git()
+                    && let Ok(mut reference) =
+                        repo.find_reference(&format!("refs/heads/{}", branch))
+                {
+                    reference.delete().expect("Failed to delete branch");
+                }
+                self.deleted_branches.push(branch);
+                empty(StatusCode::NO_CONTENT)
+            }
+            (_, ["repos", owner, repo, rest @ ..]) => {
+                if *owner != self.owner || *repo != self.repo {
+                    return error(StatusCode::NOT_FOUND, "Not Found");
+                }
+                self.rest_repository(method, rest, headers, body)
+            }
+            _ => error(StatusCode::NOT_FOUND, "Not Found"),
+        }
+    }
+
+    fn rest_repository(
+        &mut self,
+        method: &str,
+        route: &[&str],
+        headers: &HeaderMap,
+        body: &Value,
+    ) -> Response<Full<Bytes>> {
+        if let ("POST", ["pulls"]) = (method, route) {
+            return self.create_pull_request(body);
+        }
+        let (kind, number, rest) = match route {
+            [kind @ ("pulls" | "issues"), number, rest @ ..] => match number.parse::<u64>() {
+                Ok(number) => (*kind, number, rest),
+                Err(_) => return error(StatusCode::NOT_FOUND, "Not Found"),
+            },
+            _ => return error(StatusCode::NOT_FOUND, "Not Found"),
+        };
+        if !self.pull_requests.contains_key(&number) {
+            return error(StatusCode::NOT_FOUND, "Not Found");
+        }
+
+        match (method, kind, rest) {
+            ("GET", "pulls", []) => {
+                let pr = &self.pull_requests[&number];
+                let etag = format!("\"{}-{}\"", number, pr.version);
+                if headers
+                    .get(IF_NONE_MATCH)
+                    .is_some_and(|value| value.as_bytes() == etag.as_bytes())
+                {
+                    return empty(StatusCode::NOT_MODIFIED);
+                }
+                let mut response = respond(StatusCode::OK, self.rest_pull_request(pr));
+                response
+                    .headers_mut()
+                    .insert(ETAG, etag.parse().expect("ETag should be a header value"));
+                response
+            }
+            ("PATCH", "pulls", []) => {
+                let pr = self
+                    .pull_requests
+                    .get_mut(&number)
+                    .expect("PR was just found");
+                if let Some(title) = body["title"].as_str() {
+                    pr.title = title.to_string();
+                }
+                if let Some(text) = body["body"].as_str() {
+                    pr.body = text.to_string();
+                }
+                match body["state"].as_str() {
+                    Some("closed") => pr.closed = true,
+                    Some("open") if pr.merged => {
+                        return error(
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            "Validation Failed: merged pull requests can't be reopened",
+                        );
+                    }
+                    Some("open") => pr.closed = false,
+                    _ => {}
+                }
+                pr.version += 1;
+                respond(
+                    StatusCode::OK,
+                    self.rest_pull_request(&self.pull_requests[&number]),
+                )
+            }
+            ("DELETE", "pulls", ["requested_reviewers"]) => {
+                let mut removed = strings(&body["reviewers"]);
+                let org = self.owner.clone();
+                removed.extend(
+                    strings(&body["team_reviewers"])
+                        .into_iter()
+                        .map(|slug| format!("{}/{}", org, slug)),
+                );
+                let pr = self
+                    .pull_requests
+                    .get_mut(&number)
+                    .expect("PR was just found");
+                pr.reviewers.retain(|reviewer| !removed.contains(reviewer));
+                pr.version += 1;
+                respond(
+                    StatusCode::OK,
+                    self.rest_pull_request(&self.pull_requests[&number]),
+                )
+            }
+            ("PUT", "pulls", ["merge"]) => self.merge(number, body),
+            ("DELETE", "issues", ["assignees"]) => {
+                let removed = strings(&body["assignees"]);
+                let pr = self
+                    .pull_requests
+                    .get_mut(&number)
+                    .expect("PR was just found");
+                pr.assignees.retain(|assignee| !removed.contains(assignee));
+                pr.version += 1;
+                respond(StatusCode::OK, self.issue(&self.pull_requests[&number]))
+            }
+            ("POST", "issues", ["labels"]) => {
+                let pr = self
+                    .pull_requests
+                    .get_mut(&number)
+                    .expect("PR was just found");
+                for label in strings(&body["labels"]) {
+                    if !pr.labels.contains(&label) {
+                        pr.labels.push(label);
+                    }
+                }
+                pr.version += 1;
+                self.labels(number)
+            }
+            ("DELETE", "issues", ["labels", name]) => {
+                let pr = self
+                    .pull_requests
+                    .get_mut(&number)
+                    .expect("PR was just found");
+                if !pr.labels.iter().any(|label| label == name) {
+                    return error(StatusCode::NOT_FOUND, "Label does not exist");
+                }
+                pr.labels.retain(|label| label != name);
+                pr.version += 1;
+                self.labels(number)
+            }
+            _ => error(StatusCode::NOT_FOUND, "Not Found"),
+        }
+    }
+
+    fn labels(&self, number: u64) -> Response<Full<Bytes>> {
+        let labels: Vec<Value> = self.pull_requests[&number]
+            .labels
+            .iter()
+            .map(|label| self.label(label))
+            .collect();
+        respond(StatusCode::OK, Value::from(labels))
+    }
+
+    fn create_pull_request(&mut self, body: &Value) -> Response<Full<Bytes>> {
+        let text = |name: &str| body[name].as_str().unwrap_or_default().to_string();
+        let head = text("head");
+        let (fork, head) = match head.split_once(':') {
+            Some((owner, branch)) => (Some(owner.to_string()), branch.to_string()),
+            None => (None, head),
+        };
+        if fork.is_none() && self.git.is_some() && self.commit_of(&head).is_none() {
+            return error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Validation Failed: head branch doesn't exist",
+            );
+        }
+        if self
+            .pull_requests
+            .values()
+            .any(|pr| !pr.closed && pr.head == head && pr.fork == fork)
+        {
+            return error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Validation Failed: A pull request already exists for this branch",
+            );
+        }
+
+        let number = self.pull_requests.len() as u64 + 1;
+        let pr = PullRequest {
+            number,
+            title: text("title"),
+            body: text("body"),
+            base: text("base"),
+            head,
+            fork,
+            draft: body["draft"].as_bool().unwrap_or(false),
+            ..Default::default()
+        };
+        let response = respond(StatusCode::CREATED, self.rest_pull_request(&pr));
+        self.pull_requests.insert(number, pr);
+        response
+    }
+
+    fn merge(&mut self, number: u64, body: &Value) -> Response<Full<Bytes>> {
+        let pr = self.pull_requests[&number].clone();
+        if pr.closed {
+            return error(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "Pull Request is not mergeable",
+            );
+        }
+        let method = body["merge_method"].as_str().unwrap_or("merge").to_string();
+
+        let mut sha = self.head_commit(&pr);
+        if let Some(repo) = self.git() {
+            match merge_branches(&repo, &pr, &method, body) {
+                Ok(oid) => sha = oid.to_string(),
+                Err(err) => return error(StatusCode::METHOD_NOT_ALLOWED, &err.to_string()),
+            }
+        }
+
+        let pr = self
+            .pull_requests
+            .get_mut(&number)
+            .expect("PR was just found");
+        pr.closed = true;
+        pr.merged = true;
+        pr.merge_method = Some(method);
+        pr.version += 1;
+        respond(
+            StatusCode::OK,
+            json!({"sha": sha, "merged": true, "message": "Pull Request successfully merged"}),
+        )
+    }
+}
+
+/// Merge the head of `pr` into its base in `repo`, like GitHub does.
+fn merge_branches(
+    repo: &git2::Repository,
+    pr: &PullRequest,
+    method: &str,
+    body: &Value,
+) -> Result<git2::Oid, git2::Error> {
+    let base_ref = format!("refs/heads/{}", pr.base);
+    let base = repo.find_reference(&base_ref)?.peel_to_commit()?;
+    let head = repo
+        .find_reference(&format!("refs/heads/{}", pr.head))?
+        .peel_to_commit()?;
+
+    let mut index = repo.merge_commits(&base, &head, None)?;
+    if index.has_conflicts() {
+        return Err(git2::Error::from_str("Merge conflict"));
+    }
+    let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+    let signature = git2::Signature::now("GitHub", "noreply@github.com")?;
+    let message = match method {
+        "rebase" => head.message().unwrap_or_default().to_string(),
+        _ => format!(
+            "{}\n\n{}",
+            body["commit_title"].as_str().unwrap_or(&pr.title),
+            body["commit_message"].as_str().unwrap_or_default()
+        ),
+    };
+    let parents = match method {
+        "merge" => vec![&base, &head],
+        _ => vec![&base],
+    };
+    repo.commit(
+        Some(&base_ref),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &parents,
+    )
+}
+
+fn graphql_error(data: Value, kind: &str, message: &str) -> Response<Full<Bytes>> {
+    respond(
+        StatusCode::OK,
+        json!({"data": data, "errors": [{"type": kind, "message": message}]}),
+    )
+}
+
+fn not_found_node(id: &str) -> Response<Full<Bytes>> {
+    graphql_error(
+        Value::Null,
+        "NOT_FOUND",
+        &format!("Could not resolve to a node with the global id of '{}'", id),
+    )
+}
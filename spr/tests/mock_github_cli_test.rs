@@ -0,0 +1,221 @@
+/*
+ * End-to-end tests of the jj-spr binary against the mock GitHub server
+ */
+
+mod mock_github;
+
+use std::{path::Path, process::Command};
+
+use mock_github::MockGitHub;
+
+fn command(program: &str, args: &[&str], dir: &Path) -> std::process::Output {
+    Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        // Keep the user's config out of the tests.
+        .env("JJ_CONFIG", "")
+        .env("JJ_USER", "Test User")
+        .env("JJ_EMAIL", "test@example.com")
+        .output()
+        .unwrap_or_else(|e| panic!("Failed to run {}: {}", program, e))
+}
+
+fn run(program: &str, args: &[&str], dir: &Path) -> std::process::Output {
+    let output = command(program, args, dir);
+    if !output.status.success() {
+        panic!(
+            "{} {:?} failed:\n{}{}",
+            program,
+            args,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    output
+}
+
+// The binary blocks, so run it off the runtime that serves the mock.
+async fn jj_spr(args: &[&str], dir: &Path) -> std::process::Output {
+    let mut full = vec!["--github-auth-token", "test-token"];
+    full.extend_from_slice(args);
+    let full: Vec<String> = full.into_iter().map(String::from).collect();
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let args: Vec<&str> = full.iter().map(String::as_str).collect();
+        command(env!("CARGO_BIN_EXE_jj-spr"), &args, &dir)
+    })
+    .await
+    .expect("Failed to wait for jj-spr")
+}
+
+async fn jj_spr_ok(args: &[&str], dir: &Path) {
+    let output = jj_spr(args, dir).await;
+    assert!(
+        output.status.success(),
+        "jj-spr {:?} failed:\n{}{}",
+        args,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// A bare `origin` with one commit on `main`, and a colocated jj clone of it that talks to
+/// `mock` as GitHub.
+fn setup(mock: &MockGitHub) -> (tempfile::TempDir, std::path::PathBuf) {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let origin = temp_dir.path().join("origin.git");
+    let clone = temp_dir.path().join("clone");
+
+    run(
+        "git",
+        &["init", "--bare", "-b", "main", origin.to_str().unwrap()],
+        temp_dir.path(),
+    );
+    run(
+        "git",
+        &["clone", origin.to_str().unwrap(), clone.to_str().unwrap()],
+        temp_dir.path(),
+    );
+    std::fs::write(clone.join("README"), "Widgets\n").expect("Failed to write README");
+    run("git", &["add", "README"], &clone);
+    run(
+        "git",
+        &[
+            "-c",
+            "user.name=Test User",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial commit",
+        ],
+        &clone,
+    );
+    run("git", &["push", "origin", "main"], &clone);
+
+    run("jj", &["git", "init", "--colocate"], &clone);
+    for (key, value) in [
+        ("revset-aliases.\"trunk()\"", "main@origin"),
+        ("spr.githubRepository", "acme/widgets"),
+        ("spr.githubApiUrl", mock.api_url().as_str()),
+        ("spr.githubMasterBranch", "main"),
+        ("spr.branchPrefix", "spr/alice/"),
+    ] {
+        run("jj", &["config", "set", "--repo", key, value], &clone);
+    }
+
+    mock.state().git = Some(origin);
+    (temp_dir, clone)
+}
+
+fn commit(clone: &Path, file: &str, message: &str) {
+    std::fs::write(clone.join(file), message).expect("Failed to write file");
+    run("jj", &["commit", "-m", message], clone);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pushes_and_lands_a_stack() {
+    let mock = MockGitHub::start("acme", "widgets", "alice").await;
+    let (_temp_dir, clone) = setup(&mock);
+    commit(&clone, "bottom", "Bottom change");
+    commit(&clone, "top", "Top change");
+
+    jj_spr_ok(&["push"], &clone).await;
+
+    let bottom = mock.pull_request(1);
+    let top = mock.pull_request(2);
+    assert_eq!(bottom.title, "Bottom change");
+    assert_eq!(bottom.base, "main");
+    assert_eq!(top.title, "Top change");
+    assert_eq!(
+        top.base, bottom.head,
+        "Top PR isn't stacked on the bottom one"
+    );
+    assert!(
+        bottom
+            .comments
+            .iter()
+            .any(|comment| comment.body.contains("acme/widgets/pull/2")),
+        "Stack comment is missing: {:?}",
+        bottom.comments
+    );
+    let log = run(
+        "jj",
+        &["log", "--no-graph", "-r", "@-", "-T", "description"],
+        &clone,
+    );
+    assert!(
+        String::from_utf8_lossy(&log.stdout).contains("Pull Request:"),
+        "Revision wasn't linked to its PR"
+    );
+
+    jj_spr_ok(&["land", "--method", "squash"], &clone).await;
+
+    let bottom = mock.pull_request(1);
+    assert!(bottom.merged, "Bottom PR wasn't merged");
+    assert_eq!(bottom.merge_method.as_deref(), Some("squash"));
+    assert_eq!(
+        mock.pull_request(2).base,
+        "main",
+        "Top PR wasn't retargeted onto main"
+    );
+    let requests = mock.requests();
+    assert!(
+        requests.contains(&String::from(
+            "PUT /api/v3/repos/acme/widgets/pulls/1/merge"
+        )),
+        "PR wasn't merged through the API: {:?}",
+        requests
+    );
+    assert!(
+        requests.contains(&String::from("UpdatePRBase")),
+        "Top PR wasn't retargeted through the API: {:?}",
+        requests
+    );
+
+    let trunk = run(
+        "jj",
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "main@origin",
+            "-T",
+            "description",
+        ],
+        &clone,
+    );
+    assert!(
+        String::from_utf8_lossy(&trunk.stdout).starts_with("Bottom change (#1)"),
+        "Squashed commit isn't on trunk"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn push_reports_github_errors() {
+    let mock = MockGitHub::start("acme", "widgets", "alice").await;
+    let (_temp_dir, clone) = setup(&mock);
+    commit(&clone, "change", "A change");
+    mock.fail(
+        "POST /api/v3/repos/acme/widgets/pulls",
+        mock_github::Fault::Status(422),
+    );
+
+    let output = jj_spr(&["push"], &clone).await;
+
+    assert!(!output.status.success(), "Push should fail");
+    assert!(
+        mock.state().pull_requests.is_empty(),
+        "PR was opened despite the error"
+    );
+    let all_output = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        all_output.contains("Injected failure"),
+        "GitHub's message wasn't shown: {}",
+        all_output
+    );
+}
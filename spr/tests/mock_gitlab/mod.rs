@@ -0,0 +1,322 @@
+/*
+ * An in-process stand-in for the GitLab API, serving the merge request endpoints the `GitLab`
+ * adapter uses from in-memory state. Like GitLab, lists of reviewers and assignees are only ever
+ * replaced as a whole.
+ */
+
+#![allow(dead_code)]
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+
+const TIMESTAMP: &str = "2026-01-01T00:00:00Z";
+
+#[derive(Debug, Clone, Default)]
+pub struct MergeRequest {
+    pub iid: u64,
+    pub title: String,
+    pub description: String,
+    pub source: String,
+    pub target: String,
+    /// Usernames of the reviewers.
+    pub reviewers: Vec<String>,
+    /// Usernames of the assignees.
+    pub assignees: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    /// Path of the project, like `group/project`.
+    pub project: String,
+    /// The user the token belongs to.
+    pub login: String,
+    /// Other users that exist.
+    pub users: Vec<String>,
+    /// Access level of the token's user in the project, like 30 for Developer.
+    pub access_level: u64,
+    pub merge_requests: BTreeMap<u64, MergeRequest>,
+    /// Method and path of the requests received so far.
+    pub requests: Vec<String>,
+}
+
+pub struct MockGitLab {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for MockGitLab {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl MockGitLab {
+    /// Serve the project `project` for `login` on a free local port.
+    pub async fn start(project: &str, login: &str) -> Self {
+        let state = Arc::new(Mutex::new(State {
+            project: project.to_string(),
+            login: login.to_string(),
+            ..Default::default()
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock GitLab");
+        let address = listener
+            .local_addr()
+            .expect("Failed to get address of mock GitLab");
+
+        let shared = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = shared.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| handle(state.clone(), request));
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        Self {
+            address,
+            state,
+            server,
+        }
+    }
+
+    /// Base URL of the API, for `spr.gitlabApiUrl`.
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api/v4", self.address)
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("Mock GitLab state is poisoned")
+    }
+
+    pub fn merge_request(&self, iid: u64) -> MergeRequest {
+        self.state()
+            .merge_requests
+            .get(&iid)
+            .cloned()
+            .unwrap_or_else(|| panic!("Mock GitLab has no MR !{}", iid))
+    }
+
+    /// Open a merge request directly, like someone else did on GitLab.
+    pub fn open_merge_request(&self, target: &str, source: &str, title: &str) -> u64 {
+        let mut state = self.state();
+        let iid = state.merge_requests.len() as u64 + 1;
+        state.merge_requests.insert(
+            iid,
+            MergeRequest {
+                iid,
+                title: title.to_string(),
+                source: source.to_string(),
+                target: target.to_string(),
+                ..Default::default()
+            },
+        );
+        iid
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().unwrap_or("").to_string();
+    let body = request
+        .into_body()
+        .collect()
+        .await
+        .map(|body| body.to_bytes())
+        .unwrap_or_default();
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    let mut state = state.lock().expect("Mock GitLab state is poisoned");
+    state.requests.push(format!("{} {}", method, path));
+    let Some(route) = path.strip_prefix("/api/v4/") else {
+        return Ok(error(StatusCode::NOT_FOUND, "404 Not Found"));
+    };
+    let route: Vec<String> = route.split('/').map(percent_decode).collect();
+    let route: Vec<&str> = route.iter().map(String::as_str).collect();
+    Ok(state.rest(&method, &route, &query, &body))
+}
+
+fn respond(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .expect("Failed to build response")
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+    respond(status, json!({"message": message}))
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if let (b'%', Some(hex)) = (byte, tail.get(..2))
+            && let Ok(decoded) = u8::from_str_radix(std::str::from_utf8(hex).unwrap_or(""), 16)
+        {
+            bytes.push(decoded);
+            rest = &tail[2..];
+            continue;
+        }
+        bytes.push(byte);
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// GitLab's own way of telling drafts, from the title.
+fn is_draft(title: &str) -> bool {
+    let title = title.trim_start().to_lowercase();
+    title.starts_with("draft:") || title.starts_with("[draft]") || title.starts_with("(draft)")
+}
+
+impl State {
+    /// Users by id, which is their position, starting at 1 with the token's owner.
+    fn everyone(&self) -> Vec<String> {
+        std::iter::once(self.login.clone())
+            .chain(self.users.iter().cloned())
+            .collect()
+    }
+
+    fn user(&self, username: &str) -> Option<Value> {
+        let id = self.everyone().iter().position(|u| u == username)? + 1;
+        Some(json!({"id": id, "username": username}))
+    }
+
+    fn username(&self, id: &Value) -> Option<String> {
+        let index = id.as_u64()?.checked_sub(1)?;
+        self.everyone().get(index as usize).cloned()
+    }
+
+    fn users(&self, names: &[String]) -> Value {
+        Value::Array(names.iter().filter_map(|name| self.user(name)).collect())
+    }
+
+    fn merge_request_json(&self, mr: &MergeRequest) -> Value {
+        json!({
+            "id": 1000 + mr.iid,
+            "iid": mr.iid,
+            "title": mr.title,
+            "description": mr.description,
+            "state": "opened",
+            "draft": is_draft(&mr.title),
+            "source_branch": mr.source,
+            "target_branch": mr.target,
+            "sha": "0000000000000000000000000000000000000000",
+            "labels": [],
+            "reviewers": self.users(&mr.reviewers),
+            "assignees": self.users(&mr.assignees),
+            "has_conflicts": false,
+            "detailed_merge_status": "mergeable",
+            "head_pipeline": null,
+            "updated_at": TIMESTAMP,
+        })
+    }
+
+    fn ids(&self, value: &Value) -> Vec<String> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.username(id))
+            .collect()
+    }
+
+    fn rest(
+        &mut self,
+        method: &Method,
+        route: &[&str],
+        query: &str,
+        body: &Value,
+    ) -> Response<Full<Bytes>> {
+        match (method, route) {
+            (&Method::GET, ["user"]) => {
+                respond(StatusCode::OK, self.user(&self.login.clone()).unwrap())
+            }
+            (&Method::GET, ["users"]) => {
+                let username = query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("username="))
+                    .map(percent_decode)
+                    .unwrap_or_default();
+                respond(
+                    StatusCode::OK,
+                    Value::Array(self.user(&username).into_iter().collect()),
+                )
+            }
+            (&Method::GET, ["projects", project]) if *project == self.project => respond(
+                StatusCode::OK,
+                json!({
+                    "path_with_namespace": self.project,
+                    "permissions": {
+                        "project_access": {"access_level": self.access_level},
+                        "group_access": null,
+                    },
+                }),
+            ),
+            (_, ["projects", project, "merge_requests", iid, rest @ ..])
+                if *project == self.project =>
+            {
+                let Some(mr) = iid
+                    .parse()
+                    .ok()
+                    .and_then(|iid: u64| self.merge_requests.get(&iid).cloned())
+                else {
+                    return error(StatusCode::NOT_FOUND, "404 Not found");
+                };
+                match (method, rest) {
+                    (&Method::GET, []) => respond(StatusCode::OK, self.merge_request_json(&mr)),
+                    (&Method::GET, ["approvals"]) => respond(
+                        StatusCode::OK,
+                        json!({"approved": false, "approvals_left": 0, "approved_by": []}),
+                    ),
+                    (&Method::GET, ["notes"]) => respond(StatusCode::OK, json!([])),
+                    (&Method::PUT, []) => {
+                        let mut mr = mr;
+                        if let Some(title) = body["title"].as_str() {
+                            mr.title = title.to_string();
+                        }
+                        if let Some(description) = body["description"].as_str() {
+                            mr.description = description.to_string();
+                        }
+                        if !body["reviewer_ids"].is_null() {
+                            mr.reviewers = self.ids(&body["reviewer_ids"]);
+                        }
+                        if !body["assignee_ids"].is_null() {
+                            mr.assignees = self.ids(&body["assignee_ids"]);
+                        }
+                        self.merge_requests.insert(mr.iid, mr.clone());
+                        respond(StatusCode::OK, self.merge_request_json(&mr))
+                    }
+                    _ => error(StatusCode::NOT_FOUND, "404 Not found"),
+                }
+            }
+            _ => error(StatusCode::NOT_FOUND, "404 Not found"),
+        }
+    }
+}